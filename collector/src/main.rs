@@ -1,15 +1,22 @@
 #![deny(clippy::all)]
 use anyhow::Context;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use clap::Parser;
 use futures::stream::{self, StreamExt};
-use rusplay::{RugplayClient, models::RewardStatus};
+use rusplay::{
+    RugplayClient, Secret, auth,
+    cookies::{PersistentCookieStore, SESSION_COOKIE_NAME},
+    models::RewardStatus,
+};
 use serde::Deserialize;
-use std::{fs::File, io::Read, time::Duration};
+use std::{fs::File, io::Read, sync::Arc, time::Duration};
 use tokio::time::sleep;
-use tracing::{Instrument, debug, error, info};
+use tracing::{Instrument, debug, error, info, warn};
 use tracing_subscriber::EnvFilter;
 
+/// Warn when the session cookie expires in less than a day
+const SESSION_EXPIRY_WARNING: Duration = Duration::from_secs(60 * 60 * 24);
+
 #[derive(Deserialize, Debug)]
 struct TokensConfig {
     pub tokens: Vec<UserCreds>,
@@ -18,8 +25,14 @@ struct TokensConfig {
 #[derive(Deserialize, Debug)]
 struct UserCreds {
     pub name: String,
-    pub api_key: String,
-    pub cookie: Option<String>,
+    pub api_key: Secret,
+    pub cookie: Option<Secret>,
+    /// Browser cookie export (`cookies.txt` or JSON) to read the session cookie from
+    pub cookie_file: Option<String>,
+    /// JSON file where refreshed cookies are kept between runs
+    pub cookie_store: Option<String>,
+    /// API base URL, e.g. a local `rusplay-mock` (default: https://rugplay.com/api/v1)
+    pub url: Option<String>,
 }
 
 #[derive(Parser)]
@@ -48,13 +61,49 @@ async fn main() -> anyhow::Result<()> {
 
     let tokens = toml::from_str::<TokensConfig>(&contents).context("Could not parse tokens")?;
 
-    stream::iter(tokens.tokens)
-        .for_each_concurrent(None, |u| {
-            let span = tracing::info_span!("collector", user = %u.name);
+    let clients: Vec<(String, RugplayClient)> = stream::iter(tokens.tokens)
+        .filter_map(|u| async move {
+            let client = match build_client(&u) {
+                Ok(client) => client,
+                Err(e) => {
+                    error!(
+                        "Rejecting {}: could not create Rugplay Client: {e:#}",
+                        u.name
+                    );
+                    return None;
+                }
+            };
+
+            match client.validate_credentials().await {
+                Ok(status) if status.is_valid() => {
+                    info!("Credentials for {} are valid", u.name);
+                    Some((u.name, client))
+                }
+                Ok(status) => {
+                    error!(
+                        "Rejecting {}: API key is {:?}, cookie is {:?}",
+                        u.name, status.token, status.cookie
+                    );
+                    None
+                }
+                Err(e) => {
+                    error!("Rejecting {}: could not validate credentials: {e}", u.name);
+                    None
+                }
+            }
+        })
+        .collect()
+        .await;
+
+    if clients.is_empty() {
+        anyhow::bail!("No valid credentials in the token file");
+    }
+
+    stream::iter(clients)
+        .for_each_concurrent(None, |(name, client)| {
+            let span = tracing::info_span!("collector", user = %name);
             async move {
-                info!("Spawning task: {}", u.name);
-                let client = RugplayClient::new(u.api_key, u.cookie, None)
-                    .expect("Could not create Rugplay Client");
+                info!("Spawning task: {}", name);
                 claim_loop(client).instrument(span).await;
             }
         })
@@ -63,8 +112,54 @@ async fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
-fn format_wait_time(ms: u64) -> String {
-    let total_seconds = ms / 1000;
+fn build_client(creds: &UserCreds) -> anyhow::Result<RugplayClient> {
+    let api_key = creds.api_key.clone();
+    let url = creds.url.as_deref();
+    let exported = creds
+        .cookie_file
+        .as_ref()
+        .map(|path| {
+            auth::load_cookie_file(path)
+                .and_then(|c| auth::session_cookies(c, auth::RUGPLAY_DOMAIN))
+                .with_context(|| format!("Could not import cookies from {path}"))
+        })
+        .transpose()?;
+
+    let Some(path) = &creds.cookie_store else {
+        return Ok(match exported {
+            Some(cookies) => RugplayClient::new_with_cookies(
+                api_key,
+                cookies
+                    .iter()
+                    .map(|c| format!("{}={}", c.name, c.value.expose())),
+                url,
+            )?,
+            None => RugplayClient::new_with_cookies(api_key, creds.cookie.clone(), url)?,
+        });
+    };
+
+    let store = PersistentCookieStore::open(path)
+        .with_context(|| format!("Could not open cookie store {path}"))?;
+
+    // The cookies from the config file are only used to seed a new store
+    if store.get(SESSION_COOKIE_NAME).is_none() {
+        if let Some(cookies) = exported {
+            auth::import_into_store(&store, &cookies)?;
+        } else if let Some(cookie) = &creds.cookie {
+            let cookie_url = url.unwrap_or("https://rugplay.com").parse()?;
+            store.add_cookie_str(cookie.expose(), &cookie_url)?;
+        }
+    }
+
+    Ok(RugplayClient::new_with_cookie_store(
+        api_key,
+        Arc::new(store),
+        url,
+    )?)
+}
+
+fn format_wait_time(wait: Duration) -> String {
+    let total_seconds = wait.as_secs();
     let hours = total_seconds / 3600;
     let minutes = (total_seconds % 3600) / 60;
 
@@ -95,8 +190,7 @@ async fn try_claim(client: &RugplayClient) -> anyhow::Result<RewardStatus> {
         format_wait_time(claim_info.time_remaining)
     );
 
-    let wait_time = claim_info.time_remaining + 5000;
-    sleep(Duration::from_millis(wait_time)).await;
+    sleep(claim_info.time_remaining + Duration::from_secs(5)).await;
 
     client
         .claim_reward()
@@ -106,6 +200,10 @@ async fn try_claim(client: &RugplayClient) -> anyhow::Result<RewardStatus> {
 
 async fn claim_loop(client: RugplayClient) {
     loop {
+        if let Err(e) = client.check_session_expiry(SESSION_EXPIRY_WARNING) {
+            warn!("{e}. Log in again and update the cookie");
+        }
+
         match try_claim(&client).await {
             Ok(r) => {
                 debug!(
@@ -113,16 +211,12 @@ async fn claim_loop(client: RugplayClient) {
                     r.reward_amount, r.new_balance, r.login_streak
                 );
 
-                let sleep_duration =
-                    parse_next_claim_time(&r.next_claim_time).unwrap_or_else(|| {
-                        info!("Rugplay did not tell the next claim time; defaulting to ~12h");
-                        Duration::from_secs(60 * 60 * 12 + 60)
-                    });
+                let sleep_duration = time_until(r.next_claim_time).unwrap_or_else(|| {
+                    info!("Rugplay did not tell the next claim time; defaulting to ~12h");
+                    Duration::from_secs(60 * 60 * 12 + 60)
+                });
 
-                debug!(
-                    "Will sleep {}",
-                    format_wait_time(sleep_duration.as_millis() as u64)
-                );
+                debug!("Will sleep {}", format_wait_time(sleep_duration));
                 sleep(sleep_duration).await;
             }
             Err(e) => {
@@ -134,17 +228,9 @@ async fn claim_loop(client: RugplayClient) {
     }
 }
 
-fn parse_next_claim_time(next_claim_time: &Option<String>) -> Option<Duration> {
-    let next_claim_str = next_claim_time.as_ref()?;
-    let next_claim_time = chrono::DateTime::parse_from_rfc3339(next_claim_str).ok()?;
-    let next_claim_time = next_claim_time.with_timezone(&Utc);
-    let now = Utc::now();
-    let wait_duration = next_claim_time.signed_duration_since(now);
-    if wait_duration.num_milliseconds() > 0 {
-        Some(Duration::from_millis(
-            wait_duration.num_milliseconds() as u64
-        ))
-    } else {
-        None
-    }
+fn time_until(time: Option<DateTime<Utc>>) -> Option<Duration> {
+    (time? - Utc::now())
+        .to_std()
+        .ok()
+        .filter(|wait| !wait.is_zero())
 }
@@ -0,0 +1,94 @@
+#![deny(clippy::all)]
+//! Runs the collector binary against a `rusplay-mock` server
+use rusplay_mock::{MockRugplay, MockServer, MockUser, SESSION_COOKIE_NAME};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::process::Command;
+use tokio::time::{Instant, sleep};
+
+async fn start() -> MockServer {
+    MockRugplay::new()
+        .with_user(MockUser::new(1, "alice", "rgpl_alice", 100.0).with_session("alice_session"))
+        .with_user(MockUser::new(2, "bob", "rgpl_bob", 500.0).with_session("bob_session"))
+        .start()
+        .await
+        .expect("Failed to start mock server")
+}
+
+/// Waits until the mock balance of `user_id` is `expected`
+async fn wait_for_balance(server: &MockServer, user_id: u64, expected: f64) {
+    let deadline = Instant::now() + Duration::from_secs(30);
+    while server.rugplay().balance(user_id) != Some(expected) {
+        assert!(
+            Instant::now() < deadline,
+            "Balance of {user_id} is {:?}, expected {expected}",
+            server.rugplay().balance(user_id)
+        );
+        sleep(Duration::from_millis(50)).await;
+    }
+}
+
+#[tokio::test]
+async fn test_collector_claims_rewards() {
+    let server = start().await;
+    let url = server.api_url();
+    let dir = std::env::temp_dir().join(format!("rusplay-collector-{}", server.addr().port()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let store = dir.join("bob.json");
+    let config = dir.join("tokens.toml");
+    std::fs::write(
+        &config,
+        format!(
+            r#"
+[[tokens]]
+name = "Alice"
+api_key = "rgpl_alice"
+cookie = "{SESSION_COOKIE_NAME}=alice_session"
+url = "{url}"
+
+[[tokens]]
+name = "Bob"
+api_key = "rgpl_bob"
+cookie = "{SESSION_COOKIE_NAME}=bob_session"
+cookie_store = "{}"
+url = "{url}"
+
+[[tokens]]
+name = "Mallory"
+api_key = "rgpl_wrong"
+cookie = "{SESSION_COOKIE_NAME}=alice_session"
+url = "{url}"
+"#,
+            store.display()
+        ),
+    )
+    .unwrap();
+
+    let mut collector = Command::new(env!("CARGO_BIN_EXE_rusplay_collector"))
+        .arg(&config)
+        .env("RUST_LOG", "info")
+        .env("NO_COLOR", "1")
+        .stdout(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .unwrap();
+
+    wait_for_balance(&server, 1, 1_100.0).await;
+    wait_for_balance(&server, 2, 1_500.0).await;
+
+    // Both wait for the next claim instead of exiting or claiming again
+    sleep(Duration::from_millis(500)).await;
+    assert!(collector.try_wait().unwrap().is_none());
+    assert_eq!(server.rugplay().balance(1), Some(1_100.0));
+    assert_eq!(server.rugplay().balance(2), Some(1_500.0));
+
+    collector.kill().await.unwrap();
+    let output = collector.wait_with_output().await.unwrap();
+    let logs = String::from_utf8_lossy(&output.stdout);
+    assert!(logs.contains("Rejecting Mallory"), "{logs}");
+    assert!(logs.contains("Credentials for Alice are valid"), "{logs}");
+
+    let stored = std::fs::read_to_string(&store).unwrap();
+    assert!(stored.contains("bob_session"), "{stored}");
+    std::fs::remove_dir_all(&dir).unwrap();
+}
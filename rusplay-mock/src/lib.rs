@@ -0,0 +1,179 @@
+#![deny(clippy::all)]
+//! In-process fake Rugplay server
+//!
+//! [`MockRugplay`] simulates the Rugplay API on localhost: coins trade against real
+//! constant-product pools, users have balances and holdings, and daily rewards respect their
+//! cooldown. Point a `rusplay::RugplayClient` at [`MockServer::api_url`] to test clients and
+//! bots end to end.
+//!
+//! ```no_run
+//! use rusplay_mock::{MockCoin, MockRugplay, MockUser};
+//!
+//! # async fn run() -> std::io::Result<()> {
+//! let server = MockRugplay::new()
+//!     .with_user(MockUser::new(1, "alice", "rgpl_alice", 10_000.0).with_session("alice_session"))
+//!     .with_coin(MockCoin::new("TEST", "Test", 1_000_000.0, 100_000.0))
+//!     .start()
+//!     .await?;
+//! println!("Rugplay API at {}", server.api_url());
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Official endpoints (`/api/v1/...`) authenticate with the user's API key, the unofficial ones
+//! (trades, rewards, session, portfolio) with its session cookie.
+use axum::Router;
+use chrono::TimeDelta;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+
+mod routes;
+mod state;
+pub use state::{ApiError, MockCoin, MockQuestion, MockUser, Side};
+
+/// Name of the session cookie the unofficial endpoints expect
+pub const SESSION_COOKIE_NAME: &str = "__Secure-better-auth.session_token";
+
+/// A fake Rugplay instance
+///
+/// Clones share the same state, so the server can be inspected and changed while it runs.
+#[derive(Debug, Clone, Default)]
+pub struct MockRugplay {
+    state: Arc<Mutex<state::State>>,
+}
+
+impl MockRugplay {
+    /// An empty instance, with a 12h claim cooldown and a $1000 daily reward
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, state::State> {
+        self.state.lock().expect("mock state poisoned")
+    }
+
+    pub fn with_coin(self, coin: MockCoin) -> Self {
+        self.state().add_coin(coin);
+        self
+    }
+
+    pub fn with_user(self, user: MockUser) -> Self {
+        self.state().add_user(user);
+        self
+    }
+
+    pub fn with_question(self, question: MockQuestion) -> Self {
+        self.state().add_question(question);
+        self
+    }
+
+    pub fn with_claim_cooldown(self, cooldown: Duration) -> Self {
+        self.state().claim_cooldown = cooldown;
+        self
+    }
+
+    pub fn with_base_reward(self, reward: u32) -> Self {
+        self.state().base_reward = reward;
+        self
+    }
+
+    /// Launches a coin while the server runs, created at the simulated time
+    pub fn launch(&self, coin: MockCoin) {
+        self.state().add_coin(coin);
+    }
+
+    /// Moves the simulated clock forward (claim cooldowns, 24h changes...)
+    pub fn advance(&self, duration: Duration) {
+        self.state().clock_offset += TimeDelta::from_std(duration).unwrap_or_default();
+    }
+
+    /// Current $ balance of a user
+    pub fn balance(&self, user_id: u64) -> Option<f64> {
+        self.state().user(user_id).map(|u| u.user.balance)
+    }
+
+    /// Quantity of `symbol` held by a user
+    pub fn holding(&self, user_id: u64, symbol: &str) -> f64 {
+        self.state()
+            .coins
+            .get(&symbol.to_uppercase())
+            .and_then(|c| c.holders.get(&user_id).copied())
+            .unwrap_or_default()
+    }
+
+    /// Current state of a coin, including its pool
+    pub fn coin(&self, symbol: &str) -> Option<MockCoin> {
+        self.state()
+            .coins
+            .get(&symbol.to_uppercase())
+            .map(|c| c.coin.clone())
+    }
+
+    /// The axum router serving the API, to embed in another server
+    pub fn router(&self) -> Router {
+        routes::router(self.state.clone())
+    }
+
+    /// Serves the API on a random localhost port
+    pub async fn start(self) -> std::io::Result<MockServer> {
+        self.serve(SocketAddr::from(([127, 0, 0, 1], 0))).await
+    }
+
+    /// Serves the API on `addr`
+    pub async fn serve(self, addr: SocketAddr) -> std::io::Result<MockServer> {
+        let listener = TcpListener::bind(addr).await?;
+        let addr = listener.local_addr()?;
+        let router = self.router();
+
+        let handle = tokio::spawn(async move {
+            if let Err(e) = axum::serve(listener, router).await {
+                tracing::error!("Mock Rugplay server failed: {e}");
+            }
+        });
+        tracing::debug!("Mock Rugplay listening on {addr}");
+
+        Ok(MockServer {
+            addr,
+            rugplay: self,
+            handle,
+        })
+    }
+}
+
+/// A running [`MockRugplay`], stopped when dropped
+#[derive(Debug)]
+pub struct MockServer {
+    addr: SocketAddr,
+    rugplay: MockRugplay,
+    handle: JoinHandle<()>,
+}
+
+impl MockServer {
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// e.g. `http://127.0.0.1:41234`
+    pub fn base_url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Base URL of the official API, to pass to `RugplayClient::new`
+    pub fn api_url(&self) -> String {
+        format!("{}/api/v1", self.base_url())
+    }
+
+    /// The served instance, to inspect or change its state
+    pub fn rugplay(&self) -> &MockRugplay {
+        &self.rugplay
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
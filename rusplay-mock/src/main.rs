@@ -0,0 +1,33 @@
+#![deny(clippy::all)]
+//! Runs a fake Rugplay server with demo data
+//!
+//! Usage: `rusplay-mock [ADDR]` (default: 127.0.0.1:3000)
+use rusplay_mock::{MockCoin, MockQuestion, MockRugplay, MockUser, SESSION_COOKIE_NAME};
+use std::net::SocketAddr;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let addr: SocketAddr = std::env::args()
+        .nth(1)
+        .as_deref()
+        .unwrap_or("127.0.0.1:3000")
+        .parse()?;
+
+    let server = MockRugplay::new()
+        .with_user(MockUser::new(1, "demo", "rgpl_demo", 10_000.0).with_session("demo_session"))
+        .with_user(
+            MockUser::new(2, "whale", "rgpl_whale", 1_000_000.0).with_session("whale_session"),
+        )
+        .with_coin(MockCoin::new("TEST", "Test", 10_000_000.0, 100_000.0).with_creator(2))
+        .with_coin(MockCoin::new("MOON", "Moon", 500_000_000.0, 50_000.0).with_creator(1))
+        .with_question(MockQuestion::new("Will TEST reach $1?", 500.0, 1500.0))
+        .serve(addr)
+        .await?;
+
+    println!("Mock Rugplay API at {}", server.api_url());
+    println!("API key: rgpl_demo");
+    println!("Cookie: {SESSION_COOKIE_NAME}=demo_session");
+
+    tokio::signal::ctrl_c().await?;
+    Ok(())
+}
@@ -0,0 +1,641 @@
+#![deny(clippy::all)]
+//! HTTP handlers, answering with the JSON shapes of the real Rugplay API
+use crate::state::{ApiError, CoinState, Side, State, UserState};
+use axum::extract::{Path, Query, State as Extract};
+use axum::http::HeaderMap;
+use axum::http::header::{AUTHORIZATION, COOKIE};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use chrono::{DateTime, SecondsFormat, TimeDelta, Utc};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+pub(crate) type Shared = Arc<Mutex<State>>;
+type ApiResult = Result<Json<Value>, ApiError>;
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.status, Json(json!({ "error": self.message }))).into_response()
+    }
+}
+
+pub(crate) fn router(state: Shared) -> Router {
+    Router::new()
+        // Official API
+        .route("/api/v1/top", get(top))
+        .route("/api/v1/market", get(market))
+        .route("/api/v1/coin/{symbol}", get(coin_details))
+        .route("/api/v1/holders/{symbol}", get(holders))
+        .route("/api/v1/hopium", get(hopium))
+        .route("/api/v1/hopium/{id}", get(hopium_details))
+        // Unofficial API
+        .route("/api/rewards/claim", get(claim_info).post(claim))
+        .route("/api/coin/{symbol}/trade", post(trade))
+        .route("/api/trades/recent", get(recent_trades))
+        .route("/api/auth/get-session", get(session))
+        .route("/api/portfolio/total", get(portfolio_total))
+        .with_state(state)
+}
+
+fn lock(state: &Shared) -> std::sync::MutexGuard<'_, State> {
+    state.lock().expect("mock state poisoned")
+}
+
+fn timestamp(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/// User authenticated with `Authorization: Bearer <api key>`
+fn api_user<'a>(headers: &HeaderMap, state: &'a State) -> Result<&'a UserState, ApiError> {
+    headers
+        .get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .and_then(|key| state.user_by_api_key(key))
+        .ok_or_else(ApiError::unauthorized)
+}
+
+/// User authenticated with the better-auth session cookie
+fn session_user<'a>(headers: &HeaderMap, state: &'a State) -> Option<&'a UserState> {
+    headers
+        .get_all(COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(';'))
+        .filter_map(|c| c.trim().split_once('='))
+        .filter(|(name, _)| name.contains("better-auth.session_token"))
+        .find_map(|(_, value)| state.user_by_session(value))
+}
+
+fn username(state: &State, user_id: u64) -> (String, String, String) {
+    match state.user(user_id) {
+        Some(u) => (
+            u.user.username.clone(),
+            u.user.name.clone(),
+            u.user.image.clone(),
+        ),
+        None => (
+            format!("user{user_id}"),
+            format!("User {user_id}"),
+            format!("avatars/{user_id}.jpg"),
+        ),
+    }
+}
+
+fn creator(state: &State, coin: &CoinState) -> (Value, Value) {
+    match coin.coin.creator_id {
+        Some(id) => {
+            let (username, name, _) = username(state, id);
+            (json!(name), json!(username))
+        }
+        None => (Value::Null, Value::Null),
+    }
+}
+
+// ---- /top ----
+async fn top(Extract(state): Extract<Shared>, headers: HeaderMap) -> ApiResult {
+    let state = lock(&state);
+    api_user(&headers, &state)?;
+    let now = state.now();
+
+    let mut coins: Vec<&CoinState> = state.coins.values().collect();
+    coins.sort_by(|a, b| b.market_cap().total_cmp(&a.market_cap()));
+
+    let coins: Vec<Value> = coins
+        .into_iter()
+        .take(50)
+        .map(|c| {
+            json!({
+                "symbol": c.coin.symbol,
+                "name": c.coin.name,
+                "icon": c.coin.icon,
+                "price": c.price(),
+                "change24h": c.change24h(now),
+                "marketCap": c.market_cap(),
+                "volume24h": c.volume24h(now),
+            })
+        })
+        .collect();
+
+    Ok(Json(json!({ "coins": coins })))
+}
+
+// ---- /market ----
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MarketQuery {
+    search: Option<String>,
+    sort_by: Option<String>,
+    sort_order: Option<String>,
+    price_filter: Option<String>,
+    change_filter: Option<String>,
+    page: Option<u32>,
+    limit: Option<u32>,
+}
+
+async fn market(
+    Extract(state): Extract<Shared>,
+    headers: HeaderMap,
+    Query(query): Query<MarketQuery>,
+) -> ApiResult {
+    let state = lock(&state);
+    api_user(&headers, &state)?;
+    let now = state.now();
+
+    let search = query.search.unwrap_or_default().to_lowercase();
+    let mut coins: Vec<&CoinState> = state
+        .coins
+        .values()
+        .filter(|c| {
+            search.is_empty()
+                || c.coin.symbol.to_lowercase().contains(&search)
+                || c.coin.name.to_lowercase().contains(&search)
+        })
+        .filter(|c| {
+            let price = c.price();
+            match query.price_filter.as_deref().unwrap_or("all") {
+                "under1" => price < 1.0,
+                "1to10" => (1.0..10.0).contains(&price),
+                "10to100" => (10.0..100.0).contains(&price),
+                "over100" => price >= 100.0,
+                _ => true,
+            }
+        })
+        .filter(|c| {
+            let change = c.change24h(now);
+            match query.change_filter.as_deref().unwrap_or("all") {
+                "gainers" => change > 0.0,
+                "losers" => change < 0.0,
+                "hot" => c.volume24h(now) > 0.0,
+                "wild" => change.abs() >= 50.0,
+                _ => true,
+            }
+        })
+        .collect();
+
+    let key = |c: &CoinState| match query.sort_by.as_deref().unwrap_or("marketCap") {
+        "currentPrice" => c.price(),
+        "change24h" => c.change24h(now),
+        "volume24h" => c.volume24h(now),
+        "createdAt" => c.created_at.timestamp_millis() as f64,
+        _ => c.market_cap(),
+    };
+    coins.sort_by(|a, b| key(a).total_cmp(&key(b)));
+    if query.sort_order.as_deref() != Some("asc") {
+        coins.reverse();
+    }
+
+    let limit = query.limit.unwrap_or(12).clamp(1, 100);
+    let page = query.page.unwrap_or(1).max(1);
+    let total = coins.len();
+    let coins: Vec<Value> = coins
+        .into_iter()
+        .skip(((page - 1) * limit) as usize)
+        .take(limit as usize)
+        .map(|c| {
+            let (creator_name, _) = creator(&state, c);
+            json!({
+                "symbol": c.coin.symbol,
+                "name": c.coin.name,
+                "icon": c.coin.icon,
+                "currentPrice": c.price(),
+                "marketCap": c.market_cap(),
+                "volume24h": c.volume24h(now),
+                "change24h": c.change24h(now),
+                "createdAt": timestamp(c.created_at),
+                "creatorName": creator_name,
+            })
+        })
+        .collect();
+
+    Ok(Json(json!({
+        "coins": coins,
+        "total": total,
+        "page": page,
+        "limit": limit,
+        "totalPages": total.div_ceil(limit as usize),
+    })))
+}
+
+// ---- /coin/{symbol} ----
+#[derive(Debug, Deserialize)]
+struct CoinQuery {
+    timeframe: Option<String>,
+}
+
+async fn coin_details(
+    Extract(state): Extract<Shared>,
+    headers: HeaderMap,
+    Path(symbol): Path<String>,
+    Query(query): Query<CoinQuery>,
+) -> ApiResult {
+    let state = lock(&state);
+    api_user(&headers, &state)?;
+    let now = state.now();
+    let coin = state.coin(&symbol)?;
+
+    let timeframe = query.timeframe.unwrap_or_else(|| "1m".into());
+    let bucket = match timeframe.as_str() {
+        "1m" => 60,
+        "5m" => 5 * 60,
+        "15m" => 15 * 60,
+        "1h" => 60 * 60,
+        "4h" => 4 * 60 * 60,
+        "1d" => 24 * 60 * 60,
+        _ => return Err(ApiError::bad_request("Invalid timeframe")),
+    };
+
+    // (open, high, low, close, volume) by bucket start
+    let mut buckets: BTreeMap<i64, (f64, f64, f64, f64, f64)> = BTreeMap::new();
+    for point in &coin.history {
+        let time = point.time.timestamp() / bucket * bucket;
+        let candle = buckets.entry(time).or_insert((
+            point.price,
+            point.price,
+            point.price,
+            point.price,
+            0.0,
+        ));
+        candle.1 = candle.1.max(point.price);
+        candle.2 = candle.2.min(point.price);
+        candle.3 = point.price;
+        candle.4 += point.volume;
+    }
+
+    let candles: Vec<Value> = buckets
+        .iter()
+        .map(|(time, (open, high, low, close, _))| {
+            json!({ "time": time, "open": open, "high": high, "low": low, "close": close })
+        })
+        .collect();
+    let volumes: Vec<Value> = buckets
+        .iter()
+        .map(|(time, candle)| json!({ "time": time, "volume": candle.4 }))
+        .collect();
+
+    let (creator_name, creator_username) = creator(&state, coin);
+    Ok(Json(json!({
+        "coin": {
+            "id": coin.id,
+            "name": coin.coin.name,
+            "symbol": coin.coin.symbol,
+            "icon": coin.coin.icon,
+            "currentPrice": coin.price(),
+            "marketCap": coin.market_cap(),
+            "volume24h": coin.volume24h(now),
+            "change24h": coin.change24h(now),
+            "poolCoinAmount": coin.coin.pool_coin_amount,
+            "poolBaseCurrencyAmount": coin.coin.pool_base_currency_amount,
+            "circulatingSupply": coin.coin.circulating_supply,
+            "initialSupply": coin.coin.circulating_supply,
+            "isListed": true,
+            "createdAt": timestamp(coin.created_at),
+            "creatorId": coin.coin.creator_id,
+            "creatorName": creator_name,
+            "creatorUsername": creator_username,
+            "creatorBio": Value::Null,
+            "creatorImage": coin.coin.creator_id.map(|id| username(&state, id).2),
+        },
+        "candlestickData": candles,
+        "volumeData": volumes,
+        "timeframe": timeframe,
+    })))
+}
+
+// ---- /holders/{symbol} ----
+#[derive(Debug, Deserialize)]
+struct LimitQuery {
+    limit: Option<u32>,
+}
+
+async fn holders(
+    Extract(state): Extract<Shared>,
+    headers: HeaderMap,
+    Path(symbol): Path<String>,
+    Query(query): Query<LimitQuery>,
+) -> ApiResult {
+    let state = lock(&state);
+    api_user(&headers, &state)?;
+    let coin = state.coin(&symbol)?;
+
+    let mut holders: Vec<(u64, f64)> = coin.holders.iter().map(|(id, q)| (*id, *q)).collect();
+    holders.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    let limit = query.limit.unwrap_or(50).clamp(1, 200) as usize;
+    let list: Vec<Value> = holders
+        .iter()
+        .take(limit)
+        .enumerate()
+        .map(|(i, (user_id, quantity))| {
+            let (username, name, image) = username(&state, *user_id);
+            json!({
+                "rank": i + 1,
+                "userId": user_id,
+                "username": username,
+                "name": name,
+                "image": image,
+                "quantity": quantity,
+                "percentage": quantity / coin.coin.circulating_supply * 100.0,
+                "liquidationValue": coin.liquidation_value(*quantity),
+            })
+        })
+        .collect();
+
+    Ok(Json(json!({
+        "coinSymbol": coin.coin.symbol,
+        "totalHolders": holders.len(),
+        "circulatingSupply": coin.coin.circulating_supply,
+        "poolInfo": {
+            "coinAmount": coin.coin.pool_coin_amount,
+            "baseCurrencyAmount": coin.coin.pool_base_currency_amount,
+            "currentPrice": coin.price(),
+        },
+        "holders": list,
+    })))
+}
+
+// ---- /hopium ----
+#[derive(Debug, Deserialize)]
+struct HopiumQuery {
+    status: Option<String>,
+    page: Option<u32>,
+    limit: Option<u32>,
+}
+
+fn question_json(state: &State, id: u64) -> Option<Value> {
+    let q = state.questions.iter().find(|q| q.id == id)?;
+    let total = q.question.yes_amount + q.question.no_amount;
+    let yes_percentage = if total > 0.0 {
+        q.question.yes_amount / total * 100.0
+    } else {
+        50.0
+    };
+
+    Some(json!({
+        "id": q.id,
+        "question": q.question.question,
+        "status": q.question.status,
+        "resolutionDate": q.question.resolution_date.map(timestamp),
+        "totalAmount": total,
+        "yesAmount": q.question.yes_amount,
+        "noAmount": q.question.no_amount,
+        "yesPercentage": yes_percentage,
+        "noPercentage": 100.0 - yes_percentage,
+        "createdAt": timestamp(q.created_at),
+        "resolvedAt": Value::Null,
+        "requiresWebSearch": false,
+        "aiResolution": Value::Null,
+        "creator": {
+            "id": 0,
+            "name": "Rugplay",
+            "username": "rugplay",
+            "image": "avatars/0.jpg",
+        },
+    }))
+}
+
+async fn hopium(
+    Extract(state): Extract<Shared>,
+    headers: HeaderMap,
+    Query(query): Query<HopiumQuery>,
+) -> ApiResult {
+    let state = lock(&state);
+    api_user(&headers, &state)?;
+
+    let status = query.status.unwrap_or_else(|| "ACTIVE".into());
+    let ids: Vec<u64> = state
+        .questions
+        .iter()
+        .rev()
+        .filter(|q| status == "ALL" || q.question.status == status)
+        .map(|q| q.id)
+        .collect();
+
+    let limit = query.limit.unwrap_or(20).clamp(1, 100);
+    let page = query.page.unwrap_or(1).max(1);
+    let questions: Vec<Value> = ids
+        .iter()
+        .skip(((page - 1) * limit) as usize)
+        .take(limit as usize)
+        .filter_map(|id| question_json(&state, *id))
+        .collect();
+
+    Ok(Json(json!({
+        "questions": questions,
+        "total": ids.len(),
+        "page": page,
+        "limit": limit,
+        "totalPages": ids.len().div_ceil(limit as usize),
+    })))
+}
+
+async fn hopium_details(
+    Extract(state): Extract<Shared>,
+    headers: HeaderMap,
+    Path(id): Path<u64>,
+) -> ApiResult {
+    let state = lock(&state);
+    api_user(&headers, &state)?;
+
+    let mut question =
+        question_json(&state, id).ok_or_else(|| ApiError::not_found("Question not found"))?;
+    question["recentBets"] = json!([]);
+    let created_at = state
+        .questions
+        .iter()
+        .find(|q| q.id == id)
+        .map(|q| q.created_at.timestamp());
+    let history = json!([{ "time": created_at, "value": question["yesPercentage"] }]);
+
+    Ok(Json(json!({
+        "question": question,
+        "probabilityHistory": history,
+    })))
+}
+
+// ---- /rewards/claim ----
+async fn claim_info(Extract(state): Extract<Shared>, headers: HeaderMap) -> ApiResult {
+    let state = lock(&state);
+    let user = session_user(&headers, &state).ok_or_else(ApiError::unauthorized)?;
+    let remaining = state.claim_remaining(user);
+
+    Ok(Json(json!({
+        "canClaim": remaining.is_zero(),
+        "rewardAmount": state.base_reward,
+        "baseReward": state.base_reward,
+        "prestigeBonus": 0,
+        "prestigeLevel": 0,
+        "timeRemaining": remaining.as_millis() as u64,
+        "nextClaimTime": state.next_claim_time(user).map(timestamp),
+        "totalRewardsClaimed": user.total_rewards_claimed,
+        "lastRewardClaim": user.last_claim.map(timestamp),
+        "loginStreak": user.login_streak,
+    })))
+}
+
+async fn claim(Extract(state): Extract<Shared>, headers: HeaderMap) -> ApiResult {
+    let mut state = lock(&state);
+    let user_id = session_user(&headers, &state)
+        .ok_or_else(ApiError::unauthorized)?
+        .user
+        .id;
+    let outcome = state.claim(user_id)?;
+
+    Ok(Json(json!({
+        "success": true,
+        "rewardAmount": outcome.reward,
+        "baseReward": outcome.reward,
+        "prestigeBonus": 0,
+        "prestigeLevel": 0,
+        "newBalance": outcome.new_balance,
+        "totalRewardsClaimed": outcome.total_rewards_claimed,
+        "loginStreak": outcome.login_streak,
+        "nextClaimTime": timestamp(outcome.next_claim_time),
+    })))
+}
+
+// ---- /coin/{symbol}/trade ----
+#[derive(Debug, Deserialize)]
+struct TradeBody {
+    r#type: String,
+    amount: f64,
+}
+
+async fn trade(
+    Extract(state): Extract<Shared>,
+    headers: HeaderMap,
+    Path(symbol): Path<String>,
+    Json(body): Json<TradeBody>,
+) -> ApiResult {
+    let mut state = lock(&state);
+    let user_id = session_user(&headers, &state)
+        .ok_or_else(ApiError::unauthorized)?
+        .user
+        .id;
+    let side = match body.r#type.as_str() {
+        "BUY" => Side::Buy,
+        "SELL" => Side::Sell,
+        _ => return Err(ApiError::bad_request("Invalid trade type")),
+    };
+
+    let outcome = state.trade(user_id, &symbol, side, body.amount)?;
+    let mut resp = json!({
+        "success": true,
+        "type": outcome.side.as_str(),
+        "newPrice": outcome.new_price,
+        "priceImpact": outcome.price_impact,
+        "newBalance": outcome.new_balance,
+    });
+    match outcome.side {
+        Side::Buy => {
+            resp["coinsBought"] = json!(outcome.coins);
+            resp["totalCost"] = json!(outcome.value);
+        }
+        Side::Sell => resp["coinsSold"] = json!(outcome.coins),
+    }
+
+    Ok(Json(resp))
+}
+
+// ---- /trades/recent ----
+async fn recent_trades(
+    Extract(state): Extract<Shared>,
+    Query(query): Query<LimitQuery>,
+) -> ApiResult {
+    let state = lock(&state);
+    let limit = query.limit.unwrap_or(100).clamp(1, 100) as usize;
+
+    let trades: Vec<Value> = state
+        .trades
+        .iter()
+        .rev()
+        .take(limit)
+        .map(|t| {
+            let (username, _, image) = username(&state, t.user_id);
+            let coin = state.coins.get(&t.symbol);
+            json!({
+                "type": t.side.as_str(),
+                "username": username,
+                "userImage": image,
+                "userId": t.user_id.to_string(),
+                "amount": t.amount,
+                "coinSymbol": t.symbol,
+                "coinName": coin.map(|c| c.coin.name.clone()).unwrap_or_default(),
+                "coinIcon": coin.and_then(|c| c.coin.icon.clone()),
+                "totalValue": t.total_value,
+                "price": t.price,
+                "timestamp": t.time.timestamp_millis(),
+            })
+        })
+        .collect();
+
+    Ok(Json(json!({ "trades": trades })))
+}
+
+// ---- /auth/get-session ----
+async fn session(Extract(state): Extract<Shared>, headers: HeaderMap) -> Json<Value> {
+    let state = lock(&state);
+    let Some(user) = session_user(&headers, &state) else {
+        // better-auth answers `null` for missing or invalid sessions
+        return Json(Value::Null);
+    };
+    let now = state.now();
+
+    Json(json!({
+        "session": {
+            "id": format!("session-{}", user.user.id),
+            "token": user.user.session_token,
+            "expiresAt": timestamp(now + TimeDelta::days(7)),
+            "createdAt": timestamp(now),
+            "updatedAt": timestamp(now),
+            "ipAddress": "127.0.0.1",
+            "userAgent": Value::Null,
+            "userId": user.user.id.to_string(),
+        },
+        "user": {
+            "id": user.user.id.to_string(),
+            "name": user.user.name,
+            "username": user.user.username,
+            "image": user.user.image,
+        },
+    }))
+}
+
+// ---- /portfolio/total ----
+async fn portfolio_total(Extract(state): Extract<Shared>, headers: HeaderMap) -> ApiResult {
+    let state = lock(&state);
+    let user = session_user(&headers, &state).ok_or_else(ApiError::unauthorized)?;
+    let now = state.now();
+
+    let mut holdings: Vec<(&CoinState, f64)> = state
+        .coins
+        .values()
+        .filter_map(|c| c.holders.get(&user.user.id).map(|q| (c, *q)))
+        .collect();
+    holdings.sort_by(|a, b| (b.1 * b.0.price()).total_cmp(&(a.1 * a.0.price())));
+
+    let total_coin_value: f64 = holdings.iter().map(|(c, q)| q * c.price()).sum();
+    let holdings: Vec<Value> = holdings
+        .into_iter()
+        .map(|(c, quantity)| {
+            json!({
+                "symbol": c.coin.symbol,
+                "icon": c.coin.icon,
+                "quantity": quantity,
+                "currentPrice": c.price(),
+                "value": quantity * c.price(),
+                "change24h": c.change24h(now),
+            })
+        })
+        .collect();
+
+    Ok(Json(json!({
+        "baseCurrencyBalance": user.user.balance,
+        "totalCoinValue": total_coin_value,
+        "totalValue": user.user.balance + total_coin_value,
+        "coinHoldings": holdings,
+    })))
+}
@@ -0,0 +1,495 @@
+#![deny(clippy::all)]
+//! Simulated Rugplay state: pools, balances, claims and prediction markets
+use axum::http::StatusCode;
+use chrono::{DateTime, TimeDelta, Utc};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// A coin to seed the server with
+#[derive(Debug, Clone)]
+pub struct MockCoin {
+    pub symbol: String,
+    pub name: String,
+    pub icon: Option<String>,
+    /// User that created the coin. It holds the supply that is not in the pool.
+    pub creator_id: Option<u64>,
+    pub pool_coin_amount: f64,
+    pub pool_base_currency_amount: f64,
+    pub circulating_supply: f64,
+}
+
+impl MockCoin {
+    /// A coin with a pool of `pool_coin_amount` coins against `pool_base_currency_amount` $
+    pub fn new(
+        symbol: &str,
+        name: &str,
+        pool_coin_amount: f64,
+        pool_base_currency_amount: f64,
+    ) -> Self {
+        Self {
+            symbol: symbol.to_uppercase(),
+            name: name.into(),
+            icon: Some(format!("coins/{}.webp", symbol.to_lowercase())),
+            creator_id: None,
+            pool_coin_amount,
+            pool_base_currency_amount,
+            circulating_supply: 1_000_000_000.0,
+        }
+    }
+
+    pub fn with_creator(mut self, user_id: u64) -> Self {
+        self.creator_id = Some(user_id);
+        self
+    }
+
+    pub fn with_supply(mut self, circulating_supply: f64) -> Self {
+        self.circulating_supply = circulating_supply;
+        self
+    }
+}
+
+/// A user to seed the server with
+#[derive(Debug, Clone)]
+pub struct MockUser {
+    pub id: u64,
+    pub username: String,
+    pub name: String,
+    pub image: String,
+    /// API key accepted by the official endpoints
+    pub api_key: String,
+    /// Value of the better-auth session cookie accepted by the unofficial endpoints
+    pub session_token: Option<String>,
+    pub balance: f64,
+}
+
+impl MockUser {
+    pub fn new(id: u64, username: &str, api_key: &str, balance: f64) -> Self {
+        Self {
+            id,
+            username: username.into(),
+            name: username.into(),
+            image: format!("avatars/{id}.jpg"),
+            api_key: api_key.into(),
+            session_token: None,
+            balance,
+        }
+    }
+
+    pub fn with_session(mut self, session_token: &str) -> Self {
+        self.session_token = Some(session_token.into());
+        self
+    }
+}
+
+/// A prediction market question to seed the server with
+#[derive(Debug, Clone)]
+pub struct MockQuestion {
+    pub question: String,
+    /// `ACTIVE`, `RESOLVED` or `CANCELLED`
+    pub status: &'static str,
+    pub yes_amount: f64,
+    pub no_amount: f64,
+    pub resolution_date: Option<DateTime<Utc>>,
+}
+
+impl MockQuestion {
+    pub fn new(question: &str, yes_amount: f64, no_amount: f64) -> Self {
+        Self {
+            question: question.into(),
+            status: "ACTIVE",
+            yes_amount,
+            no_amount,
+            resolution_date: None,
+        }
+    }
+
+    pub fn with_status(mut self, status: &'static str) -> Self {
+        self.status = status;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Buy,
+    Sell,
+}
+
+impl Side {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Side::Buy => "BUY",
+            Side::Sell => "SELL",
+        }
+    }
+}
+
+/// An error answered as `{"error": message}`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiError {
+    pub status: StatusCode,
+    pub message: String,
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+
+    pub fn bad_request(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, message)
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::NOT_FOUND, message)
+    }
+
+    pub fn unauthorized() -> Self {
+        Self::new(StatusCode::UNAUTHORIZED, "Unauthorized")
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PricePoint {
+    pub time: DateTime<Utc>,
+    pub price: f64,
+    pub volume: f64,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct CoinState {
+    pub id: u64,
+    pub coin: MockCoin,
+    pub created_at: DateTime<Utc>,
+    /// Quantity held by user id
+    pub holders: BTreeMap<u64, f64>,
+    pub history: Vec<PricePoint>,
+}
+
+impl CoinState {
+    pub fn price(&self) -> f64 {
+        self.coin.pool_base_currency_amount / self.coin.pool_coin_amount
+    }
+
+    pub fn market_cap(&self) -> f64 {
+        self.price() * self.coin.circulating_supply
+    }
+
+    /// Change of the price in the last 24h, in %
+    pub fn change24h(&self, now: DateTime<Utc>) -> f64 {
+        let since = now - TimeDelta::days(1);
+        let old = self
+            .history
+            .iter()
+            .rev()
+            .find(|p| p.time <= since)
+            .or(self.history.first())
+            .map_or(self.price(), |p| p.price);
+        (self.price() - old) / old * 100.0
+    }
+
+    pub fn volume24h(&self, now: DateTime<Utc>) -> f64 {
+        let since = now - TimeDelta::days(1);
+        self.history
+            .iter()
+            .filter(|p| p.time > since)
+            .map(|p| p.volume)
+            .sum()
+    }
+
+    /// $ received by selling `quantity` coins into the pool
+    pub fn liquidation_value(&self, quantity: f64) -> f64 {
+        let k = self.coin.pool_coin_amount * self.coin.pool_base_currency_amount;
+        self.coin.pool_base_currency_amount - k / (self.coin.pool_coin_amount + quantity)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct UserState {
+    pub user: MockUser,
+    pub last_claim: Option<DateTime<Utc>>,
+    pub login_streak: u32,
+    pub total_rewards_claimed: u32,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct QuestionState {
+    pub id: u64,
+    pub question: MockQuestion,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct TradeRecord {
+    pub user_id: u64,
+    pub symbol: String,
+    pub side: Side,
+    pub amount: f64,
+    pub price: f64,
+    pub total_value: f64,
+    pub time: DateTime<Utc>,
+}
+
+/// Result of a trade
+#[derive(Debug, Clone)]
+pub(crate) struct TradeOutcome {
+    pub side: Side,
+    /// Coins bought or sold
+    pub coins: f64,
+    /// $ spent or received
+    pub value: f64,
+    pub new_price: f64,
+    /// Change of the price caused by the trade, in %
+    pub price_impact: f64,
+    pub new_balance: f64,
+}
+
+/// Result of a reward claim
+#[derive(Debug, Clone)]
+pub(crate) struct ClaimOutcome {
+    pub reward: u32,
+    pub new_balance: f64,
+    pub total_rewards_claimed: u32,
+    pub login_streak: u32,
+    pub next_claim_time: DateTime<Utc>,
+}
+
+#[derive(Debug)]
+pub(crate) struct State {
+    pub coins: BTreeMap<String, CoinState>,
+    pub users: Vec<UserState>,
+    pub questions: Vec<QuestionState>,
+    pub trades: Vec<TradeRecord>,
+    pub claim_cooldown: Duration,
+    pub base_reward: u32,
+    /// Added to the wall clock, to simulate the passing of time
+    pub clock_offset: TimeDelta,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self {
+            coins: BTreeMap::new(),
+            users: Vec::new(),
+            questions: Vec::new(),
+            trades: Vec::new(),
+            claim_cooldown: Duration::from_secs(12 * 60 * 60),
+            base_reward: 1000,
+            clock_offset: TimeDelta::zero(),
+        }
+    }
+}
+
+impl State {
+    pub fn now(&self) -> DateTime<Utc> {
+        Utc::now() + self.clock_offset
+    }
+
+    pub fn add_coin(&mut self, coin: MockCoin) {
+        let now = self.now();
+        let mut holders = BTreeMap::new();
+        if let Some(creator) = coin.creator_id {
+            let held = coin.circulating_supply - coin.pool_coin_amount;
+            if held > 0.0 {
+                holders.insert(creator, held);
+            }
+        }
+
+        let state = CoinState {
+            id: self.coins.len() as u64 + 1,
+            created_at: now,
+            holders,
+            history: vec![PricePoint {
+                time: now,
+                price: coin.pool_base_currency_amount / coin.pool_coin_amount,
+                volume: 0.0,
+            }],
+            coin,
+        };
+        self.coins.insert(state.coin.symbol.clone(), state);
+    }
+
+    pub fn add_user(&mut self, user: MockUser) {
+        self.users.push(UserState {
+            user,
+            last_claim: None,
+            login_streak: 0,
+            total_rewards_claimed: 0,
+        });
+    }
+
+    pub fn add_question(&mut self, question: MockQuestion) {
+        let created_at = self.now();
+        self.questions.push(QuestionState {
+            id: self.questions.len() as u64 + 1,
+            question,
+            created_at,
+        });
+    }
+
+    pub fn coin(&self, symbol: &str) -> Result<&CoinState, ApiError> {
+        self.coins
+            .get(&symbol.to_uppercase())
+            .ok_or_else(|| ApiError::not_found("Coin not found"))
+    }
+
+    pub fn user(&self, id: u64) -> Option<&UserState> {
+        self.users.iter().find(|u| u.user.id == id)
+    }
+
+    fn user_mut(&mut self, id: u64) -> Result<&mut UserState, ApiError> {
+        self.users
+            .iter_mut()
+            .find(|u| u.user.id == id)
+            .ok_or_else(ApiError::unauthorized)
+    }
+
+    pub fn user_by_api_key(&self, api_key: &str) -> Option<&UserState> {
+        self.users.iter().find(|u| u.user.api_key == api_key)
+    }
+
+    pub fn user_by_session(&self, session_token: &str) -> Option<&UserState> {
+        self.users
+            .iter()
+            .find(|u| u.user.session_token.as_deref() == Some(session_token))
+    }
+
+    /// Swaps against the constant-product pool of `symbol`
+    ///
+    /// Buys spend `amount` $, sells sell `amount` coins.
+    pub fn trade(
+        &mut self,
+        user_id: u64,
+        symbol: &str,
+        side: Side,
+        amount: f64,
+    ) -> Result<TradeOutcome, ApiError> {
+        if !amount.is_finite() || amount <= 0.0 {
+            return Err(ApiError::bad_request("Invalid amount"));
+        }
+        let now = self.now();
+        let symbol = symbol.to_uppercase();
+        let balance = self.user_mut(user_id)?.user.balance;
+        let coin = self
+            .coins
+            .get_mut(&symbol)
+            .ok_or_else(|| ApiError::not_found("Coin not found"))?;
+
+        let old_price = coin.price();
+        let pool = &mut coin.coin;
+        let k = pool.pool_coin_amount * pool.pool_base_currency_amount;
+
+        let (coins, value) = match side {
+            Side::Buy => {
+                if amount > balance {
+                    return Err(ApiError::bad_request("Insufficient funds"));
+                }
+                let pool_base = pool.pool_base_currency_amount + amount;
+                let pool_coin = k / pool_base;
+                let bought = pool.pool_coin_amount - pool_coin;
+                pool.pool_base_currency_amount = pool_base;
+                pool.pool_coin_amount = pool_coin;
+                *coin.holders.entry(user_id).or_default() += bought;
+                (bought, amount)
+            }
+            Side::Sell => {
+                let held = coin.holders.get(&user_id).copied().unwrap_or_default();
+                if amount > held {
+                    return Err(ApiError::bad_request("Insufficient coins"));
+                }
+                let pool_coin = pool.pool_coin_amount + amount;
+                let pool_base = k / pool_coin;
+                let received = pool.pool_base_currency_amount - pool_base;
+                pool.pool_base_currency_amount = pool_base;
+                pool.pool_coin_amount = pool_coin;
+                if held - amount > 0.0 {
+                    coin.holders.insert(user_id, held - amount);
+                } else {
+                    coin.holders.remove(&user_id);
+                }
+                (amount, received)
+            }
+        };
+
+        let new_price = coin.price();
+        coin.history.push(PricePoint {
+            time: now,
+            price: new_price,
+            volume: value,
+        });
+        self.trades.push(TradeRecord {
+            user_id,
+            symbol,
+            side,
+            amount: coins,
+            price: value / coins,
+            total_value: value,
+            time: now,
+        });
+
+        let user = self.user_mut(user_id)?;
+        match side {
+            Side::Buy => user.user.balance -= value,
+            Side::Sell => user.user.balance += value,
+        }
+
+        Ok(TradeOutcome {
+            side,
+            coins,
+            value,
+            new_price,
+            price_impact: ((new_price - old_price) / old_price * 100.0).abs(),
+            new_balance: user.user.balance,
+        })
+    }
+
+    /// Time left before `user_id` can claim again
+    pub fn claim_remaining(&self, user: &UserState) -> Duration {
+        let Some(last) = user.last_claim else {
+            return Duration::ZERO;
+        };
+        let next = last + TimeDelta::from_std(self.claim_cooldown).unwrap_or_default();
+        (next - self.now()).to_std().unwrap_or_default()
+    }
+
+    pub fn next_claim_time(&self, user: &UserState) -> Option<DateTime<Utc>> {
+        user.last_claim
+            .map(|last| last + TimeDelta::from_std(self.claim_cooldown).unwrap_or_default())
+    }
+
+    pub fn claim(&mut self, user_id: u64) -> Result<ClaimOutcome, ApiError> {
+        let now = self.now();
+        let cooldown = TimeDelta::from_std(self.claim_cooldown).unwrap_or_default();
+        let reward = self.base_reward;
+        let user = self.user_mut(user_id)?;
+
+        if let Some(last) = user.last_claim {
+            if now < last + cooldown {
+                return Err(ApiError::bad_request("Daily reward already claimed"));
+            }
+            // Missing a claim window resets the streak
+            user.login_streak = if now <= last + cooldown * 2 {
+                user.login_streak + 1
+            } else {
+                1
+            };
+        } else {
+            user.login_streak = 1;
+        }
+
+        user.last_claim = Some(now);
+        user.user.balance += reward as f64;
+        user.total_rewards_claimed += reward;
+
+        Ok(ClaimOutcome {
+            reward,
+            new_balance: user.user.balance,
+            total_rewards_claimed: user.total_rewards_claimed,
+            login_streak: user.login_streak,
+            next_claim_time: now + cooldown,
+        })
+    }
+}
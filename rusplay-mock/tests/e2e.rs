@@ -0,0 +1,241 @@
+#![deny(clippy::all)]
+use rusplay::candles::{self, Timeframe};
+use rusplay::launches::LaunchWatcher;
+use rusplay::models::{CoinTradeType, HopiumFilter, TradeType};
+use rusplay::schema::SchemaMode;
+use rusplay::{RugplayClient, RugplayError};
+use rusplay_mock::{
+    MockCoin, MockQuestion, MockRugplay, MockServer, MockUser, SESSION_COOKIE_NAME,
+};
+use std::time::Duration;
+
+async fn start() -> MockServer {
+    MockRugplay::new()
+        .with_user(MockUser::new(1, "alice", "rgpl_alice", 100_000.0).with_session("alice_session"))
+        .with_user(MockUser::new(2, "bob", "rgpl_bob", 500.0))
+        .with_coin(MockCoin::new("TEST", "Test", 1_000_000.0, 100_000.0).with_creator(2))
+        .with_coin(MockCoin::new("MOON", "Moon", 1_000.0, 1_000_000.0))
+        .with_question(MockQuestion::new("Will TEST moon?", 300.0, 100.0))
+        .with_question(MockQuestion::new("Old question", 0.0, 0.0).with_status("RESOLVED"))
+        .start()
+        .await
+        .expect("Failed to start mock server")
+}
+
+/// Client of alice, failing on any field the models don't know about
+fn client(server: &MockServer) -> RugplayClient {
+    RugplayClient::new(
+        "rgpl_alice",
+        Some(format!("{SESSION_COOKIE_NAME}=alice_session")),
+        Some(&server.api_url()),
+    )
+    .unwrap()
+    .with_schema_mode(SchemaMode::Strict)
+}
+
+#[tokio::test]
+async fn test_official_endpoints() {
+    let server = start().await;
+    let client = client(&server);
+
+    let top = client.get_top_coins().await.unwrap();
+    assert_eq!(top.coins[0].symbol, "MOON");
+    assert_eq!(top.coins[1].price, 0.1);
+
+    let market = client
+        .get_market(&[("search", "tes"), ("limit", "5")])
+        .await
+        .unwrap();
+    assert_eq!(market.total, 1);
+    assert_eq!(market.coins[0].creator_name.as_deref(), Some("bob"));
+
+    let details = client.get_coin_details("TEST", Some("1h")).await.unwrap();
+    assert_eq!(details.coin.pool_coin_amount, Some(1_000_000.0));
+    assert_eq!(details.candlestick_data.len(), 1);
+
+    let holders = client.get_holders("TEST", Some(10)).await.unwrap();
+    assert_eq!(holders.total_holders, 1);
+    assert_eq!(holders.holders[0].username, "bob");
+    assert_eq!(holders.pool_info.current_price, 0.1);
+
+    let hopium = client
+        .get_hopium(HopiumFilter::ACTIVE, None, None)
+        .await
+        .unwrap();
+    assert_eq!(hopium.total, 1);
+    assert_eq!(hopium.questions[0].yes_percentage, 75.0);
+
+    let question = client.get_hopium_details(1).await.unwrap();
+    assert_eq!(question.question.question, "Will TEST moon?");
+
+    let err = client.get_holders("NOPE", None).await.unwrap_err();
+    assert!(
+        matches!(&err, RugplayError::Request(e) if e.status() == Some(axum::http::StatusCode::NOT_FOUND))
+    );
+
+    let anonymous = RugplayClient::new("wrong_key", None, Some(&server.api_url())).unwrap();
+    assert!(anonymous.get_top_coins().await.unwrap_err().is_auth_error());
+}
+
+#[tokio::test]
+async fn test_trades_follow_constant_product() {
+    let server = start().await;
+    let client = client(&server);
+    let rugplay = server.rugplay();
+
+    let buy = client
+        .trade("TEST", CoinTradeType::BUY, 100_000)
+        .await
+        .unwrap();
+    // x * y = k: doubling the $ side halves the coin side
+    assert_eq!(buy.coins_bought, Some(500_000.0));
+    assert_eq!(buy.new_price, 0.4);
+    assert_eq!(buy.new_balance, 0.0);
+    assert_eq!(rugplay.holding(1, "TEST"), 500_000.0);
+
+    let err = client
+        .trade("TEST", CoinTradeType::BUY, 1)
+        .await
+        .unwrap_err();
+    // Insufficient funds
+    assert!(
+        matches!(&err, RugplayError::Request(e) if e.status() == Some(axum::http::StatusCode::BAD_REQUEST)),
+        "{err}"
+    );
+
+    let sell = client
+        .trade("TEST", CoinTradeType::SELL, 500_000)
+        .await
+        .unwrap();
+    assert_eq!(sell.coins_sold, Some(500_000.0));
+    assert_eq!(sell.new_balance, 100_000.0);
+    let pool = rugplay.coin("TEST").unwrap();
+    assert_eq!(pool.pool_coin_amount, 1_000_000.0);
+    assert_eq!(pool.pool_base_currency_amount, 100_000.0);
+
+    let recent = client.get_recent_trades(10).await.unwrap();
+    assert_eq!(recent.trades.len(), 2);
+    assert!(matches!(recent.trades[0].r#type, TradeType::Sell));
+    assert_eq!(recent.trades[1].username, "alice");
+
+    let portfolio = client.get_portfolio_total().await.unwrap();
+    assert_eq!(portfolio.base_currency_balance, 100_000.0);
+    assert!(portfolio.coin_holdings.is_empty());
+}
+
+#[tokio::test]
+async fn test_candles_from_trades_match_chart() {
+    let server = start().await;
+    let client = client(&server);
+    let rugplay = server.rugplay();
+
+    rugplay.advance(Duration::from_secs(120));
+    client
+        .trade("MOON", CoinTradeType::BUY, 1_000)
+        .await
+        .unwrap();
+    client
+        .trade("MOON", CoinTradeType::BUY, 2_000)
+        .await
+        .unwrap();
+    rugplay.advance(Duration::from_secs(60));
+    client.trade("MOON", CoinTradeType::BUY, 500).await.unwrap();
+
+    let trades = client.get_recent_trades(100).await.unwrap().trades;
+    let built = candles::from_trades(&trades, Timeframe::M1);
+    let chart = client
+        .get_coin_details("MOON", Some(Timeframe::M1.as_str()))
+        .await
+        .unwrap();
+
+    // The chart also has the candle of the coin creation, before any trade
+    assert_eq!(built.volumes.len(), 2);
+    let chart_volumes = &chart.volume_data[chart.volume_data.len() - 2..];
+    for (built, chart) in built.volumes.iter().zip(chart_volumes) {
+        assert_eq!(built.time, chart.time);
+        assert!(
+            (built.volume - chart.volume).abs() < 1e-6,
+            "{built:?} {chart:?}"
+        );
+    }
+    assert_eq!(built.volumes[0].volume.round(), 3_000.0);
+}
+
+#[tokio::test]
+async fn test_launch_watcher() {
+    let server = start().await;
+    let client = client(&server);
+    let rugplay = server.rugplay();
+
+    let mut watcher = LaunchWatcher::new(client.clone())
+        .with_creators(["bob"])
+        .with_min_liquidity(1_000.0);
+    assert!(watcher.poll().await.unwrap().is_empty());
+
+    rugplay.advance(Duration::from_secs(60));
+    rugplay.launch(MockCoin::new("NEW", "New", 1_000_000.0, 10_000.0).with_creator(2));
+    rugplay.launch(MockCoin::new("TINY", "Tiny", 1_000_000.0, 10.0).with_creator(2));
+    rugplay.launch(MockCoin::new("ANON", "Anon", 1_000_000.0, 10_000.0));
+    client.trade("NEW", CoinTradeType::BUY, 100).await.unwrap();
+    client.trade("TEST", CoinTradeType::BUY, 100).await.unwrap();
+    client.trade("NEW", CoinTradeType::SELL, 10).await.unwrap();
+
+    let launches = watcher.poll().await.unwrap();
+    assert_eq!(launches.len(), 1);
+    let launch = &launches[0];
+    assert_eq!(launch.symbol(), "NEW");
+    assert_eq!(launch.creator(), Some("bob"));
+    assert!(launch.liquidity() > 10_000.0);
+    assert_eq!(launch.first_trades.len(), 2);
+    assert!(matches!(launch.first_trades[0].r#type, TradeType::Buy));
+    assert!(matches!(launch.first_trades[1].r#type, TradeType::Sell));
+
+    assert!(watcher.poll().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_claim_cooldown() {
+    let server = start().await;
+    let client = client(&server);
+    let rugplay = server.rugplay();
+
+    let info = client.get_claim_info().await.unwrap();
+    assert!(info.can_claim);
+
+    let reward = client.claim_reward().await.unwrap();
+    assert_eq!(reward.reward_amount, 1000);
+    assert_eq!(reward.new_balance, 101_000.0);
+    assert_eq!(reward.login_streak, 1);
+
+    let info = client.get_claim_info().await.unwrap();
+    assert!(!info.can_claim);
+    assert!(info.time_remaining > Duration::from_secs(11 * 60 * 60));
+    assert!(matches!(
+        client.claim_reward().await,
+        Err(RugplayError::Request(_))
+    ));
+
+    rugplay.advance(Duration::from_secs(12 * 60 * 60));
+    let reward = client.claim_reward().await.unwrap();
+    assert_eq!(reward.login_streak, 2);
+    assert_eq!(rugplay.balance(1), Some(102_000.0));
+}
+
+#[tokio::test]
+async fn test_session() {
+    let server = start().await;
+    let client = client(&server);
+
+    let status = client.validate_credentials().await.unwrap();
+    assert!(status.is_valid());
+    let me = client.whoami().await.unwrap();
+    assert_eq!(me.username, "alice");
+
+    let stale = RugplayClient::new(
+        "rgpl_alice",
+        Some(format!("{SESSION_COOKIE_NAME}=expired")),
+        Some(&server.api_url()),
+    )
+    .unwrap();
+    assert!(stale.get_session().await.unwrap().is_none());
+}
@@ -0,0 +1,32 @@
+#![no_main]
+//! Feeds arbitrary bytes to every response model
+//!
+//! Run with `cargo +nightly fuzz run models fuzz/corpus/models tests/fixtures/models` from
+//! the `rusplay` directory, so the recorded responses seed the corpus.
+use libfuzzer_sys::fuzz_target;
+use rusplay::models::*;
+use rusplay::schema::UnknownFields;
+use serde::de::DeserializeOwned;
+
+fn check<T: DeserializeOwned + UnknownFields>(data: &[u8]) {
+    if let Ok(value) = serde_json::from_slice::<T>(data) {
+        let mut unknown = Vec::new();
+        value.unknown_fields("", &mut unknown);
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    check::<TopCoinsResponse>(data);
+    check::<MarketResponse>(data);
+    check::<CoinDetailsResponse>(data);
+    check::<HoldersResponse>(data);
+    check::<HopiumResponse>(data);
+    check::<HopiumDetailsResponse>(data);
+    check::<ClaimInfo>(data);
+    check::<RewardStatus>(data);
+    check::<TradeResponse>(data);
+    check::<RecentTradeResponse>(data);
+    check::<SessionResponse>(data);
+    check::<PortfolioTotal>(data);
+    check::<Option<SessionResponse>>(data);
+});
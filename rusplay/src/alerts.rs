@@ -0,0 +1,456 @@
+#![deny(clippy::all)]
+//! Alert rules on prices, volumes and hopium probabilities
+//!
+//! An [`AlertEngine`] holds [`Rule`]s and evaluates them against polled coin and question
+//! details ([`AlertEngine::on_coin`], [`AlertEngine::on_hopium`]) or streamed trades
+//! ([`AlertEngine::on_trade`]). A rule fires when its condition becomes true, and fires again
+//! only after the condition went false and back to true. A condition that is already true the
+//! first time it's evaluated doesn't fire.
+use crate::RugplayClient;
+use crate::candles::{CandleBuilder, Timeframe};
+use crate::models::{
+    Candle, CoinDetailsResponse, HopiumDetailsResponse, QuestionId, Symbol, Trade, VolumePoint,
+};
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+/// Alerts a subscriber can miss before it starts lagging
+const CHANNEL_CAPACITY: usize = 256;
+
+/// Candles kept per coin for the rules evaluated on trades
+const MAX_TRADE_CANDLES: usize = 1_440;
+
+/// Direction in which a value crosses a level
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Cross {
+    /// From under the level to the level or more
+    Above,
+    /// From over the level to the level or less
+    Below,
+}
+
+impl Cross {
+    fn is_past(&self, value: f64, level: f64) -> bool {
+        match self {
+            Cross::Above => value >= level,
+            Cross::Below => value <= level,
+        }
+    }
+}
+
+/// What a [`Rule`] watches
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    /// The price of a coin crosses `level`
+    PriceCross {
+        symbol: Symbol,
+        level: f64,
+        cross: Cross,
+    },
+    /// The price of a coin changes by `percent` or more within `window`: a rise if `percent` is
+    /// positive, a drop if it's negative
+    ///
+    /// Measured from the close of the candle at the start of the window, so the candles must
+    /// cover it.
+    Move {
+        symbol: Symbol,
+        percent: f64,
+        window: Duration,
+    },
+    /// The volume of the current candle is at least `factor` times the average volume of the
+    /// `lookback` candles before it
+    VolumeSpike {
+        symbol: Symbol,
+        factor: f64,
+        lookback: usize,
+    },
+    /// The yes probability of a hopium question (in %) crosses `level`
+    ProbabilityCross {
+        question: QuestionId,
+        level: f64,
+        cross: Cross,
+    },
+}
+
+impl Condition {
+    fn symbol(&self) -> Option<&Symbol> {
+        match self {
+            Condition::PriceCross { symbol, .. }
+            | Condition::Move { symbol, .. }
+            | Condition::VolumeSpike { symbol, .. } => Some(symbol),
+            Condition::ProbabilityCross { .. } => None,
+        }
+    }
+}
+
+/// A named condition with its debounce and cooldown
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rule {
+    pub name: String,
+    pub condition: Condition,
+    debounce: u32,
+    cooldown: Duration,
+}
+
+impl Rule {
+    pub fn new(name: impl Into<String>, condition: Condition) -> Self {
+        Self {
+            name: name.into(),
+            condition,
+            debounce: 1,
+            cooldown: Duration::ZERO,
+        }
+    }
+
+    /// Number of consecutive evaluations the condition must be true for before firing
+    /// (default: 1)
+    pub fn with_debounce(mut self, evaluations: u32) -> Self {
+        self.debounce = evaluations.max(1);
+        self
+    }
+
+    /// Minimum time between two alerts of the rule (default: none)
+    pub fn with_cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+}
+
+/// What happened, with the values that made the rule fire
+#[derive(Debug, Clone, PartialEq)]
+pub enum AlertEvent {
+    PriceCrossed {
+        symbol: Symbol,
+        price: f64,
+        level: f64,
+        cross: Cross,
+    },
+    Moved {
+        symbol: Symbol,
+        /// Price change over the window, in %
+        change: f64,
+        window: Duration,
+    },
+    VolumeSpike {
+        symbol: Symbol,
+        volume: f64,
+        average: f64,
+    },
+    ProbabilityCrossed {
+        question: QuestionId,
+        probability: f64,
+        level: f64,
+        cross: Cross,
+    },
+}
+
+/// A fired rule
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alert {
+    /// Name of the rule
+    pub rule: String,
+    pub time: DateTime<Utc>,
+    pub event: AlertEvent,
+}
+
+#[derive(Debug)]
+struct RuleState {
+    rule: Rule,
+    /// Whether the condition was seen false since the last alert
+    armed: bool,
+    streak: u32,
+    last_alert: Option<DateTime<Utc>>,
+}
+
+/// Market data of a coin at a time
+struct CoinSnapshot<'a> {
+    symbol: &'a Symbol,
+    price: f64,
+    candles: &'a [Candle],
+    volumes: &'a [VolumePoint],
+    now: DateTime<Utc>,
+}
+
+/// Evaluates [`Rule`]s and sends the [`Alert`]s to its subscribers
+#[derive(Debug)]
+pub struct AlertEngine {
+    rules: Vec<RuleState>,
+    timeframe: Timeframe,
+    trade_candles: BTreeMap<Symbol, CandleBuilder>,
+    sender: broadcast::Sender<Alert>,
+}
+
+impl Default for AlertEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AlertEngine {
+    pub fn new() -> Self {
+        Self {
+            rules: Vec::new(),
+            timeframe: Timeframe::M1,
+            trade_candles: BTreeMap::new(),
+            sender: broadcast::channel(CHANNEL_CAPACITY).0,
+        }
+    }
+
+    pub fn with_rule(mut self, rule: Rule) -> Self {
+        self.add_rule(rule);
+        self
+    }
+
+    /// Timeframe of the candles polled by [`AlertEngine::poll`] and built from trades by
+    /// [`AlertEngine::on_trade`] (default: 1m)
+    pub fn with_timeframe(mut self, timeframe: Timeframe) -> Self {
+        self.timeframe = timeframe;
+        self
+    }
+
+    pub fn add_rule(&mut self, rule: Rule) {
+        self.rules.push(RuleState {
+            rule,
+            armed: false,
+            streak: 0,
+            last_alert: None,
+        });
+    }
+
+    /// Removes the rules named `name`, returns whether there was one
+    pub fn remove_rule(&mut self, name: &str) -> bool {
+        let len = self.rules.len();
+        self.rules.retain(|r| r.rule.name != name);
+        self.rules.len() != len
+    }
+
+    pub fn rules(&self) -> impl Iterator<Item = &Rule> {
+        self.rules.iter().map(|r| &r.rule)
+    }
+
+    /// Receives every later alert
+    pub fn subscribe(&self) -> broadcast::Receiver<Alert> {
+        self.sender.subscribe()
+    }
+
+    /// Evaluates the coin rules of `details.coin` at `now`
+    pub fn on_coin(&mut self, details: &CoinDetailsResponse, now: DateTime<Utc>) -> Vec<Alert> {
+        self.evaluate_coin(CoinSnapshot {
+            symbol: &details.coin.symbol,
+            price: details.coin.current_price,
+            candles: &details.candlestick_data,
+            volumes: &details.volume_data,
+            now,
+        })
+    }
+
+    /// Evaluates the coin rules of the traded coin, on candles built from the trades it was given
+    ///
+    /// Trades should be given in time order. Trades of coins without rules are ignored.
+    pub fn on_trade(&mut self, trade: &Trade) -> Vec<Alert> {
+        if !self
+            .rules
+            .iter()
+            .any(|r| r.rule.condition.symbol() == Some(&trade.coin_symbol))
+        {
+            return Vec::new();
+        }
+        let Some(now) = DateTime::from_timestamp_millis(trade.timestamp as i64) else {
+            return Vec::new();
+        };
+        let timeframe = self.timeframe;
+        let mut builder = self
+            .trade_candles
+            .remove(&trade.coin_symbol)
+            .unwrap_or_else(|| CandleBuilder::new(timeframe).with_max_candles(MAX_TRADE_CANDLES));
+        builder.push_trade(trade);
+
+        let alerts = self.evaluate_coin(CoinSnapshot {
+            symbol: &trade.coin_symbol,
+            price: trade.price,
+            candles: builder.candles(),
+            volumes: builder.volumes(),
+            now,
+        });
+        self.trade_candles
+            .insert(trade.coin_symbol.clone(), builder);
+        alerts
+    }
+
+    /// Evaluates the rules of the question of `details` at `now`
+    pub fn on_hopium(&mut self, details: &HopiumDetailsResponse, now: DateTime<Utc>) -> Vec<Alert> {
+        let question = details.question.id;
+        let probability = details.question.yes_percentage;
+        self.evaluate(now, |condition| match *condition {
+            Condition::ProbabilityCross {
+                question: q,
+                level,
+                cross,
+            } if q == question => Some((
+                cross.is_past(probability, level),
+                AlertEvent::ProbabilityCrossed {
+                    question,
+                    probability,
+                    level,
+                    cross,
+                },
+            )),
+            _ => None,
+        })
+    }
+
+    /// Fetches the coins and questions of the rules and evaluates them
+    ///
+    /// A coin or question that fails to load is logged and skipped.
+    pub async fn poll(&mut self, client: &RugplayClient) -> Vec<Alert> {
+        let symbols: BTreeSet<Symbol> = self
+            .rules()
+            .filter_map(|r| r.condition.symbol().cloned())
+            .collect();
+        let questions: BTreeSet<QuestionId> = self
+            .rules()
+            .filter_map(|r| match r.condition {
+                Condition::ProbabilityCross { question, .. } => Some(question),
+                _ => None,
+            })
+            .collect();
+
+        let mut alerts = Vec::new();
+        if !symbols.is_empty() {
+            let timeframe = Some(self.timeframe.as_str());
+            for (symbol, details) in client.get_coin_details_many(symbols, timeframe).await {
+                match details {
+                    Ok(details) => alerts.extend(self.on_coin(&details, Utc::now())),
+                    Err(e) => tracing::warn!("Polling {symbol} for alerts failed: {e}"),
+                }
+            }
+        }
+        for question in questions {
+            match client.get_hopium_details(question).await {
+                Ok(details) => alerts.extend(self.on_hopium(&details, Utc::now())),
+                Err(e) => tracing::warn!("Polling question {question} for alerts failed: {e}"),
+            }
+        }
+        alerts
+    }
+
+    /// Polls forever every `interval`, sending the alerts to the subscribers
+    pub async fn run(mut self, client: RugplayClient, interval: Duration) {
+        loop {
+            self.poll(&client).await;
+            tokio::time::sleep(interval).await;
+        }
+    }
+
+    fn evaluate_coin(&mut self, coin: CoinSnapshot<'_>) -> Vec<Alert> {
+        let now = coin.now;
+        self.evaluate(now, |condition| {
+            if condition.symbol() != Some(coin.symbol) {
+                return None;
+            }
+            let symbol = coin.symbol.clone();
+            match *condition {
+                Condition::PriceCross { level, cross, .. } => Some((
+                    cross.is_past(coin.price, level),
+                    AlertEvent::PriceCrossed {
+                        symbol,
+                        price: coin.price,
+                        level,
+                        cross,
+                    },
+                )),
+                Condition::Move {
+                    percent, window, ..
+                } => {
+                    let change = price_change(&coin, window)?;
+                    let moved = if percent >= 0.0 {
+                        change >= percent
+                    } else {
+                        change <= percent
+                    };
+                    Some((
+                        moved,
+                        AlertEvent::Moved {
+                            symbol,
+                            change,
+                            window,
+                        },
+                    ))
+                }
+                Condition::VolumeSpike {
+                    factor, lookback, ..
+                } => {
+                    let (volume, average) = volume_spike(coin.volumes, lookback)?;
+                    Some((
+                        average > 0.0 && volume >= factor * average,
+                        AlertEvent::VolumeSpike {
+                            symbol,
+                            volume,
+                            average,
+                        },
+                    ))
+                }
+                Condition::ProbabilityCross { .. } => None,
+            }
+        })
+    }
+
+    /// Updates the rules for which `check` returns whether their condition is true, and fires
+    /// the ones that should
+    fn evaluate<F>(&mut self, now: DateTime<Utc>, check: F) -> Vec<Alert>
+    where
+        F: Fn(&Condition) -> Option<(bool, AlertEvent)>,
+    {
+        let mut alerts = Vec::new();
+        for state in &mut self.rules {
+            let Some((active, event)) = check(&state.rule.condition) else {
+                continue;
+            };
+            if !active {
+                state.armed = true;
+                state.streak = 0;
+                continue;
+            }
+
+            state.streak = state.streak.saturating_add(1);
+            let cooled_down = state.last_alert.is_none_or(|last| {
+                (now - last).to_std().unwrap_or_default() >= state.rule.cooldown
+            });
+            if state.armed && state.streak >= state.rule.debounce && cooled_down {
+                state.armed = false;
+                state.last_alert = Some(now);
+                alerts.push(Alert {
+                    rule: state.rule.name.clone(),
+                    time: now,
+                    event,
+                });
+            }
+        }
+
+        for alert in &alerts {
+            // No subscriber isn't an error
+            let _ = self.sender.send(alert.clone());
+        }
+        alerts
+    }
+}
+
+/// Price change in % since the close of the candle at `now - window`
+fn price_change(coin: &CoinSnapshot<'_>, window: Duration) -> Option<f64> {
+    let start = (coin.now.timestamp() as u64).checked_sub(window.as_secs())?;
+    let reference = coin.candles.iter().rev().find(|c| c.time <= start)?.close;
+    (reference > 0.0).then(|| (coin.price - reference) / reference * 100.0)
+}
+
+/// Volume of the last candle and average volume of the `lookback` ones before it
+fn volume_spike(volumes: &[VolumePoint], lookback: usize) -> Option<(f64, f64)> {
+    let (last, previous) = volumes.split_last()?;
+    if lookback == 0 || previous.len() < lookback {
+        return None;
+    }
+    let previous = &previous[previous.len() - lookback..];
+    let average = previous.iter().map(|v| v.volume).sum::<f64>() / lookback as f64;
+    Some((last.volume, average))
+}
@@ -0,0 +1,189 @@
+#![deny(clippy::all)]
+//! Import Rugplay cookies exported from a browser
+//!
+//! Two formats are supported:
+//! - Netscape `cookies.txt` (curl, wget, "Get cookies.txt" extensions)
+//! - JSON arrays produced by cookie-export extensions such as Cookie-Editor or EditThisCookie
+use crate::cookies::{PersistentCookieStore, StoredCookie};
+use crate::{ClientCreateError, RugplayClient, Secret};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::path::Path;
+use thiserror::Error;
+
+/// Domain of the Rugplay cookies
+pub const RUGPLAY_DOMAIN: &str = "rugplay.com";
+
+/// Errors that importing cookies can generate
+#[derive(Debug, Error)]
+pub enum AuthError {
+    #[error("Could not read cookie file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid JSON cookie export: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Invalid cookies.txt line {line}: {reason}")]
+    InvalidLine { line: usize, reason: String },
+    #[error("No Rugplay session cookie found")]
+    NoSessionCookie,
+    #[error("Could not create client: {0}")]
+    Client(#[from] ClientCreateError),
+}
+
+/// Cookie as exported by Cookie-Editor/EditThisCookie
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonCookie {
+    name: String,
+    value: Secret,
+    domain: String,
+    #[serde(default = "default_path")]
+    path: String,
+    #[serde(default)]
+    secure: bool,
+    #[serde(default)]
+    session: bool,
+    expiration_date: Option<f64>,
+}
+
+fn default_path() -> String {
+    "/".into()
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum JsonExport {
+    List(Vec<JsonCookie>),
+    Wrapped { cookies: Vec<JsonCookie> },
+}
+
+/// Parses a Netscape `cookies.txt` file
+///
+/// Lines prefixed with `#HttpOnly_` are kept, other comments and blank lines are skipped.
+pub fn parse_netscape(text: &str) -> Result<Vec<StoredCookie>, AuthError> {
+    let mut cookies = Vec::new();
+
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        let line = match line.strip_prefix("#HttpOnly_") {
+            Some(rest) => rest,
+            None if line.starts_with('#') || line.trim().is_empty() => continue,
+            None => line,
+        };
+
+        let fields: Vec<&str> = line.split('\t').collect();
+        let [domain, _subdomains, path, secure, expires, name, value] = fields[..] else {
+            return Err(AuthError::InvalidLine {
+                line: i + 1,
+                reason: format!("expected 7 tab separated fields, got {}", fields.len()),
+            });
+        };
+
+        let expires: i64 = expires.parse().map_err(|_| AuthError::InvalidLine {
+            line: i + 1,
+            reason: format!("invalid expiry {expires:?}"),
+        })?;
+
+        cookies.push(StoredCookie {
+            name: name.into(),
+            value: value.into(),
+            domain: domain.into(),
+            path: path.into(),
+            secure: secure.eq_ignore_ascii_case("TRUE"),
+            // 0 marks a session cookie
+            expires: (expires > 0)
+                .then(|| DateTime::<Utc>::from_timestamp(expires, 0))
+                .flatten(),
+        });
+    }
+
+    Ok(cookies)
+}
+
+/// Parses the JSON produced by cookie-export browser extensions
+///
+/// Accepts both a bare array and an object with a `cookies` array.
+pub fn parse_json_export(text: &str) -> Result<Vec<StoredCookie>, AuthError> {
+    let cookies = match serde_json::from_str(text)? {
+        JsonExport::List(cookies) | JsonExport::Wrapped { cookies } => cookies,
+    };
+
+    Ok(cookies
+        .into_iter()
+        .map(|c| StoredCookie {
+            expires: match (c.session, c.expiration_date) {
+                (false, Some(date)) => DateTime::<Utc>::from_timestamp(date as i64, 0),
+                _ => None,
+            },
+            name: c.name,
+            value: c.value,
+            domain: c.domain,
+            path: c.path,
+            secure: c.secure,
+        })
+        .collect())
+}
+
+/// Reads a cookie export, detecting whether it is JSON or `cookies.txt`
+pub fn load_cookie_file(path: impl AsRef<Path>) -> Result<Vec<StoredCookie>, AuthError> {
+    let text = std::fs::read_to_string(path)?;
+    if matches!(text.trim_start().chars().next(), Some('[' | '{')) {
+        parse_json_export(&text)
+    } else {
+        parse_netscape(&text)
+    }
+}
+
+/// Keeps the better-auth session cookies that belong to `domain` (e.g. [`RUGPLAY_DOMAIN`])
+///
+/// Expired cookies are dropped. Returns [`AuthError::NoSessionCookie`] if nothing is left.
+pub fn session_cookies(
+    cookies: Vec<StoredCookie>,
+    domain: &str,
+) -> Result<Vec<StoredCookie>, AuthError> {
+    let session: Vec<StoredCookie> = cookies
+        .into_iter()
+        .filter(|c| {
+            let cookie_domain = c.domain.trim_start_matches('.');
+            cookie_domain == domain || domain.ends_with(&format!(".{cookie_domain}"))
+        })
+        .filter(|c| c.name.contains("better-auth.session") && !c.is_expired())
+        .collect();
+
+    if session.is_empty() {
+        return Err(AuthError::NoSessionCookie);
+    }
+    Ok(session)
+}
+
+/// Builds a client from an API key and a browser cookie export
+///
+/// ## Arguments
+/// - token - Rugplay API key
+/// - path - `cookies.txt` or JSON cookie export
+/// - url - Optional API base URL (default: https://rugplay.com/api/v1)
+pub fn client_from_cookie_file(
+    token: impl Into<Secret>,
+    path: impl AsRef<Path>,
+    url: Option<&str>,
+) -> Result<RugplayClient, AuthError> {
+    let cookies = session_cookies(load_cookie_file(path)?, RUGPLAY_DOMAIN)?;
+
+    Ok(RugplayClient::new_with_cookies(
+        token,
+        cookies
+            .iter()
+            .map(|c| format!("{}={}", c.name, c.value.expose())),
+        url,
+    )?)
+}
+
+/// Copies imported cookies into a [`PersistentCookieStore`] and saves it
+pub fn import_into_store(
+    store: &PersistentCookieStore,
+    cookies: &[StoredCookie],
+) -> Result<(), crate::cookies::CookieStoreError> {
+    for cookie in cookies {
+        store.insert(cookie.clone());
+    }
+    store.save()
+}
@@ -0,0 +1,409 @@
+#![deny(clippy::all)]
+//! Synchronous client, enabled with the `blocking` cargo feature
+//!
+//! Wraps the async [`crate::RugplayClient`] and drives it on its own single threaded tokio
+//! runtime, so callers don't need one. Like `reqwest::blocking`, it must not be used from
+//! within an async runtime.
+use crate::cache::CacheConfig;
+use crate::cookies::{PersistentCookieStore, SessionExpiringSoon};
+use crate::depth::{DepthProfile, TradeSize};
+use crate::ids::IntoSymbol;
+use crate::metrics::MetricsSnapshot;
+use crate::models::*;
+use crate::raw::RawResponse;
+use crate::schema::SchemaMode;
+use crate::screener::{ScreenedCoin, Screener};
+use crate::transport::{BoxError, HttpRequest, HttpResponse, Transport};
+use crate::{BatchResult, ClientCreateError, Result, Secret};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::runtime::Runtime;
+use tower::{Layer, Service};
+
+/// A synchronous Rugplay user instance
+///
+/// Clones share the runtime, cache, rate limiter and metrics.
+#[derive(Debug, Clone)]
+pub struct RugplayClient {
+    inner: crate::RugplayClient,
+    runtime: Arc<Runtime>,
+}
+
+impl RugplayClient {
+    /// Create a new Client with a token and an optional cookie
+    ///
+    /// The cookie is necessary for running non-official API calls
+    pub fn new(
+        token: impl Into<Secret>,
+        cookie: Option<String>,
+        url: Option<&str>,
+    ) -> std::result::Result<Self, ClientCreateError> {
+        Self::from_async(crate::RugplayClient::new(token, cookie, url)?)
+    }
+
+    /// Create a new Client with a token and any number of cookies (`name=value` strings)
+    pub fn new_with_cookies(
+        token: impl Into<Secret>,
+        cookies: impl IntoIterator<Item = impl Into<Secret>>,
+        url: Option<&str>,
+    ) -> std::result::Result<Self, ClientCreateError> {
+        Self::from_async(crate::RugplayClient::new_with_cookies(token, cookies, url)?)
+    }
+
+    /// Create a new Client whose cookies are kept in a [`PersistentCookieStore`]
+    pub fn new_with_cookie_store(
+        token: impl Into<Secret>,
+        store: Arc<PersistentCookieStore>,
+        url: Option<&str>,
+    ) -> std::result::Result<Self, ClientCreateError> {
+        Self::from_async(crate::RugplayClient::new_with_cookie_store(
+            token, store, url,
+        )?)
+    }
+
+    /// Wraps an already configured async client
+    pub fn from_async(inner: crate::RugplayClient) -> std::result::Result<Self, ClientCreateError> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(ClientCreateError::Runtime)?;
+
+        Ok(Self {
+            inner,
+            runtime: Arc::new(runtime),
+        })
+    }
+
+    /// Returns the underlying async client
+    pub fn as_async(&self) -> &crate::RugplayClient {
+        &self.inner
+    }
+
+    /// See [`crate::RugplayClient::check_session_expiry`]
+    pub fn check_session_expiry(
+        &self,
+        within: Duration,
+    ) -> std::result::Result<(), SessionExpiringSoon> {
+        self.inner.check_session_expiry(within)
+    }
+
+    /// See [`crate::RugplayClient::with_schema_mode`]
+    pub fn with_schema_mode(mut self, schema_mode: SchemaMode) -> Self {
+        self.inner = self.inner.with_schema_mode(schema_mode);
+        self
+    }
+
+    /// See [`crate::RugplayClient::with_rate_limit`]
+    pub fn with_rate_limit(mut self, requests: u32, per: Duration) -> Self {
+        self.inner = self.inner.with_rate_limit(requests, per);
+        self
+    }
+
+    /// See [`crate::RugplayClient::with_batch_concurrency`]
+    pub fn with_batch_concurrency(mut self, concurrency: usize) -> Self {
+        self.inner = self.inner.with_batch_concurrency(concurrency);
+        self
+    }
+
+    /// See [`crate::RugplayClient::with_layer`]
+    pub fn with_layer<L>(mut self, layer: L) -> Self
+    where
+        L: Layer<Transport>,
+        L::Service: Service<HttpRequest, Response = HttpResponse> + Clone + Send + Sync + 'static,
+        <L::Service as Service<HttpRequest>>::Error: Into<BoxError>,
+        <L::Service as Service<HttpRequest>>::Future: Send + 'static,
+    {
+        self.inner = self.inner.with_layer(layer);
+        self
+    }
+
+    /// See [`crate::RugplayClient::with_transport`]
+    pub fn with_transport<S>(mut self, service: S) -> Self
+    where
+        S: Service<HttpRequest, Response = HttpResponse> + Clone + Send + Sync + 'static,
+        S::Error: Into<BoxError>,
+        S::Future: Send + 'static,
+    {
+        self.inner = self.inner.with_transport(service);
+        self
+    }
+
+    /// See [`crate::RugplayClient::with_cache`]
+    pub fn with_cache(mut self, config: CacheConfig) -> Self {
+        self.inner = self.inner.with_cache(config);
+        self
+    }
+
+    pub fn invalidate_coin(&self, symbol: &Symbol) {
+        self.inner.invalidate_coin(symbol)
+    }
+
+    pub fn clear_cache(&self) {
+        self.inner.clear_cache()
+    }
+
+    pub fn metrics(&self) -> MetricsSnapshot {
+        self.inner.metrics()
+    }
+
+    pub fn reset_metrics(&self) {
+        self.inner.reset_metrics()
+    }
+
+    /// See [`crate::RugplayClient::with_raw`]
+    pub fn with_raw(&self) -> RawClient<'_> {
+        RawClient(self)
+    }
+
+    /// See [`crate::RugplayClient::get_json`]
+    pub fn get_json(
+        &self,
+        endpoint: &str,
+        params: Option<&[(&str, &str)]>,
+    ) -> Result<serde_json::Value> {
+        self.runtime.block_on(self.inner.get_json(endpoint, params))
+    }
+
+    // ---- ENDPOINTS ----
+    /// See [`crate::RugplayClient::get_top_coins`]
+    pub fn get_top_coins(&self) -> Result<TopCoinsResponse> {
+        self.runtime.block_on(self.inner.get_top_coins())
+    }
+
+    /// See [`crate::RugplayClient::get_market`]
+    pub fn get_market(&self, params: &[(&str, &str)]) -> Result<MarketResponse> {
+        self.runtime.block_on(self.inner.get_market(params))
+    }
+
+    /// See [`crate::RugplayClient::get_coin_details`]
+    pub fn get_coin_details(
+        &self,
+        symbol: impl IntoSymbol,
+        timeframe: Option<&str>,
+    ) -> Result<CoinDetailsResponse> {
+        self.runtime
+            .block_on(self.inner.get_coin_details(symbol, timeframe))
+    }
+
+    /// See [`crate::RugplayClient::get_holders`]
+    pub fn get_holders(
+        &self,
+        symbol: impl IntoSymbol,
+        limit: Option<u32>,
+    ) -> Result<HoldersResponse> {
+        self.runtime.block_on(self.inner.get_holders(symbol, limit))
+    }
+
+    /// See [`crate::RugplayClient::get_hopium`]
+    pub fn get_hopium(
+        &self,
+        hopium_status: HopiumFilter,
+        limit: Option<u32>,
+        page: Option<u32>,
+    ) -> Result<HopiumResponse> {
+        self.runtime
+            .block_on(self.inner.get_hopium(hopium_status, limit, page))
+    }
+
+    /// See [`crate::RugplayClient::get_hopium_details`]
+    pub fn get_hopium_details(
+        &self,
+        question_id: impl Into<QuestionId>,
+    ) -> Result<HopiumDetailsResponse> {
+        self.runtime
+            .block_on(self.inner.get_hopium_details(question_id))
+    }
+
+    // ---- Unofficial API ----
+    pub fn get_claim_info(&self) -> Result<ClaimInfo> {
+        self.runtime.block_on(self.inner.get_claim_info())
+    }
+
+    pub fn claim_reward(&self) -> Result<RewardStatus> {
+        self.runtime.block_on(self.inner.claim_reward())
+    }
+
+    pub fn trade(
+        &self,
+        coin: impl IntoSymbol,
+        trade_type: CoinTradeType,
+        amount: u32,
+    ) -> Result<TradeResponse> {
+        self.runtime
+            .block_on(self.inner.trade(coin, trade_type, amount))
+    }
+
+    pub fn get_recent_trades(&self, limit: u32) -> Result<RecentTradeResponse> {
+        self.runtime.block_on(self.inner.get_recent_trades(limit))
+    }
+
+    /// See [`crate::RugplayClient::get_session`]
+    pub fn get_session(&self) -> Result<Option<SessionResponse>> {
+        self.runtime.block_on(self.inner.get_session())
+    }
+
+    /// See [`crate::RugplayClient::get_portfolio_total`]
+    pub fn get_portfolio_total(&self) -> Result<PortfolioTotal> {
+        self.runtime.block_on(self.inner.get_portfolio_total())
+    }
+
+    /// See [`crate::RugplayClient::get_depth_profile`]
+    pub fn get_depth_profile(
+        &self,
+        symbol: impl IntoSymbol,
+        sizes: &[TradeSize],
+    ) -> Result<DepthProfile> {
+        self.runtime
+            .block_on(self.inner.get_depth_profile(symbol, sizes))
+    }
+
+    /// See [`crate::RugplayClient::screen`]
+    pub fn screen(&self, screener: &Screener) -> Result<Vec<ScreenedCoin>> {
+        self.runtime.block_on(self.inner.screen(screener))
+    }
+
+    // ---- Batch ----
+    /// See [`crate::RugplayClient::get_coin_details_many`]
+    pub fn get_coin_details_many<S: IntoSymbol>(
+        &self,
+        symbols: impl IntoIterator<Item = S>,
+        timeframe: Option<&str>,
+    ) -> BatchResult<CoinDetailsResponse> {
+        self.runtime
+            .block_on(self.inner.get_coin_details_many(symbols, timeframe))
+    }
+
+    /// See [`crate::RugplayClient::get_holders_many`]
+    pub fn get_holders_many<S: IntoSymbol>(
+        &self,
+        symbols: impl IntoIterator<Item = S>,
+        limit: Option<u32>,
+    ) -> BatchResult<HoldersResponse> {
+        self.runtime
+            .block_on(self.inner.get_holders_many(symbols, limit))
+    }
+
+    /// See [`crate::RugplayClient::whoami`]
+    pub fn whoami(&self) -> Result<WhoAmI> {
+        self.runtime.block_on(self.inner.whoami())
+    }
+
+    /// See [`crate::RugplayClient::validate_credentials`]
+    pub fn validate_credentials(&self) -> Result<CredentialStatus> {
+        self.runtime.block_on(self.inner.validate_credentials())
+    }
+}
+
+/// Synchronous [`crate::raw::RawClient`], created with [`RugplayClient::with_raw`]
+#[derive(Debug, Clone, Copy)]
+pub struct RawClient<'a>(&'a RugplayClient);
+
+impl RawClient<'_> {
+    // ---- ENDPOINTS ----
+    pub fn get_top_coins(&self) -> Result<RawResponse<TopCoinsResponse>> {
+        self.0
+            .runtime
+            .block_on(self.0.inner.with_raw().get_top_coins())
+    }
+
+    pub fn get_market(&self, params: &[(&str, &str)]) -> Result<RawResponse<MarketResponse>> {
+        self.0
+            .runtime
+            .block_on(self.0.inner.with_raw().get_market(params))
+    }
+
+    pub fn get_coin_details(
+        &self,
+        symbol: impl IntoSymbol,
+        timeframe: Option<&str>,
+    ) -> Result<RawResponse<CoinDetailsResponse>> {
+        self.0
+            .runtime
+            .block_on(self.0.inner.with_raw().get_coin_details(symbol, timeframe))
+    }
+
+    pub fn get_holders(
+        &self,
+        symbol: impl IntoSymbol,
+        limit: Option<u32>,
+    ) -> Result<RawResponse<HoldersResponse>> {
+        self.0
+            .runtime
+            .block_on(self.0.inner.with_raw().get_holders(symbol, limit))
+    }
+
+    pub fn get_hopium(
+        &self,
+        hopium_status: HopiumFilter,
+        limit: Option<u32>,
+        page: Option<u32>,
+    ) -> Result<RawResponse<HopiumResponse>> {
+        self.0.runtime.block_on(
+            self.0
+                .inner
+                .with_raw()
+                .get_hopium(hopium_status, limit, page),
+        )
+    }
+
+    pub fn get_hopium_details(
+        &self,
+        question_id: impl Into<QuestionId>,
+    ) -> Result<RawResponse<HopiumDetailsResponse>> {
+        self.0
+            .runtime
+            .block_on(self.0.inner.with_raw().get_hopium_details(question_id))
+    }
+
+    // ---- Unofficial API ----
+    pub fn get_claim_info(&self) -> Result<RawResponse<ClaimInfo>> {
+        self.0
+            .runtime
+            .block_on(self.0.inner.with_raw().get_claim_info())
+    }
+
+    pub fn claim_reward(&self) -> Result<RawResponse<RewardStatus>> {
+        self.0
+            .runtime
+            .block_on(self.0.inner.with_raw().claim_reward())
+    }
+
+    pub fn trade(
+        &self,
+        coin: impl IntoSymbol,
+        trade_type: CoinTradeType,
+        amount: u32,
+    ) -> Result<RawResponse<TradeResponse>> {
+        self.0
+            .runtime
+            .block_on(self.0.inner.with_raw().trade(coin, trade_type, amount))
+    }
+
+    pub fn get_recent_trades(&self, limit: u32) -> Result<RawResponse<RecentTradeResponse>> {
+        self.0
+            .runtime
+            .block_on(self.0.inner.with_raw().get_recent_trades(limit))
+    }
+
+    pub fn get_session(&self) -> Result<RawResponse<Option<SessionResponse>>> {
+        self.0
+            .runtime
+            .block_on(self.0.inner.with_raw().get_session())
+    }
+
+    pub fn get_portfolio_total(&self) -> Result<RawResponse<PortfolioTotal>> {
+        self.0
+            .runtime
+            .block_on(self.0.inner.with_raw().get_portfolio_total())
+    }
+
+    pub fn get_json(
+        &self,
+        endpoint: &str,
+        params: Option<&[(&str, &str)]>,
+    ) -> Result<RawResponse<serde_json::Value>> {
+        self.0
+            .runtime
+            .block_on(self.0.inner.with_raw().get_json(endpoint, params))
+    }
+}
@@ -0,0 +1,231 @@
+#![deny(clippy::all)]
+//! In-memory TTL cache for read-only endpoints
+//!
+//! Enabled with [`crate::RugplayClient::with_cache`]. Responses are cached per endpoint and
+//! query parameters, and concurrent identical requests share a single call to Rugplay.
+use reqwest::StatusCode;
+use reqwest::header::HeaderMap;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Time to live of the cached responses of each endpoint
+///
+/// Only endpoints with a TTL are cached. The default caches the official, read-only endpoints.
+#[derive(Debug, Clone)]
+pub struct CacheConfig {
+    ttls: HashMap<&'static str, Duration>,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self::empty()
+            .ttl("top", Duration::from_secs(10))
+            .ttl("market", Duration::from_secs(10))
+            .ttl("coin", Duration::from_secs(5))
+            .ttl("holders", Duration::from_secs(30))
+            .ttl("hopium", Duration::from_secs(30))
+            .ttl("hopium_details", Duration::from_secs(30))
+    }
+}
+
+impl CacheConfig {
+    /// A configuration that caches nothing
+    pub fn empty() -> Self {
+        Self {
+            ttls: HashMap::new(),
+        }
+    }
+
+    /// Sets the TTL of an endpoint (`"top"`, `"market"`, `"coin"`, `"holders"`, `"hopium"`,
+    /// `"hopium_details"`)
+    pub fn ttl(mut self, endpoint: &'static str, ttl: Duration) -> Self {
+        self.ttls.insert(endpoint, ttl);
+        self
+    }
+
+    /// Stops caching an endpoint
+    pub fn disable(mut self, endpoint: &'static str) -> Self {
+        self.ttls.remove(endpoint);
+        self
+    }
+
+    pub fn ttl_of(&self, endpoint: &str) -> Option<Duration> {
+        self.ttls.get(endpoint).copied()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct CachedResponse {
+    pub body: String,
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    expires_at: Instant,
+    coin: Option<String>,
+}
+
+/// The invalidations a request has seen before being sent
+///
+/// A response is only cached if its coin wasn't invalidated, and the cache wasn't cleared,
+/// while it was being fetched.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Generation {
+    coin: Option<String>,
+    invalidations: u64,
+    clears: u64,
+}
+
+#[derive(Debug, Default)]
+struct Entries {
+    responses: HashMap<String, CachedResponse>,
+    /// Number of invalidations of each coin
+    invalidations: HashMap<String, u64>,
+    clears: u64,
+}
+
+impl Entries {
+    fn generation(&self, coin: Option<String>) -> Generation {
+        Generation {
+            invalidations: coin
+                .as_ref()
+                .and_then(|c| self.invalidations.get(c))
+                .copied()
+                .unwrap_or_default(),
+            coin,
+            clears: self.clears,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct ResponseCache {
+    config: CacheConfig,
+    entries: Mutex<Entries>,
+    in_flight: Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>,
+}
+
+impl ResponseCache {
+    pub fn new(config: CacheConfig) -> Self {
+        Self {
+            config,
+            entries: Mutex::default(),
+            in_flight: Mutex::default(),
+        }
+    }
+
+    pub fn ttl_of(&self, endpoint: &str) -> Option<Duration> {
+        self.config.ttl_of(endpoint)
+    }
+
+    pub fn get(&self, key: &str) -> Option<CachedResponse> {
+        let responses = &mut self.entries.lock().expect("cache poisoned").responses;
+        match responses.get(key) {
+            Some(entry) if entry.expires_at > Instant::now() => Some(entry.clone()),
+            Some(_) => {
+                responses.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Takes the generation of a request about `coin`, before sending it
+    pub fn generation(&self, coin: Option<&str>) -> Generation {
+        self.entries
+            .lock()
+            .expect("cache poisoned")
+            .generation(coin.map(str::to_uppercase))
+    }
+
+    /// Caches a response, unless its coin was invalidated since `generation` was taken
+    pub fn insert(
+        &self,
+        key: String,
+        ttl: Duration,
+        generation: Generation,
+        body: String,
+        status: StatusCode,
+        headers: HeaderMap,
+    ) {
+        let mut entries = self.entries.lock().expect("cache poisoned");
+        if entries.generation(generation.coin.clone()) != generation {
+            tracing::debug!("Not caching {key}, invalidated while fetching it");
+            return;
+        }
+
+        let entry = CachedResponse {
+            body,
+            status,
+            headers,
+            expires_at: Instant::now() + ttl,
+            coin: generation.coin,
+        };
+        entries.responses.insert(key, entry);
+    }
+
+    /// Returns the lock that concurrent requests for `key` wait on
+    pub fn in_flight(&self, key: &str) -> InFlight<'_> {
+        let lock = self
+            .in_flight
+            .lock()
+            .expect("cache poisoned")
+            .entry(key.to_string())
+            .or_default()
+            .clone();
+        InFlight {
+            cache: self,
+            key: key.to_string(),
+            lock,
+        }
+    }
+
+    /// Removes every cached response about `coin`
+    pub fn invalidate_coin(&self, coin: &str) {
+        let coin = coin.to_uppercase();
+        let mut entries = self.entries.lock().expect("cache poisoned");
+        entries
+            .responses
+            .retain(|_, e| e.coin.as_deref() != Some(coin.as_str()));
+        *entries.invalidations.entry(coin).or_default() += 1;
+    }
+
+    pub fn clear(&self) {
+        let mut entries = self.entries.lock().expect("cache poisoned");
+        entries.responses.clear();
+        entries.clears += 1;
+    }
+}
+
+/// Lock of the requests for a key, forgotten on drop once nobody else is waiting on it
+#[derive(Debug)]
+pub(crate) struct InFlight<'a> {
+    cache: &'a ResponseCache,
+    key: String,
+    lock: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl InFlight<'_> {
+    pub async fn lock(&self) -> tokio::sync::MutexGuard<'_, ()> {
+        self.lock.lock().await
+    }
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        let mut in_flight = self.cache.in_flight.lock().expect("cache poisoned");
+        // One reference in the map, one held here
+        if Arc::strong_count(&self.lock) <= 2 {
+            in_flight.remove(&self.key);
+        }
+    }
+}
+
+/// Key of a request in the cache
+pub(crate) fn cache_key(endpoint: &str, params: Option<&[(&str, &str)]>) -> String {
+    let mut key = endpoint.to_string();
+    for (i, (k, v)) in params.unwrap_or_default().iter().enumerate() {
+        key.push(if i == 0 { '?' } else { '&' });
+        key.push_str(&format!("{k}={v}"));
+    }
+    key
+}
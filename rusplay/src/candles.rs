@@ -0,0 +1,268 @@
+#![deny(clippy::all)]
+//! Candle construction from trades and resampling of candle series
+//!
+//! Candles are aligned like the ones of [`crate::RugplayClient::get_coin_details`]: each one
+//! starts at a multiple of the interval since the epoch, `time` is in seconds and the volume is
+//! in base currency.
+use crate::models::{Candle, CoinDetailsResponse, Trade, VolumePoint};
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::str::FromStr;
+use std::time::Duration;
+use thiserror::Error;
+
+/// Chart timeframes of [`crate::RugplayClient::get_coin_details`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Timeframe {
+    M1,
+    M5,
+    M15,
+    H1,
+    H4,
+    D1,
+}
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+#[error("Invalid timeframe {0:?}")]
+pub struct InvalidTimeframe(pub String);
+
+impl Timeframe {
+    pub const ALL: [Timeframe; 6] = [
+        Timeframe::M1,
+        Timeframe::M5,
+        Timeframe::M15,
+        Timeframe::H1,
+        Timeframe::H4,
+        Timeframe::D1,
+    ];
+
+    /// The name Rugplay uses (e.g. `"15m"`)
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Timeframe::M1 => "1m",
+            Timeframe::M5 => "5m",
+            Timeframe::M15 => "15m",
+            Timeframe::H1 => "1h",
+            Timeframe::H4 => "4h",
+            Timeframe::D1 => "1d",
+        }
+    }
+
+    pub fn seconds(&self) -> u64 {
+        match self {
+            Timeframe::M1 => 60,
+            Timeframe::M5 => 5 * 60,
+            Timeframe::M15 => 15 * 60,
+            Timeframe::H1 => 60 * 60,
+            Timeframe::H4 => 4 * 60 * 60,
+            Timeframe::D1 => 24 * 60 * 60,
+        }
+    }
+}
+
+impl Display for Timeframe {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Timeframe {
+    type Err = InvalidTimeframe;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|t| t.as_str() == s)
+            .ok_or_else(|| InvalidTimeframe(s.to_string()))
+    }
+}
+
+impl From<Timeframe> for Duration {
+    fn from(timeframe: Timeframe) -> Self {
+        Duration::from_secs(timeframe.seconds())
+    }
+}
+
+/// Candles and their volumes, index by index, like in [`CoinDetailsResponse`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CandleSeries {
+    pub candles: Vec<Candle>,
+    pub volumes: Vec<VolumePoint>,
+}
+
+impl From<CoinDetailsResponse> for CandleSeries {
+    fn from(details: CoinDetailsResponse) -> Self {
+        Self {
+            candles: details.candlestick_data,
+            volumes: details.volume_data,
+        }
+    }
+}
+
+/// Most flat candles added for a single gap when the builder has no
+/// [`CandleBuilder::with_max_candles`], older ones of the gap are skipped
+pub const MAX_FORWARD_FILL: usize = 100_000;
+
+/// Builds candles of a fixed interval from prices, trades or smaller candles
+///
+/// Data is expected in time order. A point older than the last candle only extends the high,
+/// low and volume of its own candle, and is dropped if that candle doesn't exist. The last
+/// candle is the one in progress.
+#[derive(Debug, Clone)]
+pub struct CandleBuilder {
+    interval: u64,
+    forward_fill: bool,
+    max_candles: Option<usize>,
+    series: CandleSeries,
+}
+
+impl CandleBuilder {
+    /// Creates a builder of candles of `interval` (whole seconds, at least one)
+    pub fn new(interval: impl Into<Duration>) -> Self {
+        Self {
+            interval: interval.into().as_secs().max(1),
+            forward_fill: true,
+            max_candles: None,
+            series: CandleSeries::default(),
+        }
+    }
+
+    /// Whether intervals without data get a flat candle at the previous close (default: true)
+    ///
+    /// Disable it to only get the intervals with data, like Rugplay does. A gap is filled with
+    /// at most [`MAX_FORWARD_FILL`] candles, or the max candles if set.
+    pub fn with_forward_fill(mut self, forward_fill: bool) -> Self {
+        self.forward_fill = forward_fill;
+        self
+    }
+
+    /// Only keeps the last `max` candles (at least one), to build candles from a live feed
+    pub fn with_max_candles(mut self, max: usize) -> Self {
+        self.max_candles = Some(max.max(1));
+        self
+    }
+
+    /// Adds a price at `time` (seconds since the epoch) with the volume traded at it
+    pub fn push(&mut self, time: u64, price: f64, volume: f64) {
+        let Some((i, last)) = self.slot(time, price) else {
+            return;
+        };
+        let candle = &mut self.series.candles[i];
+        candle.high = candle.high.max(price);
+        candle.low = candle.low.min(price);
+        if last {
+            candle.close = price;
+        }
+        self.series.volumes[i].volume += volume;
+    }
+
+    /// Adds a trade, with `price * amount` as its volume
+    pub fn push_trade(&mut self, trade: &Trade) {
+        self.push(
+            trade.timestamp / 1000,
+            trade.price,
+            trade.price * trade.amount,
+        );
+    }
+
+    /// Merges a candle of a smaller interval
+    pub fn push_candle(&mut self, candle: &Candle, volume: f64) {
+        let Some((i, last)) = self.slot(candle.time, candle.open) else {
+            return;
+        };
+        let merged = &mut self.series.candles[i];
+        merged.high = merged.high.max(candle.high);
+        merged.low = merged.low.min(candle.low);
+        if last {
+            merged.close = candle.close;
+        }
+        self.series.volumes[i].volume += volume;
+    }
+
+    pub fn candles(&self) -> &[Candle] {
+        &self.series.candles
+    }
+
+    pub fn volumes(&self) -> &[VolumePoint] {
+        &self.series.volumes
+    }
+
+    pub fn finish(self) -> CandleSeries {
+        self.series
+    }
+
+    /// Index of the candle of `time`, opening it (and the gap before it) at `open` if needed,
+    /// and whether it's the last one
+    fn slot(&mut self, time: u64, open: f64) -> Option<(usize, bool)> {
+        let start = time / self.interval * self.interval;
+        let candles = &mut self.series.candles;
+        let volumes = &mut self.series.volumes;
+
+        match candles.last() {
+            Some(last) if last.time == start => {}
+            Some(last) if last.time > start => {
+                let i = candles.binary_search_by_key(&start, |c| c.time).ok()?;
+                return Some((i, false));
+            }
+            previous => {
+                if let Some(previous) = previous.filter(|_| self.forward_fill) {
+                    let close = previous.close;
+                    // Candles older than the kept ones would be drained right away
+                    let kept = self.max_candles.unwrap_or(MAX_FORWARD_FILL) as u64;
+                    let from = (previous.time + self.interval)
+                        .max(start.saturating_sub(kept.saturating_mul(self.interval)));
+                    let gap = (from..start).step_by(self.interval as usize);
+                    for t in gap {
+                        candles.push(Candle::new(t, close, close, close, close));
+                        volumes.push(VolumePoint::new(t, 0.0));
+                    }
+                }
+                candles.push(Candle::new(start, open, open, open, open));
+                volumes.push(VolumePoint::new(start, 0.0));
+                if let Some(max) = self.max_candles
+                    && candles.len() > max
+                {
+                    let excess = candles.len() - max;
+                    candles.drain(..excess);
+                    volumes.drain(..excess);
+                }
+            }
+        }
+        Some((candles.len() - 1, true))
+    }
+}
+
+/// Builds candles of `interval` from trades, in any order (e.g. from
+/// [`crate::RugplayClient::get_recent_trades`], which lists the newest first)
+///
+/// The trades should all be of the same coin. Intervals without trades are forward-filled, see
+/// [`CandleBuilder::with_forward_fill`].
+pub fn from_trades(trades: &[Trade], interval: impl Into<Duration>) -> CandleSeries {
+    let mut trades: Vec<&Trade> = trades.iter().collect();
+    trades.sort_by_key(|t| t.timestamp);
+
+    let mut builder = CandleBuilder::new(interval);
+    for trade in trades {
+        builder.push_trade(trade);
+    }
+    builder.finish()
+}
+
+/// Resamples candles to a larger interval (e.g. 1m candles into 15m ones)
+///
+/// Volumes are matched to candles by `time`, missing ones count as no volume. Intervals without
+/// candles are forward-filled.
+pub fn resample(
+    candles: &[Candle],
+    volumes: &[VolumePoint],
+    interval: impl Into<Duration>,
+) -> CandleSeries {
+    let volumes: BTreeMap<u64, f64> = volumes.iter().map(|v| (v.time, v.volume)).collect();
+    let mut builder = CandleBuilder::new(interval);
+    for candle in candles {
+        builder.push_candle(
+            candle,
+            volumes.get(&candle.time).copied().unwrap_or_default(),
+        );
+    }
+    builder.finish()
+}
@@ -0,0 +1,273 @@
+#![deny(clippy::all)]
+//! Persistent cookie storage
+//!
+//! [`PersistentCookieStore`] keeps the cookies of one account in a JSON file, so a session
+//! cookie refreshed by Rugplay survives process restarts.
+use crate::Secret;
+use chrono::{DateTime, TimeDelta, Utc};
+use reqwest::header::HeaderValue;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, SystemTime};
+use thiserror::Error;
+use url::Url;
+
+/// Name of the cookie holding the Rugplay (better-auth) session
+pub const SESSION_COOKIE_NAME: &str = "__Secure-better-auth.session_token";
+
+/// A cookie as saved in the JSON file
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StoredCookie {
+    pub name: String,
+    #[serde(serialize_with = "serialize_secret")]
+    pub value: Secret,
+    pub domain: String,
+    pub path: String,
+    pub secure: bool,
+    /// `None` for session cookies or cookies with an unknown expiry
+    pub expires: Option<DateTime<Utc>>,
+}
+
+impl StoredCookie {
+    pub fn is_expired(&self) -> bool {
+        self.expires.is_some_and(|e| e <= Utc::now())
+    }
+
+    fn matches(&self, url: &Url) -> bool {
+        let Some(host) = url.host_str() else {
+            return false;
+        };
+        let domain = self.domain.trim_start_matches('.');
+        let domain_match = host == domain || host.ends_with(&format!(".{domain}"));
+        let path_match = url.path().starts_with(&self.path);
+        let scheme_match = !self.secure || url.scheme() == "https";
+
+        domain_match && path_match && scheme_match && !self.is_expired()
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CookieFile {
+    cookies: Vec<StoredCookie>,
+}
+
+/// Errors that the cookie store can generate
+#[derive(Debug, Error)]
+pub enum CookieStoreError {
+    #[error("Could not access cookie file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid cookie file: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Invalid cookie: {0}")]
+    Parse(#[from] cookie::ParseError),
+}
+
+/// The session cookie will expire before the configured threshold
+#[derive(Debug, Error, Clone, PartialEq)]
+#[error("Cookie {name} expires at {expires_at} (in {}s)", remaining.as_secs())]
+pub struct SessionExpiringSoon {
+    pub name: String,
+    pub expires_at: DateTime<Utc>,
+    pub remaining: Duration,
+}
+
+/// A cookie store backed by a JSON file
+///
+/// Every cookie set by Rugplay is written back to the file immediately.
+#[derive(Debug)]
+pub struct PersistentCookieStore {
+    path: PathBuf,
+    cookies: Mutex<Vec<StoredCookie>>,
+}
+
+impl PersistentCookieStore {
+    /// Loads the store from `path`. A missing file results in an empty store.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, CookieStoreError> {
+        let path = path.as_ref().to_path_buf();
+        let cookies = match std::fs::read_to_string(&path) {
+            Ok(text) => serde_json::from_str::<CookieFile>(&text)?.cookies,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(Self {
+            path,
+            cookies: Mutex::new(cookies),
+        })
+    }
+
+    /// Adds a cookie from a `Set-Cookie` like string (e.g. `name=value; Max-Age=3600`)
+    /// and saves the store
+    pub fn add_cookie_str(&self, cookie: &str, url: &Url) -> Result<(), CookieStoreError> {
+        let parsed = cookie::Cookie::parse(cookie)?;
+        self.insert(from_cookie(&parsed, url));
+        self.save()
+    }
+
+    /// Writes the store to its JSON file
+    ///
+    /// The file is replaced atomically, and is only readable by its owner on Unix.
+    pub fn save(&self) -> Result<(), CookieStoreError> {
+        let file = CookieFile {
+            cookies: self.cookies(),
+        };
+        let json = serde_json::to_string_pretty(&file)?;
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let mut tmp_name = self.path.file_name().unwrap_or_default().to_os_string();
+        // Unique per save, so that concurrent saves don't share a temporary file
+        static SAVES: AtomicUsize = AtomicUsize::new(0);
+        let save = SAVES.fetch_add(1, Ordering::Relaxed);
+        tmp_name.push(format!(".{}-{save}.tmp", std::process::id()));
+        let tmp = self.path.with_file_name(tmp_name);
+
+        let result =
+            write_private(&tmp, json.as_bytes()).and_then(|()| std::fs::rename(&tmp, &self.path));
+        if result.is_err() {
+            let _ = std::fs::remove_file(&tmp);
+        }
+        Ok(result?)
+    }
+
+    /// Returns a copy of every stored cookie
+    pub fn cookies(&self) -> Vec<StoredCookie> {
+        self.cookies.lock().expect("cookie store poisoned").clone()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cookies
+            .lock()
+            .expect("cookie store poisoned")
+            .is_empty()
+    }
+
+    /// Returns the stored cookie with `name`, if any
+    pub fn get(&self, name: &str) -> Option<StoredCookie> {
+        self.cookies
+            .lock()
+            .expect("cookie store poisoned")
+            .iter()
+            .find(|c| c.name == name)
+            .cloned()
+    }
+
+    /// Returns an error if the session cookie expires within `within`
+    ///
+    /// Cookies without a known expiry never trigger the signal.
+    pub fn check_expiry(&self, within: Duration) -> Result<(), SessionExpiringSoon> {
+        let Some(expires_at) = self.get(SESSION_COOKIE_NAME).and_then(|c| c.expires) else {
+            return Ok(());
+        };
+
+        let remaining = (expires_at - Utc::now()).to_std().unwrap_or_default();
+        if remaining <= within {
+            return Err(SessionExpiringSoon {
+                name: SESSION_COOKIE_NAME.into(),
+                expires_at,
+                remaining,
+            });
+        }
+        Ok(())
+    }
+
+    pub(crate) fn insert(&self, cookie: StoredCookie) {
+        let mut cookies = self.cookies.lock().expect("cookie store poisoned");
+        cookies.retain(|c| {
+            !(c.name == cookie.name && c.domain == cookie.domain && c.path == cookie.path)
+        });
+        if !cookie.is_expired() {
+            cookies.push(cookie);
+        }
+    }
+}
+
+impl reqwest::cookie::CookieStore for PersistentCookieStore {
+    fn set_cookies(&self, cookie_headers: &mut dyn Iterator<Item = &HeaderValue>, url: &Url) {
+        let mut changed = false;
+        for header in cookie_headers {
+            let Ok(text) = header.to_str() else {
+                continue;
+            };
+            match cookie::Cookie::parse(text) {
+                Ok(parsed) => {
+                    self.insert(from_cookie(&parsed, url));
+                    changed = true;
+                }
+                Err(e) => tracing::warn!("Ignoring invalid Set-Cookie header: {e}"),
+            }
+        }
+
+        if changed && let Err(e) = self.save() {
+            tracing::error!(
+                "Could not save cookie store to {}: {e}",
+                self.path.display()
+            );
+        }
+    }
+
+    fn cookies(&self, url: &Url) -> Option<HeaderValue> {
+        let header = self
+            .cookies
+            .lock()
+            .expect("cookie store poisoned")
+            .iter()
+            .filter(|c| c.matches(url))
+            .map(|c| format!("{}={}", c.name, c.value.expose()))
+            .collect::<Vec<_>>()
+            .join("; ");
+
+        if header.is_empty() {
+            None
+        } else {
+            HeaderValue::from_str(&header).ok()
+        }
+    }
+}
+
+/// Creates or truncates `path` with mode 0600 and writes `data` to it
+fn write_private(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    let mut file = options.open(path)?;
+    file.write_all(data)?;
+    file.sync_all()
+}
+
+/// Cookie values are written to the file as is
+fn serialize_secret<S: serde::Serializer>(
+    secret: &Secret,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(secret.expose())
+}
+
+fn from_cookie(cookie: &cookie::Cookie<'_>, url: &Url) -> StoredCookie {
+    // Max-Age takes precedence over Expires (RFC 6265, 5.3)
+    let expires = match cookie.max_age() {
+        Some(max_age) => Some(Utc::now() + TimeDelta::seconds(max_age.whole_seconds())),
+        None => cookie
+            .expires_datetime()
+            .map(|e| DateTime::<Utc>::from(SystemTime::from(e))),
+    };
+
+    StoredCookie {
+        name: cookie.name().into(),
+        value: cookie.value().into(),
+        domain: cookie
+            .domain()
+            .map(str::to_string)
+            .or_else(|| url.host_str().map(str::to_string))
+            .unwrap_or_default(),
+        path: cookie.path().unwrap_or("/").into(),
+        secure: cookie.secure().unwrap_or(false),
+        expires,
+    }
+}
@@ -0,0 +1,151 @@
+#![deny(clippy::all)]
+//! Liquidity depth of a coin's pool
+//!
+//! Shows what buying or selling a given size would do to the price, from the pool amounts of
+//! [`PoolInfo`].
+use crate::models::{PoolInfo, Symbol};
+use std::fmt::{self, Display, Write};
+
+/// Size of a simulated trade
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TradeSize {
+    /// Amount of base currency ($)
+    Base(f64),
+    /// Percentage of the pool: of its base currency when buying, of its coins when selling
+    PoolPercent(f64),
+}
+
+impl Display for TradeSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TradeSize::Base(base) => write!(f, "${base}"),
+            TradeSize::PoolPercent(percent) => write!(f, "{percent}% pool"),
+        }
+    }
+}
+
+/// $100, $1k, $10k, 1%, 5% and 10% of the pool
+pub const DEFAULT_SIZES: [TradeSize; 6] = [
+    TradeSize::Base(100.0),
+    TradeSize::Base(1_000.0),
+    TradeSize::Base(10_000.0),
+    TradeSize::PoolPercent(1.0),
+    TradeSize::PoolPercent(5.0),
+    TradeSize::PoolPercent(10.0),
+];
+
+/// A simulated trade against the pool
+#[derive(Debug, Clone, PartialEq)]
+pub struct DepthLevel {
+    pub size: TradeSize,
+    /// Coins received when buying, sold when selling
+    pub coins: f64,
+    /// Base currency spent when buying, received when selling
+    pub base: f64,
+    pub average_price: f64,
+    /// Price of the coin after the trade
+    pub ending_price: f64,
+    /// Change from the spot price to the ending price, in %
+    pub impact: f64,
+}
+
+/// Buy and sell side depth of a coin
+#[derive(Debug, Clone, PartialEq)]
+pub struct DepthProfile {
+    pub symbol: Symbol,
+    pub spot_price: f64,
+    pub buy: Vec<DepthLevel>,
+    pub sell: Vec<DepthLevel>,
+}
+
+/// Simulates buying and selling each of `sizes` against `pool`
+///
+/// A `TradeSize::Base` sell sells the coins worth that amount at the spot price.
+pub fn depth_profile(symbol: Symbol, pool: &PoolInfo, sizes: &[TradeSize]) -> DepthProfile {
+    let spot_price = pool.base_currency_amount / pool.coin_amount;
+    let level = |size, coins: f64, base: f64, ending_price: f64| DepthLevel {
+        size,
+        coins,
+        base,
+        average_price: base / coins,
+        ending_price,
+        impact: (ending_price - spot_price) / spot_price * 100.0,
+    };
+
+    let buy = sizes
+        .iter()
+        .map(|&size| {
+            let base = match size {
+                TradeSize::Base(base) => base,
+                TradeSize::PoolPercent(p) => pool.base_currency_amount * p / 100.0,
+            };
+            level(
+                size,
+                pool.buy_output(base),
+                base,
+                pool.price_after_buy(base),
+            )
+        })
+        .collect();
+
+    let sell = sizes
+        .iter()
+        .map(|&size| {
+            let coins = match size {
+                TradeSize::Base(base) => base / spot_price,
+                TradeSize::PoolPercent(p) => pool.coin_amount * p / 100.0,
+            };
+            level(
+                size,
+                coins,
+                pool.sell_proceeds(coins),
+                pool.price_after_sell(coins),
+            )
+        })
+        .collect();
+
+    DepthProfile {
+        symbol,
+        spot_price,
+        buy,
+        sell,
+    }
+}
+
+impl DepthProfile {
+    /// Renders the profile as a plain text table
+    pub fn to_table(&self) -> String {
+        let mut table = format!(
+            "*{} depth, spot price {:.8}\n",
+            self.symbol, self.spot_price
+        );
+        let _ = writeln!(
+            table,
+            "{:<5} {:>12} {:>18} {:>14} {:>14} {:>14} {:>9}",
+            "Side", "Size", "Coins", "Base", "Avg price", "End price", "Impact"
+        );
+        let sides = [("BUY", &self.buy), ("SELL", &self.sell)];
+        for (side, levels) in sides {
+            for l in levels {
+                let _ = writeln!(
+                    table,
+                    "{:<5} {:>12} {:>18.2} {:>14.2} {:>14.8} {:>14.8} {:>8.2}%",
+                    side,
+                    l.size.to_string(),
+                    l.coins,
+                    l.base,
+                    l.average_price,
+                    l.ending_price,
+                    l.impact
+                );
+            }
+        }
+        table
+    }
+}
+
+impl Display for DepthProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_table())
+    }
+}
@@ -0,0 +1,616 @@
+#![deny(clippy::all)]
+//! Filter expression language, used by the [`crate::screener`]
+//!
+//! ```text
+//! market_cap > 10000 and change24h > 50 and top10_share < 60% and age < 2h
+//! ```
+//!
+//! - Numbers, strings (`"TEST"`), `true` and `false`, and variables (`market_cap`)
+//! - Numbers can have a unit: `%` divides by 100, `s`, `m`, `h`, `d` and `w` convert a duration
+//!   to seconds. `_` can separate digits (`10_000`)
+//! - `+ - * /`, comparisons `< <= > >= == !=`, and `and`, `or`, `not` (or `&&`, `||`, `!`)
+//!
+//! Expressions deeper than [`MAX_DEPTH`] don't parse.
+//!
+//! Variables are resolved while evaluating. A variable that isn't available yet makes the
+//! expression [`ExprError::Unavailable`], unless the result doesn't depend on it (`false and x`).
+use std::collections::BTreeSet;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Value of an expression or a variable
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Number(f64),
+    Bool(bool),
+    Text(String),
+}
+
+impl Value {
+    fn type_name(&self) -> &'static str {
+        match self {
+            Value::Number(_) => "number",
+            Value::Bool(_) => "boolean",
+            Value::Text(_) => "string",
+        }
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Number(n) => write!(f, "{n}"),
+            Value::Bool(b) => write!(f, "{b}"),
+            Value::Text(s) => write!(f, "{s:?}"),
+        }
+    }
+}
+
+impl From<f64> for Value {
+    fn from(n: f64) -> Self {
+        Value::Number(n)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Self {
+        Value::Bool(b)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Self {
+        Value::Text(s.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Self {
+        Value::Text(s)
+    }
+}
+
+#[derive(Debug, Error, Clone, PartialEq)]
+pub enum ExprError {
+    #[error("Syntax error at {position}: {message}")]
+    Parse { position: usize, message: String },
+
+    #[error("Unknown variable {0:?}")]
+    UnknownVariable(String),
+
+    /// The variable exists but its value isn't known (yet)
+    #[error("Variable {0:?} is not available")]
+    Unavailable(String),
+
+    #[error("Type error: {0}")]
+    Type(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+    And,
+    Or,
+}
+
+impl BinaryOp {
+    fn as_str(&self) -> &'static str {
+        match self {
+            BinaryOp::Add => "+",
+            BinaryOp::Sub => "-",
+            BinaryOp::Mul => "*",
+            BinaryOp::Div => "/",
+            BinaryOp::Lt => "<",
+            BinaryOp::Le => "<=",
+            BinaryOp::Gt => ">",
+            BinaryOp::Ge => ">=",
+            BinaryOp::Eq => "==",
+            BinaryOp::Ne => "!=",
+            BinaryOp::And => "and",
+            BinaryOp::Or => "or",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Literal(Value),
+    Variable(String),
+    Neg(Box<Node>),
+    Not(Box<Node>),
+    Binary(BinaryOp, Box<Node>, Box<Node>),
+}
+
+/// A parsed expression
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expr {
+    source: String,
+    root: Node,
+}
+
+impl Expr {
+    pub fn parse(source: &str) -> Result<Self, ExprError> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser {
+            tokens,
+            position: 0,
+            end: source.len(),
+            nesting: 0,
+        };
+        let (root, _) = parser.or()?;
+        if let Some((position, token)) = parser.tokens.get(parser.position) {
+            return Err(parse_error(*position, format!("unexpected {token}")));
+        }
+
+        Ok(Self {
+            source: source.trim().to_string(),
+            root,
+        })
+    }
+
+    /// The source the expression was parsed from
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// Names of the variables used by the expression
+    pub fn variables(&self) -> BTreeSet<&str> {
+        let mut variables = BTreeSet::new();
+        let mut nodes = vec![&self.root];
+        while let Some(node) = nodes.pop() {
+            match node {
+                Node::Literal(_) => {}
+                Node::Variable(name) => {
+                    variables.insert(name.as_str());
+                }
+                Node::Neg(node) | Node::Not(node) => nodes.push(node),
+                Node::Binary(_, left, right) => nodes.extend([left.as_ref(), right.as_ref()]),
+            }
+        }
+        variables
+    }
+
+    /// Evaluates the expression, `variables` returns `None` for unavailable variables
+    pub fn eval<F>(&self, variables: &F) -> Result<Value, ExprError>
+    where
+        F: Fn(&str) -> Option<Value>,
+    {
+        eval(&self.root, variables)
+    }
+
+    /// Evaluates an expression that must return a boolean (e.g. a filter)
+    pub fn eval_bool<F>(&self, variables: &F) -> Result<bool, ExprError>
+    where
+        F: Fn(&str) -> Option<Value>,
+    {
+        match self.eval(variables)? {
+            Value::Bool(b) => Ok(b),
+            other => Err(ExprError::Type(format!(
+                "expected a boolean, got a {}",
+                other.type_name()
+            ))),
+        }
+    }
+
+    /// Evaluates an expression that must return a number (e.g. a sort key)
+    pub fn eval_number<F>(&self, variables: &F) -> Result<f64, ExprError>
+    where
+        F: Fn(&str) -> Option<Value>,
+    {
+        match self.eval(variables)? {
+            Value::Number(n) => Ok(n),
+            other => Err(ExprError::Type(format!(
+                "expected a number, got a {}",
+                other.type_name()
+            ))),
+        }
+    }
+}
+
+impl FromStr for Expr {
+    type Err = ExprError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+fn eval<F>(node: &Node, variables: &F) -> Result<Value, ExprError>
+where
+    F: Fn(&str) -> Option<Value>,
+{
+    match node {
+        Node::Literal(value) => Ok(value.clone()),
+        Node::Variable(name) => variables(name).ok_or_else(|| ExprError::Unavailable(name.clone())),
+        Node::Neg(node) => Ok(Value::Number(-number(eval(node, variables)?, "-")?)),
+        Node::Not(node) => Ok(Value::Bool(!boolean(eval(node, variables)?, "not")?)),
+        Node::Binary(op @ (BinaryOp::And | BinaryOp::Or), left, right) => {
+            // The side that decides the result wins over an unavailable one
+            let decisive = *op == BinaryOp::Or;
+            let left = eval(left, variables).and_then(|v| boolean(v, op.as_str()));
+            match &left {
+                Ok(b) if *b == decisive => return Ok(Value::Bool(decisive)),
+                Err(e) if !matches!(e, ExprError::Unavailable(_)) => return Err(e.clone()),
+                _ => {}
+            }
+            let right = boolean(eval(right, variables)?, op.as_str())?;
+            if right == decisive {
+                return Ok(Value::Bool(decisive));
+            }
+            left.map(Value::Bool)
+        }
+        Node::Binary(op, left, right) => {
+            let left = eval(left, variables)?;
+            let right = eval(right, variables)?;
+            binary(*op, left, right)
+        }
+    }
+}
+
+fn binary(op: BinaryOp, left: Value, right: Value) -> Result<Value, ExprError> {
+    let value = match (op, &left, &right) {
+        (BinaryOp::Eq, _, _) => Value::Bool(left == right),
+        (BinaryOp::Ne, _, _) => Value::Bool(left != right),
+        (_, Value::Number(l), Value::Number(r)) => match op {
+            BinaryOp::Add => Value::Number(l + r),
+            BinaryOp::Sub => Value::Number(l - r),
+            BinaryOp::Mul => Value::Number(l * r),
+            BinaryOp::Div => Value::Number(l / r),
+            BinaryOp::Lt => Value::Bool(l < r),
+            BinaryOp::Le => Value::Bool(l <= r),
+            BinaryOp::Gt => Value::Bool(l > r),
+            BinaryOp::Ge => Value::Bool(l >= r),
+            _ => unreachable!("handled by the caller"),
+        },
+        (
+            BinaryOp::Lt | BinaryOp::Le | BinaryOp::Gt | BinaryOp::Ge,
+            Value::Text(l),
+            Value::Text(r),
+        ) => Value::Bool(match op {
+            BinaryOp::Lt => l < r,
+            BinaryOp::Le => l <= r,
+            BinaryOp::Gt => l > r,
+            _ => l >= r,
+        }),
+        _ => {
+            return Err(ExprError::Type(format!(
+                "can't apply {} to a {} and a {}",
+                op.as_str(),
+                left.type_name(),
+                right.type_name()
+            )));
+        }
+    };
+    Ok(value)
+}
+
+fn number(value: Value, op: &str) -> Result<f64, ExprError> {
+    match value {
+        Value::Number(n) => Ok(n),
+        other => Err(ExprError::Type(format!(
+            "{op} expects a number, got a {}",
+            other.type_name()
+        ))),
+    }
+}
+
+fn boolean(value: Value, op: &str) -> Result<bool, ExprError> {
+    match value {
+        Value::Bool(b) => Ok(b),
+        other => Err(ExprError::Type(format!(
+            "{op} expects a boolean, got a {}",
+            other.type_name()
+        ))),
+    }
+}
+
+fn parse_error(position: usize, message: impl Into<String>) -> ExprError {
+    ExprError::Parse {
+        position,
+        message: message.into(),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Text(String),
+    Ident(String),
+    Op(&'static str),
+    Open,
+    Close,
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Number(n) => write!(f, "number {n}"),
+            Token::Text(s) => write!(f, "string {s:?}"),
+            Token::Ident(s) => write!(f, "{s:?}"),
+            Token::Op(op) => write!(f, "{op:?}"),
+            Token::Open => f.write_str("\"(\""),
+            Token::Close => f.write_str("\")\""),
+        }
+    }
+}
+
+const OPERATORS: [&str; 14] = [
+    "<=", ">=", "==", "!=", "&&", "||", "<", ">", "=", "!", "+", "-", "*", "/",
+];
+
+fn tokenize(source: &str) -> Result<Vec<(usize, Token)>, ExprError> {
+    let mut tokens = Vec::new();
+    let mut chars = source.char_indices().peekable();
+
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_digit() || c == '.' {
+            let mut end = start;
+            while let Some(&(i, c)) = chars.peek() {
+                if !(c.is_ascii_digit() || c == '.' || c == '_') {
+                    break;
+                }
+                end = i + c.len_utf8();
+                chars.next();
+            }
+            let digits = source[start..end].replace('_', "");
+            let mut value: f64 = digits.parse().map_err(|_| {
+                parse_error(start, format!("invalid number {:?}", &source[start..end]))
+            })?;
+
+            let unit_start = end;
+            while let Some(&(i, c)) = chars.peek() {
+                if !(c.is_ascii_alphanumeric() || c == '%') {
+                    break;
+                }
+                end = i + c.len_utf8();
+                chars.next();
+            }
+            value *= match &source[unit_start..end] {
+                "" => 1.0,
+                "%" => 0.01,
+                "s" => 1.0,
+                "m" => 60.0,
+                "h" => 60.0 * 60.0,
+                "d" => 24.0 * 60.0 * 60.0,
+                "w" => 7.0 * 24.0 * 60.0 * 60.0,
+                unit => return Err(parse_error(unit_start, format!("unknown unit {unit:?}"))),
+            };
+            tokens.push((start, Token::Number(value)));
+        } else if c.is_alphabetic() || c == '_' {
+            let mut end = start;
+            while let Some(&(i, c)) = chars.peek() {
+                if !(c.is_alphanumeric() || c == '_') {
+                    break;
+                }
+                end = i + c.len_utf8();
+                chars.next();
+            }
+            let token = match &source[start..end] {
+                "and" => Token::Op("and"),
+                "or" => Token::Op("or"),
+                "not" => Token::Op("not"),
+                ident => Token::Ident(ident.to_string()),
+            };
+            tokens.push((start, token));
+        } else if c == '"' || c == '\'' {
+            chars.next();
+            let mut text = String::new();
+            loop {
+                match chars.next() {
+                    Some((_, q)) if q == c => break,
+                    Some((_, ch)) => text.push(ch),
+                    None => return Err(parse_error(start, "unterminated string")),
+                }
+            }
+            tokens.push((start, Token::Text(text)));
+        } else if c == '(' || c == ')' {
+            chars.next();
+            tokens.push((start, if c == '(' { Token::Open } else { Token::Close }));
+        } else {
+            let op = OPERATORS
+                .iter()
+                .find(|op| source[start..].starts_with(*op))
+                .ok_or_else(|| parse_error(start, format!("unexpected {c:?}")))?;
+            for _ in 0..op.len() {
+                chars.next();
+            }
+            let op = match *op {
+                "&&" => "and",
+                "||" => "or",
+                "!" => "not",
+                "=" => "==",
+                op => op,
+            };
+            tokens.push((start, Token::Op(op)));
+        }
+    }
+    Ok(tokens)
+}
+
+/// Deepest expression tree, and deepest nesting of parentheses, `not` and `-`, that parse
+///
+/// Parsing and evaluating recurse into the tree, a limit keeps long inputs from overflowing the
+/// stack.
+pub const MAX_DEPTH: usize = 64;
+
+/// A node and the depth of its tree
+type Parsed = Result<(Node, usize), ExprError>;
+
+/// Recursive descent parser, from the lowest precedence (`or`) to the highest
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    position: usize,
+    end: usize,
+    /// Parentheses, `not` and `-` the parser is in
+    nesting: usize,
+}
+
+impl Parser {
+    fn peek_op(&self) -> Option<&'static str> {
+        match self.tokens.get(self.position) {
+            Some((_, Token::Op(op))) => Some(op),
+            _ => None,
+        }
+    }
+
+    fn too_deep(&self) -> ExprError {
+        let position = self
+            .tokens
+            .get(self.position)
+            .map_or(self.end, |(position, _)| *position);
+        parse_error(
+            position,
+            format!("expression is nested deeper than {MAX_DEPTH}"),
+        )
+    }
+
+    /// Checks that the tree of a new node isn't too deep
+    fn node(&self, node: Node, depth: usize) -> Parsed {
+        if depth > MAX_DEPTH {
+            return Err(self.too_deep());
+        }
+        Ok((node, depth))
+    }
+
+    /// Parses with `parse` one level deeper
+    fn nested(&mut self, parse: fn(&mut Self) -> Parsed) -> Parsed {
+        if self.nesting >= MAX_DEPTH {
+            return Err(self.too_deep());
+        }
+        self.nesting += 1;
+        let parsed = parse(self);
+        self.nesting -= 1;
+        parsed
+    }
+
+    fn binary(
+        &mut self,
+        ops: &[(&str, BinaryOp)],
+        next: fn(&mut Self) -> Parsed,
+        chain: bool,
+    ) -> Parsed {
+        let (mut node, mut depth) = next(self)?;
+        while let Some(&(_, op)) = self
+            .peek_op()
+            .and_then(|token| ops.iter().find(|(o, _)| *o == token))
+        {
+            self.position += 1;
+            let (right, right_depth) = next(self)?;
+            (node, depth) = self.node(
+                Node::Binary(op, Box::new(node), Box::new(right)),
+                depth.max(right_depth) + 1,
+            )?;
+            if !chain {
+                break;
+            }
+        }
+        Ok((node, depth))
+    }
+
+    fn or(&mut self) -> Parsed {
+        self.binary(&[("or", BinaryOp::Or)], Self::and, true)
+    }
+
+    fn and(&mut self) -> Parsed {
+        self.binary(&[("and", BinaryOp::And)], Self::not, true)
+    }
+
+    fn not(&mut self) -> Parsed {
+        if self.peek_op() == Some("not") {
+            self.position += 1;
+            let (node, depth) = self.nested(Self::not)?;
+            return self.node(Node::Not(Box::new(node)), depth + 1);
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Parsed {
+        self.binary(
+            &[
+                ("<", BinaryOp::Lt),
+                ("<=", BinaryOp::Le),
+                (">", BinaryOp::Gt),
+                (">=", BinaryOp::Ge),
+                ("==", BinaryOp::Eq),
+                ("!=", BinaryOp::Ne),
+            ],
+            Self::sum,
+            false,
+        )
+    }
+
+    fn sum(&mut self) -> Parsed {
+        self.binary(
+            &[("+", BinaryOp::Add), ("-", BinaryOp::Sub)],
+            Self::product,
+            true,
+        )
+    }
+
+    fn product(&mut self) -> Parsed {
+        self.binary(
+            &[("*", BinaryOp::Mul), ("/", BinaryOp::Div)],
+            Self::unary,
+            true,
+        )
+    }
+
+    fn unary(&mut self) -> Parsed {
+        if self.peek_op() == Some("-") {
+            self.position += 1;
+            let (node, depth) = self.nested(Self::unary)?;
+            return self.node(Node::Neg(Box::new(node)), depth + 1);
+        }
+        self.atom()
+    }
+
+    fn atom(&mut self) -> Parsed {
+        let Some((position, token)) = self.tokens.get(self.position).cloned() else {
+            return Err(parse_error(self.end, "unexpected end of expression"));
+        };
+        self.position += 1;
+
+        let node = match token {
+            Token::Number(n) => Node::Literal(Value::Number(n)),
+            Token::Text(s) => Node::Literal(Value::Text(s)),
+            Token::Ident(ident) => match ident.as_str() {
+                "true" => Node::Literal(Value::Bool(true)),
+                "false" => Node::Literal(Value::Bool(false)),
+                _ => Node::Variable(ident),
+            },
+            Token::Open => {
+                let parsed = self.nested(Self::or)?;
+                return match self.tokens.get(self.position) {
+                    Some((_, Token::Close)) => {
+                        self.position += 1;
+                        Ok(parsed)
+                    }
+                    Some((position, token)) => Err(parse_error(
+                        *position,
+                        format!("expected \")\", got {token}"),
+                    )),
+                    None => Err(parse_error(self.end, "missing \")\"")),
+                };
+            }
+            token => return Err(parse_error(position, format!("unexpected {token}"))),
+        };
+        Ok((node, 1))
+    }
+}
@@ -0,0 +1,246 @@
+#![deny(clippy::all)]
+//! Typed identifiers of coins, users and prediction market questions
+//!
+//! Rugplay isn't consistent about their JSON types (user ids are sometimes numbers, sometimes
+//! strings), so [`UserId`] and [`QuestionId`] accept both forms.
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::borrow::Borrow;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Maximum length of a coin symbol
+pub const MAX_SYMBOL_LEN: usize = 12;
+
+/// A coin symbol, normalized to uppercase (e.g. `TEST`)
+///
+/// The `*` prefix Rugplay shows in front of symbols is accepted and removed. Symbols given by the
+/// user are validated, symbols returned by Rugplay are only normalized.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Symbol(String);
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+#[error("Invalid coin symbol {0:?}")]
+pub struct InvalidSymbol(pub String);
+
+impl Symbol {
+    pub fn new(symbol: &str) -> Result<Self, InvalidSymbol> {
+        let normalized = normalize(symbol);
+        let valid = !normalized.is_empty()
+            && normalized.len() <= MAX_SYMBOL_LEN
+            && normalized.bytes().all(|b| b.is_ascii_alphanumeric());
+
+        if valid {
+            Ok(Self(normalized))
+        } else {
+            Err(InvalidSymbol(symbol.to_string()))
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
+
+fn normalize(symbol: &str) -> String {
+    symbol.trim().trim_start_matches('*').to_ascii_uppercase()
+}
+
+impl Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromStr for Symbol {
+    type Err = InvalidSymbol;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
+impl TryFrom<&str> for Symbol {
+    type Error = InvalidSymbol;
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Self::new(value)
+    }
+}
+
+impl TryFrom<String> for Symbol {
+    type Error = InvalidSymbol;
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::new(&value)
+    }
+}
+
+impl AsRef<str> for Symbol {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for Symbol {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl PartialEq<str> for Symbol {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&str> for Symbol {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+impl Serialize for Symbol {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Symbol {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // A symbol off the input rules mustn't fail a whole response
+        let symbol = String::deserialize(deserializer)?;
+        Ok(Symbol(normalize(&symbol)))
+    }
+}
+
+/// Conversion of the symbol arguments of the endpoints
+///
+/// Implemented for string types, which are validated, and for [`Symbol`].
+pub trait IntoSymbol {
+    fn into_symbol(self) -> Result<Symbol, InvalidSymbol>;
+}
+
+impl IntoSymbol for Symbol {
+    fn into_symbol(self) -> Result<Symbol, InvalidSymbol> {
+        Ok(self)
+    }
+}
+
+impl IntoSymbol for &Symbol {
+    fn into_symbol(self) -> Result<Symbol, InvalidSymbol> {
+        Ok(self.clone())
+    }
+}
+
+impl IntoSymbol for &str {
+    fn into_symbol(self) -> Result<Symbol, InvalidSymbol> {
+        Symbol::new(self)
+    }
+}
+
+impl IntoSymbol for String {
+    fn into_symbol(self) -> Result<Symbol, InvalidSymbol> {
+        Symbol::new(&self)
+    }
+}
+
+impl IntoSymbol for &String {
+    fn into_symbol(self) -> Result<Symbol, InvalidSymbol> {
+        Symbol::new(self)
+    }
+}
+
+/// Defines a numeric id that deserializes from JSON numbers and numeric strings
+macro_rules! numeric_id {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name(pub u64);
+
+        impl $name {
+            pub fn get(self) -> u64 {
+                self.0
+            }
+        }
+
+        impl From<u64> for $name {
+            fn from(id: u64) -> Self {
+                Self(id)
+            }
+        }
+
+        impl From<$name> for u64 {
+            fn from(id: $name) -> Self {
+                id.0
+            }
+        }
+
+        impl PartialEq<u64> for $name {
+            fn eq(&self, other: &u64) -> bool {
+                self.0 == *other
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                Display::fmt(&self.0, f)
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = std::num::ParseIntError;
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                s.trim().parse().map(Self)
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_u64(self.0)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                deserializer.deserialize_any(IdVisitor).map(Self)
+            }
+        }
+    };
+}
+
+numeric_id!(
+    /// Id of a Rugplay user
+    UserId
+);
+
+numeric_id!(
+    /// Id of a prediction market (Hopium) question
+    QuestionId
+);
+
+struct IdVisitor;
+
+impl Visitor<'_> for IdVisitor {
+    type Value = u64;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a non-negative integer or a string containing one")
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<u64, E> {
+        Ok(v)
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<u64, E> {
+        u64::try_from(v).map_err(|_| E::invalid_value(de::Unexpected::Signed(v), &self))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<u64, E> {
+        v.trim()
+            .parse()
+            .map_err(|_| E::invalid_value(de::Unexpected::Str(v), &self))
+    }
+}
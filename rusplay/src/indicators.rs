@@ -0,0 +1,424 @@
+#![deny(clippy::all)]
+//! Technical indicators over [`Candle`] series
+//!
+//! Every indicator can be fed one value at a time (e.g. from live trades) with `update`, or one
+//! candle at a time through [`Indicator::next`]. [`Indicator::batch`] runs it over a whole series,
+//! such as [`crate::models::CoinDetailsResponse::candlestick_data`], and returns one value per
+//! candle, `None` until the indicator has seen enough data.
+use crate::models::{Candle, VolumePoint};
+use std::collections::{BTreeMap, VecDeque};
+
+/// An indicator computed candle by candle
+pub trait Indicator {
+    type Output;
+
+    /// Feeds the next candle, returns the value once enough candles were seen
+    fn next(&mut self, candle: &Candle) -> Option<Self::Output>;
+
+    /// Forgets every candle seen so far
+    fn reset(&mut self);
+
+    /// Feeds every candle, returns one value per candle
+    fn batch(&mut self, candles: &[Candle]) -> Vec<Option<Self::Output>> {
+        candles.iter().map(|c| self.next(c)).collect()
+    }
+}
+
+/// Simple moving average of the last `period` closes
+#[derive(Debug, Clone)]
+pub struct Sma {
+    period: usize,
+    window: VecDeque<f64>,
+    sum: f64,
+}
+
+impl Sma {
+    pub fn new(period: usize) -> Self {
+        let period = period.max(1);
+        Self {
+            period,
+            window: VecDeque::with_capacity(period + 1),
+            sum: 0.0,
+        }
+    }
+
+    pub fn update(&mut self, value: f64) -> Option<f64> {
+        self.window.push_back(value);
+        self.sum += value;
+        if self.window.len() > self.period
+            && let Some(old) = self.window.pop_front()
+        {
+            self.sum -= old;
+        }
+        (self.window.len() == self.period).then(|| self.sum / self.period as f64)
+    }
+}
+
+impl Indicator for Sma {
+    type Output = f64;
+
+    fn next(&mut self, candle: &Candle) -> Option<f64> {
+        self.update(candle.close)
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new(self.period);
+    }
+}
+
+/// Exponential moving average, seeded with the SMA of the first `period` closes
+#[derive(Debug, Clone)]
+pub struct Ema {
+    period: usize,
+    alpha: f64,
+    seed: Sma,
+    value: Option<f64>,
+}
+
+impl Ema {
+    pub fn new(period: usize) -> Self {
+        let period = period.max(1);
+        Self {
+            period,
+            alpha: 2.0 / (period as f64 + 1.0),
+            seed: Sma::new(period),
+            value: None,
+        }
+    }
+
+    pub fn update(&mut self, value: f64) -> Option<f64> {
+        self.value = match self.value {
+            Some(ema) => Some(ema + self.alpha * (value - ema)),
+            None => self.seed.update(value),
+        };
+        self.value
+    }
+
+    /// The last value, if the EMA is warmed up
+    pub fn value(&self) -> Option<f64> {
+        self.value
+    }
+}
+
+impl Indicator for Ema {
+    type Output = f64;
+
+    fn next(&mut self, candle: &Candle) -> Option<f64> {
+        self.update(candle.close)
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new(self.period);
+    }
+}
+
+/// Average of gains and losses with Wilder's smoothing
+#[derive(Debug, Clone)]
+struct Wilder {
+    period: usize,
+    count: usize,
+    value: f64,
+}
+
+impl Wilder {
+    fn new(period: usize) -> Self {
+        Self {
+            period: period.max(1),
+            count: 0,
+            value: 0.0,
+        }
+    }
+
+    fn update(&mut self, value: f64) -> Option<f64> {
+        let n = self.period as f64;
+        if self.count < self.period {
+            self.count += 1;
+            self.value += value / n;
+        } else {
+            self.value = (self.value * (n - 1.0) + value) / n;
+        }
+        (self.count == self.period).then_some(self.value)
+    }
+}
+
+/// Relative strength index (0 to 100) with Wilder's smoothing
+#[derive(Debug, Clone)]
+pub struct Rsi {
+    period: usize,
+    previous: Option<f64>,
+    gains: Wilder,
+    losses: Wilder,
+}
+
+impl Rsi {
+    pub fn new(period: usize) -> Self {
+        Self {
+            period,
+            previous: None,
+            gains: Wilder::new(period),
+            losses: Wilder::new(period),
+        }
+    }
+
+    pub fn update(&mut self, value: f64) -> Option<f64> {
+        let change = value - self.previous.replace(value)?;
+        let gain = self.gains.update(change.max(0.0));
+        let loss = self.losses.update((-change).max(0.0));
+        let (gain, loss) = (gain?, loss?);
+
+        Some(if loss == 0.0 {
+            if gain == 0.0 { 50.0 } else { 100.0 }
+        } else {
+            100.0 - 100.0 / (1.0 + gain / loss)
+        })
+    }
+}
+
+impl Indicator for Rsi {
+    type Output = f64;
+
+    fn next(&mut self, candle: &Candle) -> Option<f64> {
+        self.update(candle.close)
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new(self.period);
+    }
+}
+
+/// Value of [`Macd`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MacdOutput {
+    /// Fast EMA minus slow EMA
+    pub macd: f64,
+    /// EMA of `macd`
+    pub signal: f64,
+    /// `macd` minus `signal`
+    pub histogram: f64,
+}
+
+/// Moving average convergence divergence
+#[derive(Debug, Clone)]
+pub struct Macd {
+    fast: Ema,
+    slow: Ema,
+    signal: Ema,
+}
+
+impl Macd {
+    pub fn new(fast: usize, slow: usize, signal: usize) -> Self {
+        Self {
+            fast: Ema::new(fast),
+            slow: Ema::new(slow),
+            signal: Ema::new(signal),
+        }
+    }
+
+    pub fn update(&mut self, value: f64) -> Option<MacdOutput> {
+        let fast = self.fast.update(value);
+        let slow = self.slow.update(value);
+        let macd = fast? - slow?;
+        let signal = self.signal.update(macd)?;
+        Some(MacdOutput {
+            macd,
+            signal,
+            histogram: macd - signal,
+        })
+    }
+}
+
+impl Default for Macd {
+    /// The usual 12, 26, 9 periods
+    fn default() -> Self {
+        Self::new(12, 26, 9)
+    }
+}
+
+impl Indicator for Macd {
+    type Output = MacdOutput;
+
+    fn next(&mut self, candle: &Candle) -> Option<MacdOutput> {
+        self.update(candle.close)
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new(self.fast.period, self.slow.period, self.signal.period);
+    }
+}
+
+/// Value of [`BollingerBands`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bands {
+    pub upper: f64,
+    pub middle: f64,
+    pub lower: f64,
+}
+
+/// SMA of the closes, plus and minus `k` standard deviations
+#[derive(Debug, Clone)]
+pub struct BollingerBands {
+    k: f64,
+    sma: Sma,
+}
+
+impl BollingerBands {
+    pub fn new(period: usize, k: f64) -> Self {
+        Self {
+            k,
+            sma: Sma::new(period),
+        }
+    }
+
+    pub fn update(&mut self, value: f64) -> Option<Bands> {
+        let middle = self.sma.update(value)?;
+        let variance = self
+            .sma
+            .window
+            .iter()
+            .map(|v| (v - middle).powi(2))
+            .sum::<f64>()
+            / self.sma.period as f64;
+        let width = self.k * variance.sqrt();
+        Some(Bands {
+            upper: middle + width,
+            middle,
+            lower: middle - width,
+        })
+    }
+}
+
+impl Default for BollingerBands {
+    /// 20 periods, 2 standard deviations
+    fn default() -> Self {
+        Self::new(20, 2.0)
+    }
+}
+
+impl Indicator for BollingerBands {
+    type Output = Bands;
+
+    fn next(&mut self, candle: &Candle) -> Option<Bands> {
+        self.update(candle.close)
+    }
+
+    fn reset(&mut self) {
+        self.sma.reset();
+    }
+}
+
+/// Average true range with Wilder's smoothing
+///
+/// Only works over candles, since the true range needs the high and low prices.
+#[derive(Debug, Clone)]
+pub struct Atr {
+    period: usize,
+    previous_close: Option<f64>,
+    average: Wilder,
+}
+
+impl Atr {
+    pub fn new(period: usize) -> Self {
+        Self {
+            period,
+            previous_close: None,
+            average: Wilder::new(period),
+        }
+    }
+}
+
+impl Indicator for Atr {
+    type Output = f64;
+
+    fn next(&mut self, candle: &Candle) -> Option<f64> {
+        let range = candle.high - candle.low;
+        let true_range = match self.previous_close.replace(candle.close) {
+            Some(close) => range
+                .max((candle.high - close).abs())
+                .max((candle.low - close).abs()),
+            None => range,
+        };
+        self.average.update(true_range)
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new(self.period);
+    }
+}
+
+/// Volume weighted average price since the first candle
+#[derive(Debug, Clone, Default)]
+pub struct Vwap {
+    volume: f64,
+    value: f64,
+}
+
+impl Vwap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a trade (or a candle's typical price and volume), returns `None` until there is volume
+    pub fn update(&mut self, price: f64, volume: f64) -> Option<f64> {
+        self.volume += volume;
+        self.value += price * volume;
+        (self.volume > 0.0).then(|| self.value / self.volume)
+    }
+
+    /// Adds a candle with the volume traded during it
+    pub fn next(&mut self, candle: &Candle, volume: f64) -> Option<f64> {
+        self.update(candle.typical_price(), volume)
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// VWAP at every candle, matching candles and volume points by `time`
+    ///
+    /// Candles without a volume point count as no volume.
+    pub fn batch(&mut self, candles: &[Candle], volumes: &[VolumePoint]) -> Vec<Option<f64>> {
+        let volumes: BTreeMap<u64, f64> = volumes.iter().map(|v| (v.time, v.volume)).collect();
+        candles
+            .iter()
+            .map(|c| self.next(c, volumes.get(&c.time).copied().unwrap_or_default()))
+            .collect()
+    }
+}
+
+/// Rate of change: percentage change of the close over `period` candles
+#[derive(Debug, Clone)]
+pub struct Roc {
+    period: usize,
+    window: VecDeque<f64>,
+}
+
+impl Roc {
+    pub fn new(period: usize) -> Self {
+        let period = period.max(1);
+        Self {
+            period,
+            window: VecDeque::with_capacity(period + 1),
+        }
+    }
+
+    pub fn update(&mut self, value: f64) -> Option<f64> {
+        self.window.push_back(value);
+        if self.window.len() <= self.period {
+            return None;
+        }
+        let old = self.window.pop_front()?;
+        (old != 0.0).then(|| (value - old) / old * 100.0)
+    }
+}
+
+impl Indicator for Roc {
+    type Output = f64;
+
+    fn next(&mut self, candle: &Candle) -> Option<f64> {
+        self.update(candle.close)
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new(self.period);
+    }
+}
@@ -0,0 +1,286 @@
+#![deny(clippy::all)]
+//! New coin launch detection
+//!
+//! [`LaunchWatcher`] polls the market sorted by creation date and emits a [`NewCoin`] for each
+//! coin it hasn't seen before, with its creator, initial pool and first trades.
+use crate::models::{CoinDetail, HoldersResponse, MarketCoin, PoolInfo, Symbol, Trade};
+use crate::{Result, RugplayClient};
+use futures::{Stream, stream};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::time::Duration;
+
+/// A newly launched coin
+#[derive(Debug)]
+pub struct NewCoin {
+    pub coin: CoinDetail,
+    /// Holders and pool when the coin was detected
+    pub holders: HoldersResponse,
+    /// Oldest trades of the coin among the recent trades, oldest first
+    pub first_trades: Vec<Trade>,
+}
+
+impl NewCoin {
+    pub fn symbol(&self) -> &Symbol {
+        &self.coin.symbol
+    }
+
+    /// Username of the creator
+    pub fn creator(&self) -> Option<&str> {
+        self.coin.creator_username.as_deref()
+    }
+
+    pub fn pool(&self) -> &PoolInfo {
+        &self.holders.pool_info
+    }
+
+    /// Base currency in the pool
+    pub fn liquidity(&self) -> f64 {
+        self.holders.pool_info.base_currency_amount
+    }
+}
+
+/// Detects coin launches by polling the market
+///
+/// The first poll only records the coins that already exist. Each later poll reads the newest
+/// coins until it reaches one it has seen, up to `max_pages` pages.
+#[derive(Debug)]
+pub struct LaunchWatcher {
+    client: RugplayClient,
+    seen: BTreeSet<Symbol>,
+    /// New coins that couldn't be fetched, oldest first, retried by the next poll
+    failed: Vec<Symbol>,
+    seeded: bool,
+    poll_interval: Duration,
+    page_size: u32,
+    max_pages: u32,
+    first_trades: usize,
+    trades_limit: u32,
+    creators: Option<BTreeSet<String>>,
+    excluded_creators: BTreeSet<String>,
+    min_liquidity: Option<f64>,
+    max_liquidity: Option<f64>,
+}
+
+impl LaunchWatcher {
+    pub fn new(client: RugplayClient) -> Self {
+        Self {
+            client,
+            seen: BTreeSet::new(),
+            failed: Vec::new(),
+            seeded: false,
+            poll_interval: Duration::from_secs(10),
+            page_size: 20,
+            max_pages: 5,
+            first_trades: 10,
+            trades_limit: 100,
+            creators: None,
+            excluded_creators: BTreeSet::new(),
+            min_liquidity: None,
+            max_liquidity: None,
+        }
+    }
+
+    /// Time between two polls of [`LaunchWatcher::into_stream`] (default: 10s)
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Coins per market page, max 100 (default: 20)
+    pub fn with_page_size(mut self, page_size: u32) -> Self {
+        self.page_size = page_size.clamp(1, 100);
+        self
+    }
+
+    /// Maximum number of market pages read by a poll (default: 5)
+    pub fn with_max_pages(mut self, max_pages: u32) -> Self {
+        self.max_pages = max_pages.max(1);
+        self
+    }
+
+    /// Number of first trades of a [`NewCoin`], taken from the last `trades_limit` trades
+    /// (default: 10 out of 100). 0 doesn't fetch the trades
+    pub fn with_first_trades(mut self, first_trades: usize, trades_limit: u32) -> Self {
+        self.first_trades = first_trades;
+        self.trades_limit = trades_limit;
+        self
+    }
+
+    /// Only emits the coins created by one of these usernames
+    pub fn with_creators<S: Into<String>>(mut self, creators: impl IntoIterator<Item = S>) -> Self {
+        self.creators = Some(creators.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Ignores the coins created by these usernames
+    pub fn with_excluded_creators<S: Into<String>>(
+        mut self,
+        creators: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.excluded_creators
+            .extend(creators.into_iter().map(Into::into));
+        self
+    }
+
+    /// Ignores the coins launched with less base currency in their pool
+    pub fn with_min_liquidity(mut self, liquidity: f64) -> Self {
+        self.min_liquidity = Some(liquidity);
+        self
+    }
+
+    /// Ignores the coins launched with more base currency in their pool
+    pub fn with_max_liquidity(mut self, liquidity: f64) -> Self {
+        self.max_liquidity = Some(liquidity);
+        self
+    }
+
+    /// Polls the market once and returns the coins launched since the last poll, oldest first
+    ///
+    /// If the market can't be read, none of the new coins are marked as seen so the next poll
+    /// retries them. A new coin whose holders or details can't be fetched is logged and retried
+    /// by the next poll, and the first trades are left empty if the recent trades can't be
+    /// fetched.
+    pub async fn poll(&mut self) -> Result<Vec<NewCoin>> {
+        let mut new_coins: Vec<Symbol> = self
+            .new_market_coins()
+            .await?
+            .into_iter()
+            .map(|c| c.symbol)
+            .collect();
+        if !self.seeded {
+            self.seeded = true;
+            self.seen.extend(new_coins);
+            return Ok(Vec::new());
+        }
+        new_coins.reverse();
+
+        // The coins that failed before are older than the new ones
+        let mut symbols = std::mem::take(&mut self.failed);
+        new_coins.retain(|s| !symbols.contains(s));
+        symbols.extend(new_coins);
+
+        let mut launches = Vec::new();
+        for symbol in symbols {
+            match self.new_coin(&symbol).await {
+                Ok(Some(launch)) => launches.push(launch),
+                Ok(None) => {}
+                Err(e) => {
+                    tracing::warn!("Could not fetch new coin {symbol}, retrying next poll: {e}");
+                    self.failed.push(symbol);
+                    continue;
+                }
+            }
+            self.seen.insert(symbol);
+        }
+
+        if self.first_trades > 0 && !launches.is_empty() {
+            let mut trades: BTreeMap<Symbol, Vec<Trade>> = BTreeMap::new();
+            let recent = match self.client.get_recent_trades(self.trades_limit).await {
+                Ok(recent) => recent.trades,
+                Err(e) => {
+                    tracing::warn!("Could not fetch the first trades of new coins: {e}");
+                    Vec::new()
+                }
+            };
+            // Newest first, reversed so that trades with the same timestamp stay in order
+            for trade in recent.into_iter().rev() {
+                trades
+                    .entry(trade.coin_symbol.clone())
+                    .or_default()
+                    .push(trade);
+            }
+            for launch in &mut launches {
+                let mut first = trades.remove(&launch.coin.symbol).unwrap_or_default();
+                first.sort_by_key(|t| t.timestamp);
+                first.truncate(self.first_trades);
+                launch.first_trades = first;
+            }
+        }
+
+        Ok(launches)
+    }
+
+    /// Fetches a new coin, `None` if the filters exclude it
+    async fn new_coin(&self, symbol: &Symbol) -> Result<Option<NewCoin>> {
+        let holders = self.client.get_holders(symbol, None).await?;
+        let liquidity = holders.pool_info.base_currency_amount;
+        if self.min_liquidity.is_some_and(|min| liquidity < min)
+            || self.max_liquidity.is_some_and(|max| liquidity > max)
+        {
+            return Ok(None);
+        }
+
+        let details = self.client.get_coin_details(symbol, None).await?;
+        if !self.creator_allowed(details.coin.creator_username.as_deref()) {
+            return Ok(None);
+        }
+        Ok(Some(NewCoin {
+            coin: details.coin,
+            holders,
+            first_trades: Vec::new(),
+        }))
+    }
+
+    fn creator_allowed(&self, creator: Option<&str>) -> bool {
+        match creator {
+            Some(creator) => {
+                !self.excluded_creators.contains(creator)
+                    && self.creators.as_ref().is_none_or(|c| c.contains(creator))
+            }
+            None => self.creators.is_none(),
+        }
+    }
+
+    /// The newest coins that haven't been seen, newest first
+    async fn new_market_coins(&self) -> Result<Vec<MarketCoin>> {
+        let limit = self.page_size.to_string();
+        let mut coins = Vec::new();
+
+        for page in 1..=self.max_pages {
+            let page = page.to_string();
+            let response = self
+                .client
+                .get_market(&[
+                    ("sortBy", "createdAt"),
+                    ("sortOrder", "desc"),
+                    ("limit", &limit),
+                    ("page", &page),
+                ])
+                .await?;
+
+            let count = response.coins.len();
+            let mut reached_seen = false;
+            for coin in response.coins {
+                if self.seen.contains(&coin.symbol) {
+                    reached_seen = true;
+                } else if !coins.iter().any(|c: &MarketCoin| c.symbol == coin.symbol) {
+                    coins.push(coin);
+                }
+            }
+            if !self.seeded || reached_seen || count < self.page_size as usize {
+                break;
+            }
+        }
+        Ok(coins)
+    }
+
+    /// Polls forever, yielding each launch. A failed poll yields its error and polling goes on
+    pub fn into_stream(self) -> impl Stream<Item = Result<NewCoin>> {
+        let state = (self, VecDeque::new(), true);
+        stream::unfold(state, |(mut watcher, mut pending, mut first)| async move {
+            loop {
+                if let Some(launch) = pending.pop_front() {
+                    return Some((Ok(launch), (watcher, pending, first)));
+                }
+                if !first {
+                    tokio::time::sleep(watcher.poll_interval).await;
+                }
+                first = false;
+                match watcher.poll().await {
+                    Ok(launches) => pending.extend(launches),
+                    Err(e) => return Some((Err(e), (watcher, pending, first))),
+                }
+            }
+        })
+    }
+}
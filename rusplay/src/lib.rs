@@ -1,19 +1,82 @@
 #![deny(clippy::all)]
-use reqwest::{Client as HttpClient, Method, Url, cookie::Jar};
+use futures::{StreamExt, stream};
+use reqwest::header::{AUTHORIZATION, CONTENT_TYPE, HeaderValue};
+use reqwest::{Client as HttpClient, Method, StatusCode, Url, cookie::Jar};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use thiserror::Error;
-
+use tower::{Layer, Service, ServiceExt};
+use tracing::Instrument;
+
+pub mod alerts;
+pub mod auth;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod cache;
+pub mod candles;
+pub mod cookies;
+pub mod depth;
+pub mod expr;
+pub mod ids;
+pub mod indicators;
+pub mod launches;
+pub mod metrics;
 pub mod models;
+pub mod ratelimit;
+pub mod raw;
+pub mod replay;
+pub mod risk;
+pub mod schema;
+pub mod screener;
+pub mod secret;
+pub mod trade_events;
+pub mod transport;
+use crate::metrics::{ClientMetrics, ErrorKind, MetricsSnapshot};
+use cache::{CacheConfig, ResponseCache};
+use cookies::{PersistentCookieStore, SessionExpiringSoon};
+use depth::{DepthProfile, TradeSize};
+use expr::ExprError;
+use ids::{IntoSymbol, InvalidSymbol};
 use models::*;
+use ratelimit::RateLimiter;
+use raw::{RawClient, RawResponse};
+use schema::{SchemaMode, UnknownFields};
+use screener::{ScreenedCoin, Screener};
+pub use secret::Secret;
+use transport::{BoxError, HttpRequest, HttpResponse, ReqwestTransport, Transport};
+
+/// Number of concurrent requests of the `*_many` endpoints
+pub const DEFAULT_BATCH_CONCURRENCY: usize = 8;
 
 /// An asyncronous Rugplay user instance
 #[derive(Clone)]
 pub struct RugplayClient {
     base_url: String,
-    token: String,
-    http: HttpClient,
+    token: Secret,
+    /// `Authorization` header of `token`, marked as sensitive
+    auth: HeaderValue,
+    /// Whether cookies were given to the constructor, for clients without a cookie store
+    has_cookie: bool,
+    cookie_store: Option<Arc<PersistentCookieStore>>,
+    schema_mode: SchemaMode,
+    cache: Option<Arc<ResponseCache>>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    batch_concurrency: usize,
+    metrics: Arc<ClientMetrics>,
+    transport: Transport,
+}
+
+impl std::fmt::Debug for RugplayClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RugplayClient")
+            .field("base_url", &self.base_url)
+            .field("token", &self.token)
+            .field("has_cookie", &self.has_cookie())
+            .finish_non_exhaustive()
+    }
 }
 
 /// Errors that the API can generate
@@ -22,6 +85,21 @@ pub enum RugplayError {
     #[error("HTTP request failed: {0}")]
     Request(#[from] reqwest::Error),
 
+    #[error("HTTP transport failed: {0}")]
+    Transport(BoxError),
+
+    #[error("Invalid request: {0}")]
+    InvalidRequest(String),
+
+    #[error(transparent)]
+    InvalidSymbol(#[from] InvalidSymbol),
+
+    #[error("Invalid expression: {0}")]
+    Expr(#[from] ExprError),
+
+    #[error("Failed to serialize request body: {0}")]
+    Serialize(serde_json::Error),
+
     #[error("Failed to deserialize JSON: {source}\nResponse text: {text}")]
     Deserialize {
         source: serde_json::Error,
@@ -31,18 +109,71 @@ pub enum RugplayError {
     #[error("Invalid response: {0}")]
     InvalidResponse(String),
 
+    #[error("Unknown fields in {endpoint} response: {}", fields.join(", "))]
+    UnknownFields {
+        endpoint: &'static str,
+        fields: Vec<String>,
+    },
+
     #[error("No cookie provided")]
     NoCookie,
+
+    #[error("Session cookie is invalid or expired")]
+    InvalidSession,
+}
+
+impl RugplayError {
+    /// Whether the error is caused by missing or rejected credentials (HTTP 401 or 403)
+    pub fn is_auth_error(&self) -> bool {
+        match self {
+            RugplayError::Request(e) => matches!(
+                e.status(),
+                Some(StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN)
+            ),
+            RugplayError::NoCookie | RugplayError::InvalidSession => true,
+            _ => false,
+        }
+    }
+
+    /// The kind of error, used to label metrics
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            RugplayError::Request(e) if e.is_status() => ErrorKind::Http,
+            RugplayError::Request(e) if e.is_timeout() => ErrorKind::Timeout,
+            RugplayError::Transport(e) if is_timeout(e.as_ref()) => ErrorKind::Timeout,
+            RugplayError::Request(_)
+            | RugplayError::Transport(_)
+            | RugplayError::InvalidRequest(_)
+            | RugplayError::InvalidSymbol(_)
+            | RugplayError::Expr(_) => ErrorKind::Request,
+            RugplayError::Serialize(_) => ErrorKind::Serialize,
+            RugplayError::Deserialize { .. } => ErrorKind::Deserialize,
+            RugplayError::InvalidResponse(_) => ErrorKind::InvalidResponse,
+            RugplayError::UnknownFields { .. } => ErrorKind::UnknownFields,
+            RugplayError::NoCookie => ErrorKind::NoCookie,
+            RugplayError::InvalidSession => ErrorKind::InvalidSession,
+        }
+    }
 }
 
 pub type Result<T> = std::result::Result<T, RugplayError>;
 
+/// Results of a batch request by symbol
+///
+/// Valid symbols are keyed by their normalized form, invalid ones by the string that was given.
+pub type BatchResult<T> = BTreeMap<String, Result<T>>;
+
 #[derive(Debug, Error)]
 pub enum ClientCreateError {
     #[error("Invalid URL")]
     InvalidUrl(#[from] url::ParseError),
     #[error("Could not build HTTP Client")]
     ReqwestError(#[from] reqwest::Error),
+    #[error("The API key can't be sent in a header")]
+    InvalidToken,
+    #[cfg(feature = "blocking")]
+    #[error("Could not start the runtime of the blocking client: {0}")]
+    Runtime(std::io::Error),
 }
 
 impl RugplayClient {
@@ -50,80 +181,402 @@ impl RugplayClient {
     ///
     /// The cookie is necessary for running non-official API calls
     pub fn new(
-        token: impl Into<String>,
+        token: impl Into<Secret>,
         cookie: Option<String>,
         url: Option<&str>,
+    ) -> std::result::Result<Self, ClientCreateError> {
+        Self::new_with_cookies(token, cookie, url)
+    }
+
+    /// Create a new Client with a token and any number of cookies (`name=value` strings)
+    pub fn new_with_cookies(
+        token: impl Into<Secret>,
+        cookies: impl IntoIterator<Item = impl Into<Secret>>,
+        url: Option<&str>,
     ) -> std::result::Result<Self, ClientCreateError> {
         let jar = Arc::new(Jar::default());
         let base_url: Url = Url::from_str(url.unwrap_or("https://rugplay.com/api/v1"))?;
-        // Insert the auth cookie
-        if let Some(cookie) = cookie {
-            let cookie_value = cookie;
-            jar.add_cookie_str(&cookie_value, &base_url);
+        // Insert the auth cookies
+        let mut has_cookie = false;
+        for cookie in cookies {
+            jar.add_cookie_str(cookie.into().expose(), &base_url);
+            has_cookie = true;
         }
 
         let http = HttpClient::builder().cookie_provider(jar).build()?;
+        let token = token.into();
 
         Ok(Self {
             base_url: url.unwrap_or("https://rugplay.com/api/v1").into(),
-            token: token.into(),
-            http,
+            auth: bearer(&token)?,
+            token,
+            has_cookie,
+            cookie_store: None,
+            schema_mode: SchemaMode::default(),
+            cache: None,
+            rate_limiter: None,
+            batch_concurrency: DEFAULT_BATCH_CONCURRENCY,
+            metrics: Arc::default(),
+            transport: Transport::new(ReqwestTransport::new(http)),
         })
     }
 
-    async fn get<T: for<'de> Deserialize<'de>>(
+    /// Create a new Client whose cookies are kept in a [`PersistentCookieStore`]
+    ///
+    /// Cookies refreshed by Rugplay are saved to the store's file as they arrive.
+    pub fn new_with_cookie_store(
+        token: impl Into<Secret>,
+        store: Arc<PersistentCookieStore>,
+        url: Option<&str>,
+    ) -> std::result::Result<Self, ClientCreateError> {
+        let base_url = url.unwrap_or("https://rugplay.com/api/v1");
+        Url::from_str(base_url)?;
+
+        let http = HttpClient::builder()
+            .cookie_provider(store.clone())
+            .build()?;
+        let token = token.into();
+
+        Ok(Self {
+            base_url: base_url.into(),
+            auth: bearer(&token)?,
+            token,
+            has_cookie: false,
+            cookie_store: Some(store),
+            schema_mode: SchemaMode::default(),
+            cache: None,
+            rate_limiter: None,
+            batch_concurrency: DEFAULT_BATCH_CONCURRENCY,
+            metrics: Arc::default(),
+            transport: Transport::new(ReqwestTransport::new(http)),
+        })
+    }
+
+    /// Whether the client has cookies to send, read from the cookie store if it has one
+    pub(crate) fn has_cookie(&self) -> bool {
+        match &self.cookie_store {
+            Some(store) => store.cookies().iter().any(|c| !c.is_expired()),
+            None => self.has_cookie,
+        }
+    }
+
+    /// Returns [`SessionExpiringSoon`] if the stored session cookie expires within `within`
+    ///
+    /// Clients without a [`PersistentCookieStore`] can't know the expiry and always return `Ok`.
+    pub fn check_session_expiry(
         &self,
+        within: std::time::Duration,
+    ) -> std::result::Result<(), SessionExpiringSoon> {
+        match &self.cookie_store {
+            Some(store) => store.check_expiry(within),
+            None => Ok(()),
+        }
+    }
+
+    /// Sets how unknown fields in responses are handled (default: [`SchemaMode::Lenient`])
+    pub fn with_schema_mode(mut self, schema_mode: SchemaMode) -> Self {
+        self.schema_mode = schema_mode;
+        self
+    }
+
+    /// Sends at most `requests` requests every `per`, across all clones of this client
+    pub fn with_rate_limit(mut self, requests: u32, per: Duration) -> Self {
+        self.rate_limiter = Some(Arc::new(RateLimiter::new(requests, per)));
+        self
+    }
+
+    /// Maximum number of concurrent requests of the `*_many` endpoints
+    /// (default: [`DEFAULT_BATCH_CONCURRENCY`])
+    pub fn with_batch_concurrency(mut self, concurrency: usize) -> Self {
+        self.batch_concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Wraps the HTTP transport with a tower layer (timeout, concurrency limit, custom auth...)
+    ///
+    /// Layers are applied on top of the previous ones, so the last one added sees the request
+    /// first. Caching and the rate limiter of the client run before any layer.
+    pub fn with_layer<L>(mut self, layer: L) -> Self
+    where
+        L: Layer<Transport>,
+        L::Service: Service<HttpRequest, Response = HttpResponse> + Clone + Send + Sync + 'static,
+        <L::Service as Service<HttpRequest>>::Error: Into<BoxError>,
+        <L::Service as Service<HttpRequest>>::Future: Send + 'static,
+    {
+        self.transport = Transport::new(layer.layer(self.transport));
+        self
+    }
+
+    /// Replaces the HTTP transport
+    ///
+    /// The default transport (reqwest) handles the cookies given to the constructor, a
+    /// replacement has to send them itself.
+    pub fn with_transport<S>(mut self, service: S) -> Self
+    where
+        S: Service<HttpRequest, Response = HttpResponse> + Clone + Send + Sync + 'static,
+        S::Error: Into<BoxError>,
+        S::Future: Send + 'static,
+    {
+        self.transport = Transport::new(service);
+        self
+    }
+
+    /// Caches the responses of read-only endpoints in memory
+    ///
+    /// Concurrent identical requests share a single call to Rugplay, and a successful
+    /// [`RugplayClient::trade`] invalidates the cached data of the traded coin.
+    pub fn with_cache(mut self, config: CacheConfig) -> Self {
+        self.cache = Some(Arc::new(ResponseCache::new(config)));
+        self
+    }
+
+    /// Removes the cached responses about a coin (details and holders)
+    pub fn invalidate_coin(&self, symbol: &Symbol) {
+        if let Some(cache) = &self.cache {
+            cache.invalidate_coin(symbol.as_str());
+        }
+    }
+
+    /// Removes every cached response
+    pub fn clear_cache(&self) {
+        if let Some(cache) = &self.cache {
+            cache.clear();
+        }
+    }
+
+    /// Returns a snapshot of the request metrics, shared by all clones of this client
+    pub fn metrics(&self) -> MetricsSnapshot {
+        self.metrics.snapshot()
+    }
+
+    /// Clears the request metrics
+    pub fn reset_metrics(&self) {
+        self.metrics.reset()
+    }
+
+    /// Returns a view of this client whose endpoints also return the raw HTTP response
+    pub fn with_raw(&self) -> RawClient<'_> {
+        RawClient::new(self)
+    }
+
+    /// Calls any GET endpoint and returns the untyped JSON body
+    ///
+    /// Escape hatch for endpoints this crate doesn't cover yet.
+    ///
+    /// ## Arguments
+    /// - endpoint - Path relative to the API base URL (e.g. `"top"` or `"../trades/recent"`)
+    /// - params - Query parameters
+    pub async fn get_json(
+        &self,
+        endpoint: &str,
+        params: Option<&[(&str, &str)]>,
+    ) -> Result<serde_json::Value> {
+        self.with_raw()
+            .get_json(endpoint, params)
+            .await
+            .map(RawResponse::into_inner)
+    }
+
+    pub(crate) async fn get<T: for<'de> Deserialize<'de> + UnknownFields>(
+        &self,
+        name: &'static str,
         endpoint: &str,
         params: Option<&[(&str, &str)]>,
-    ) -> Result<T> {
-        let url = format!("{}/{}", self.base_url, endpoint);
-        let req = self
-            .http
-            .request(Method::GET, &url)
-            .bearer_auth(&self.token)
-            .query(params.unwrap_or_default());
-
-        let resp = req.send().await?.error_for_status()?;
-        let text = resp.text().await?;
-
-        match serde_json::from_str(&text) {
-            Ok(data) => Ok(data),
-            Err(e) => Err(RugplayError::Deserialize { source: e, text }),
+    ) -> Result<RawResponse<T>> {
+        let Some((cache, ttl)) = self
+            .cache
+            .as_ref()
+            .and_then(|c| c.ttl_of(name).map(|ttl| (c, ttl)))
+        else {
+            return self
+                .request(name, Method::GET, endpoint, params, None)
+                .await;
+        };
+
+        let key = cache::cache_key(endpoint, params);
+        // Released on drop, even if this future is cancelled
+        let in_flight = cache.in_flight(&key);
+        // Concurrent identical requests wait here for the first one to fill the cache
+        let _guard = in_flight.lock().await;
+
+        if let Some(hit) = cache.get(&key) {
+            tracing::debug!(endpoint = name, "Cache hit for {key}");
+            return self.parse(name, hit.body, hit.status, hit.headers, Duration::ZERO);
         }
+
+        let coin = matches!(name, "coin" | "holders")
+            .then(|| endpoint.rsplit('/').next())
+            .flatten();
+        let generation = cache.generation(coin);
+        let raw: RawResponse<T> = self
+            .request(name, Method::GET, endpoint, params, None)
+            .await?;
+        cache.insert(
+            key,
+            ttl,
+            generation,
+            raw.body.clone(),
+            raw.status,
+            raw.headers.clone(),
+        );
+        Ok(raw)
     }
 
-    async fn post<T: for<'de> Deserialize<'de>, J: Serialize>(
+    pub(crate) async fn post<T: for<'de> Deserialize<'de> + UnknownFields, J: Serialize>(
         &self,
+        name: &'static str,
         endpoint: &str,
         params: Option<&[(&str, &str)]>,
         json: Option<J>,
-    ) -> Result<T> {
-        let url = format!("{}/{}", self.base_url, endpoint);
+    ) -> Result<RawResponse<T>> {
+        let body = json
+            .map(serde_json::to_value)
+            .transpose()
+            .map_err(RugplayError::Serialize)?;
 
-        let mut req = self
-            .http
-            .request(Method::POST, &url)
-            .bearer_auth(&self.token)
-            .query(params.unwrap_or_default());
+        self.request(name, Method::POST, endpoint, params, body)
+            .await
+    }
 
-        if let Some(body) = json {
-            req = req.json(&body);
+    async fn request<T: for<'de> Deserialize<'de> + UnknownFields>(
+        &self,
+        name: &'static str,
+        method: Method,
+        endpoint: &str,
+        params: Option<&[(&str, &str)]>,
+        body: Option<serde_json::Value>,
+    ) -> Result<RawResponse<T>> {
+        let span = tracing::debug_span!(
+            "rugplay_request",
+            endpoint = name,
+            method = %method,
+            status = tracing::field::Empty,
+            latency_ms = tracing::field::Empty,
+            size = tracing::field::Empty,
+            // Requests aren't retried by the client, a retrying layer sees them as one request
+            retries = 0,
+        );
+
+        let start = Instant::now();
+        let result = async move {
+            let mut url = Url::parse(&format!("{}/{}", self.base_url, endpoint))
+                .map_err(|e| RugplayError::InvalidRequest(format!("{endpoint}: {e}")))?;
+            if let Some(params) = params.filter(|p| !p.is_empty()) {
+                url.query_pairs_mut().extend_pairs(params);
+            }
+            let body = body
+                .map(|b| serde_json::to_vec(&b))
+                .transpose()
+                .map_err(RugplayError::Serialize)?;
+
+            if let Some(limiter) = &self.rate_limiter {
+                let waited = limiter.acquire().await;
+                if !waited.is_zero() {
+                    self.metrics.record_rate_limit_wait(waited);
+                }
+            }
+
+            let mut req = http::Request::builder()
+                .method(method)
+                .uri(url.as_str())
+                .header(AUTHORIZATION, self.auth.clone());
+            if body.is_some() {
+                req = req.header(CONTENT_TYPE, "application/json");
+            }
+            let req = req
+                .body(body.map(Into::into).unwrap_or_default())
+                .map_err(|e| RugplayError::InvalidRequest(e.to_string()))?;
+
+            let span = tracing::Span::current();
+            let resp = match self.transport.clone().oneshot(req).await {
+                Ok(resp) => resp,
+                Err(e) => {
+                    span.record("latency_ms", start.elapsed().as_millis() as u64);
+                    tracing::debug!("Request failed: {e}");
+                    return Err(transport_error(e));
+                }
+            };
+            let (parts, body) = resp.into_parts();
+            let status = parts.status;
+            span.record("status", status.as_u16());
+
+            let text = String::from_utf8_lossy(&body).into_owned();
+            let elapsed = start.elapsed();
+            span.record("latency_ms", elapsed.as_millis() as u64);
+            span.record("size", text.len());
+
+            if status.is_client_error() || status.is_server_error() {
+                tracing::debug!("Request failed: {status}");
+                return Err(status_error(http::Response::from_parts(parts, body), url));
+            }
+            tracing::debug!("Request finished");
+
+            self.parse(name, text, status, parts.headers, elapsed)
         }
+        .instrument(span)
+        .await;
+
+        self.metrics.record_request(
+            name,
+            start.elapsed(),
+            result.as_ref().err().map(RugplayError::kind),
+        );
+        result
+    }
 
-        let resp = req.send().await?.error_for_status()?;
-
-        let text = resp.text().await?;
+    /// Deserializes a response body and checks it for unknown fields
+    fn parse<T: for<'de> Deserialize<'de> + UnknownFields>(
+        &self,
+        name: &'static str,
+        text: String,
+        status: StatusCode,
+        headers: reqwest::header::HeaderMap,
+        elapsed: Duration,
+    ) -> Result<RawResponse<T>> {
+        let data: T = match serde_json::from_str(&text) {
+            Ok(data) => data,
+            Err(e) => {
+                tracing::warn!(endpoint = name, "Rugplay schema drifted: {e}");
+                tracing::debug!(
+                    body = %loggable_body(name, &text),
+                    "Failed to deserialize response: {e}"
+                );
+                return Err(RugplayError::Deserialize { source: e, text });
+            }
+        };
 
-        match serde_json::from_str(&text) {
-            Ok(data) => Ok(data),
-            Err(e) => Err(RugplayError::Deserialize { source: e, text }),
+        let mut unknown = Vec::new();
+        data.unknown_fields("", &mut unknown);
+        if !unknown.is_empty() {
+            tracing::warn!(
+                endpoint = name,
+                "Rugplay schema drifted, unknown fields: {}",
+                unknown.join(", ")
+            );
+            if self.schema_mode == SchemaMode::Strict {
+                return Err(RugplayError::UnknownFields {
+                    endpoint: name,
+                    fields: unknown,
+                });
+            }
         }
+
+        Ok(RawResponse {
+            value: data,
+            body: text,
+            status,
+            headers,
+            elapsed,
+        })
     }
 
     // ---- ENDPOINTS ----
     /// Returns the top 50 coins by market cap.
     pub async fn get_top_coins(&self) -> Result<TopCoinsResponse> {
-        self.get("top", None).await
+        self.with_raw()
+            .get_top_coins()
+            .await
+            .map(RawResponse::into_inner)
     }
 
     /// Returns paginated market data with filtering and sorting options.
@@ -137,7 +590,10 @@ impl RugplayClient {
     /// - page - Page number (default: 1)
     /// - limit - Items per page, max 100 (default: 12)
     pub async fn get_market(&self, params: &[(&str, &str)]) -> Result<MarketResponse> {
-        self.get("market", Some(params)).await
+        self.with_raw()
+            .get_market(params)
+            .await
+            .map(RawResponse::into_inner)
     }
 
     /// Returns detailed information about a specific coin including price history.
@@ -145,20 +601,13 @@ impl RugplayClient {
     // timeframe - Optional. Chart timeframe: 1m, 5m, 15m, 1h, 4h, 1d (default: 1m)
     pub async fn get_coin_details(
         &self,
-        symbol: &str,
+        symbol: impl IntoSymbol,
         timeframe: Option<&str>,
     ) -> Result<CoinDetailsResponse> {
-        let endpoint = format!("coin/{}", symbol);
-        let params = timeframe.map(|t| [("timeframe", t)]).unwrap_or_default();
-        self.get(
-            &endpoint,
-            if timeframe.is_some() {
-                Some(&params)
-            } else {
-                None
-            },
-        )
-        .await
+        self.with_raw()
+            .get_coin_details(symbol, timeframe)
+            .await
+            .map(RawResponse::into_inner)
     }
 
     /// Returns the top 50 holders of a specific coin.
@@ -166,14 +615,15 @@ impl RugplayClient {
     /// ## Arguments:
     /// - symbol - Coin symbol (e.g., "TEST")
     /// - limit - Number of holders to return, max 200 (default: 50)
-    pub async fn get_holders(&self, symbol: &str, limit: Option<u32>) -> Result<HoldersResponse> {
-        let endpoint = format!("holders/{}", symbol);
-        if let Some(ref limit) = limit {
-            let params = &[("limit", &limit.to_string()[..])];
-            self.get(&endpoint, Some(params)).await
-        } else {
-            self.get(&endpoint, None).await
-        }
+    pub async fn get_holders(
+        &self,
+        symbol: impl IntoSymbol,
+        limit: Option<u32>,
+    ) -> Result<HoldersResponse> {
+        self.with_raw()
+            .get_holders(symbol, limit)
+            .await
+            .map(RawResponse::into_inner)
     }
 
     /// Returns prediction market questions with pagination and filtering options.
@@ -188,12 +638,10 @@ impl RugplayClient {
         limit: Option<u32>,
         page: Option<u32>,
     ) -> Result<HopiumResponse> {
-        let params = &[
-            ("limit", &limit.unwrap_or(20).to_string()[..]),
-            ("status", &hopium_status.to_string()),
-            ("page", &page.unwrap_or(1).to_string()),
-        ];
-        self.get("hopium", Some(params)).await
+        self.with_raw()
+            .get_hopium(hopium_status, limit, page)
+            .await
+            .map(RawResponse::into_inner)
     }
 
     /// Returns detailed information about a specific prediction market question including recent bets and probability history.
@@ -201,44 +649,243 @@ impl RugplayClient {
     /// ## Arguments
     ///
     /// - question_id - Hopium question to get
-    pub async fn get_hopium_details(&self, question_id: u32) -> Result<HopiumDetailsResponse> {
-        let endpoint = format!("hopium/{question_id}");
-
-        self.get(&endpoint, None).await
+    pub async fn get_hopium_details(
+        &self,
+        question_id: impl Into<QuestionId>,
+    ) -> Result<HopiumDetailsResponse> {
+        self.with_raw()
+            .get_hopium_details(question_id)
+            .await
+            .map(RawResponse::into_inner)
     }
 
     // ---- Unofficial API ----
     pub async fn get_claim_info(&self) -> Result<ClaimInfo> {
-        self.get("../rewards/claim", None).await
+        self.with_raw()
+            .get_claim_info()
+            .await
+            .map(RawResponse::into_inner)
     }
 
     pub async fn claim_reward(&self) -> Result<RewardStatus> {
-        self.post::<RewardStatus, ()>("../rewards/claim", None, None)
+        self.with_raw()
+            .claim_reward()
             .await
+            .map(RawResponse::into_inner)
     }
 
     pub async fn trade(
         &self,
-        coin: &str,
+        coin: impl IntoSymbol,
         trade_type: CoinTradeType,
         amount: u32,
     ) -> Result<TradeResponse> {
-        let trade_request = TradeRequest {
-            amount,
-            r#type: trade_type,
-        };
+        self.with_raw()
+            .trade(coin, trade_type, amount)
+            .await
+            .map(RawResponse::into_inner)
+    }
 
-        let endpoint = format!("../coin/{coin}/trade");
+    pub async fn get_recent_trades(&self, limit: u32) -> Result<RecentTradeResponse> {
+        self.with_raw()
+            .get_recent_trades(limit)
+            .await
+            .map(RawResponse::into_inner)
+    }
 
-        self.post::<TradeResponse, TradeRequest>(&endpoint, None, Some(trade_request))
+    /// Returns the current better-auth session, or `None` if the cookie is not valid
+    pub async fn get_session(&self) -> Result<Option<SessionResponse>> {
+        self.with_raw()
+            .get_session()
             .await
+            .map(RawResponse::into_inner)
     }
 
-    pub async fn get_recent_trades(&self, limit: u32) -> Result<RecentTradeResponse> {
-        self.get::<RecentTradeResponse>(
-            "../trades/recent",
-            Some(&[("limit", limit.to_string().as_str())]),
-        )
+    /// Returns the balance and coin holdings of the authenticated user
+    pub async fn get_portfolio_total(&self) -> Result<PortfolioTotal> {
+        self.with_raw()
+            .get_portfolio_total()
+            .await
+            .map(RawResponse::into_inner)
+    }
+
+    /// Simulates buying and selling each of `sizes` (e.g. [`depth::DEFAULT_SIZES`]) against
+    /// the pool of a coin
+    pub async fn get_depth_profile(
+        &self,
+        symbol: impl IntoSymbol,
+        sizes: &[TradeSize],
+    ) -> Result<DepthProfile> {
+        let holders = self.get_holders(symbol, Some(1)).await?;
+        Ok(depth::depth_profile(
+            holders.coin_symbol,
+            &holders.pool_info,
+            sizes,
+        ))
+    }
+
+    /// Scans the whole market and returns the coins matching the screener's filter
+    ///
+    /// Holders and candles are only fetched for the coins whose filter or sort key need them.
+    /// A coin whose holders or candles can't be fetched is logged and doesn't match.
+    pub async fn screen(&self, screener: &Screener) -> Result<Vec<ScreenedCoin>> {
+        screener::screen(self, screener).await
+    }
+
+    // ---- Batch ----
+    /// Returns the details of many coins, fetched concurrently
+    ///
+    /// Requests go through the rate limiter and cache like single requests. A failing symbol
+    /// doesn't fail the batch: each symbol has its own result, and invalid symbols get a
+    /// [`RugplayError::InvalidSymbol`] without sending anything.
+    ///
+    /// ## Arguments
+    /// - symbols - Coin symbols (duplicates are fetched once)
+    /// - timeframe - Optional. Chart timeframe: 1m, 5m, 15m, 1h, 4h, 1d (default: 1m)
+    pub async fn get_coin_details_many<S: IntoSymbol>(
+        &self,
+        symbols: impl IntoIterator<Item = S>,
+        timeframe: Option<&str>,
+    ) -> BatchResult<CoinDetailsResponse> {
+        self.batch(symbols, |symbol| async move {
+            self.get_coin_details(symbol, timeframe).await
+        })
         .await
     }
+
+    /// Returns the holders of many coins, fetched concurrently
+    ///
+    /// ## Arguments
+    /// - symbols - Coin symbols (duplicates are fetched once)
+    /// - limit - Number of holders to return per coin, max 200 (default: 50)
+    pub async fn get_holders_many<S: IntoSymbol>(
+        &self,
+        symbols: impl IntoIterator<Item = S>,
+        limit: Option<u32>,
+    ) -> BatchResult<HoldersResponse> {
+        self.batch(symbols, |symbol| async move {
+            self.get_holders(symbol, limit).await
+        })
+        .await
+    }
+
+    async fn batch<S, T, F, Fut>(
+        &self,
+        symbols: impl IntoIterator<Item = S>,
+        f: F,
+    ) -> BatchResult<T>
+    where
+        S: IntoSymbol,
+        F: Fn(Symbol) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut results = BatchResult::new();
+        let mut valid = BTreeSet::new();
+        for symbol in symbols {
+            match symbol.into_symbol() {
+                Ok(symbol) => {
+                    valid.insert(symbol);
+                }
+                Err(e) => {
+                    results.insert(e.0.clone(), Err(e.into()));
+                }
+            }
+        }
+
+        let fetched: Vec<(String, Result<T>)> = stream::iter(valid)
+            .map(|symbol| {
+                let fut = f(symbol.clone());
+                async move { (symbol.into_string(), fut.await) }
+            })
+            .buffer_unordered(self.batch_concurrency)
+            .collect()
+            .await;
+        results.extend(fetched);
+        results
+    }
+
+    /// Returns the user authenticated by the session cookie
+    pub async fn whoami(&self) -> Result<WhoAmI> {
+        let session = self
+            .get_session()
+            .await?
+            .ok_or(RugplayError::InvalidSession)?;
+        let portfolio = self.get_portfolio_total().await?;
+
+        Ok(WhoAmI {
+            id: session.user.id,
+            username: session.user.username,
+            balance: portfolio.base_currency_balance,
+        })
+    }
+
+    /// Checks the API key and the session cookie separately.
+    ///
+    /// Authentication failures are reported in the returned [`CredentialStatus`],
+    /// any other failure (network, server errors...) is returned as an error.
+    pub async fn validate_credentials(&self) -> Result<CredentialStatus> {
+        let token = match self.get_top_coins().await {
+            Ok(_) => CredentialCheck::Valid,
+            Err(e) if e.is_auth_error() => CredentialCheck::Invalid,
+            Err(e) => return Err(e),
+        };
+
+        let cookie = match self.get_session().await {
+            Ok(Some(_)) => CredentialCheck::Valid,
+            Ok(None) => CredentialCheck::Invalid,
+            Err(RugplayError::NoCookie) => CredentialCheck::Missing,
+            Err(e) if e.is_auth_error() => CredentialCheck::Invalid,
+            Err(e) => return Err(e),
+        };
+
+        Ok(CredentialStatus { token, cookie })
+    }
+}
+
+/// Endpoints whose responses can carry credentials
+const CREDENTIAL_ENDPOINTS: [&str; 4] = ["session", "claim_info", "claim_reward", "trade"];
+
+/// A response body with the [`replay::REDACTED_FIELDS`] redacted, safe to log
+///
+/// Bodies that aren't JSON are only kept for endpoints without credentials.
+fn loggable_body(name: &str, text: &str) -> String {
+    match serde_json::from_str(text) {
+        Ok(mut json) => {
+            replay::redact_json(&mut json);
+            json.to_string()
+        }
+        Err(_) if CREDENTIAL_ENDPOINTS.contains(&name) => "[REDACTED]".to_string(),
+        Err(_) => text.to_string(),
+    }
+}
+
+fn is_timeout(e: &(dyn std::error::Error + 'static)) -> bool {
+    e.is::<tower::timeout::error::Elapsed>()
+        || e.downcast_ref::<reqwest::Error>()
+            .is_some_and(reqwest::Error::is_timeout)
+}
+
+/// The `Authorization` header of an API key
+fn bearer(token: &Secret) -> std::result::Result<HeaderValue, ClientCreateError> {
+    let header = Secret::new(format!("Bearer {}", token.expose()));
+    let mut auth =
+        HeaderValue::from_str(header.expose()).map_err(|_| ClientCreateError::InvalidToken)?;
+    auth.set_sensitive(true);
+    Ok(auth)
+}
+
+/// The error reqwest gives to a response with an error status
+fn status_error(resp: HttpResponse, url: Url) -> RugplayError {
+    match reqwest::Response::from(resp).error_for_status() {
+        Err(e) => RugplayError::Request(e.with_url(url)),
+        Ok(resp) => RugplayError::InvalidResponse(format!("unexpected status {}", resp.status())),
+    }
+}
+
+/// Keeps reqwest errors as [`RugplayError::Request`]
+fn transport_error(e: BoxError) -> RugplayError {
+    match e.downcast::<reqwest::Error>() {
+        Ok(e) => RugplayError::Request(*e),
+        Err(e) => RugplayError::Transport(e),
+    }
 }
@@ -0,0 +1,189 @@
+#![deny(clippy::all)]
+//! Request metrics
+//!
+//! Every [`crate::RugplayClient`] records per-endpoint request counts, errors and latencies.
+//! Read them with [`crate::RugplayClient::metrics`].
+//!
+//! With the `metrics` cargo feature, the same values are also published through the
+//! [`metrics`](https://docs.rs/metrics) facade, so any exporter (e.g. Prometheus) can
+//! expose them:
+//! - `rusplay_requests_total{endpoint}` (counter)
+//! - `rusplay_errors_total{endpoint, kind}` (counter)
+//! - `rusplay_request_duration_seconds{endpoint}` (histogram)
+//! - `rusplay_rate_limit_waits_total` (counter)
+//! - `rusplay_rate_limit_wait_seconds` (histogram)
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Upper bounds of the latency histogram buckets
+pub const LATENCY_BUCKETS: [Duration; 8] = [
+    Duration::from_millis(50),
+    Duration::from_millis(100),
+    Duration::from_millis(250),
+    Duration::from_millis(500),
+    Duration::from_secs(1),
+    Duration::from_millis(2500),
+    Duration::from_secs(5),
+    Duration::from_secs(10),
+];
+
+/// Kind of a [`crate::RugplayError`], used to label error counts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ErrorKind {
+    /// Rugplay answered with an error status
+    Http,
+    Timeout,
+    /// Connection or other transport failure
+    Request,
+    Serialize,
+    Deserialize,
+    InvalidResponse,
+    UnknownFields,
+    NoCookie,
+    InvalidSession,
+}
+
+impl ErrorKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorKind::Http => "http",
+            ErrorKind::Timeout => "timeout",
+            ErrorKind::Request => "request",
+            ErrorKind::Serialize => "serialize",
+            ErrorKind::Deserialize => "deserialize",
+            ErrorKind::InvalidResponse => "invalid_response",
+            ErrorKind::UnknownFields => "unknown_fields",
+            ErrorKind::NoCookie => "no_cookie",
+            ErrorKind::InvalidSession => "invalid_session",
+        }
+    }
+}
+
+impl Display for ErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Latency histogram, counting each request in the single bucket its latency falls in
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LatencyHistogram {
+    /// Count of requests per bucket of [`LATENCY_BUCKETS`], plus a last bucket for slower ones
+    pub buckets: [u64; LATENCY_BUCKETS.len() + 1],
+    pub count: u64,
+    pub sum: Duration,
+}
+
+impl LatencyHistogram {
+    fn record(&mut self, latency: Duration) {
+        let bucket = LATENCY_BUCKETS
+            .iter()
+            .position(|b| latency <= *b)
+            .unwrap_or(LATENCY_BUCKETS.len());
+        self.buckets[bucket] += 1;
+        self.count += 1;
+        self.sum += latency;
+    }
+
+    pub fn mean(&self) -> Option<Duration> {
+        (self.count > 0)
+            .then(|| Duration::from_secs_f64(self.sum.as_secs_f64() / self.count as f64))
+    }
+}
+
+/// Metrics of a single endpoint
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EndpointMetrics {
+    pub requests: u64,
+    pub errors: BTreeMap<ErrorKind, u64>,
+    pub latency: LatencyHistogram,
+}
+
+impl EndpointMetrics {
+    pub fn error_count(&self) -> u64 {
+        self.errors.values().sum()
+    }
+}
+
+/// Point in time copy of the client metrics
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MetricsSnapshot {
+    /// Metrics by endpoint name (e.g. `"top"`, `"coin"`, `"trade"`)
+    pub endpoints: BTreeMap<&'static str, EndpointMetrics>,
+    /// Number of times the client waited because of its rate limiter
+    pub rate_limit_waits: u64,
+    /// Total time spent waiting because of the rate limiter
+    pub rate_limit_wait_time: Duration,
+}
+
+impl MetricsSnapshot {
+    pub fn total_requests(&self) -> u64 {
+        self.endpoints.values().map(|e| e.requests).sum()
+    }
+
+    pub fn total_errors(&self) -> u64 {
+        self.endpoints.values().map(|e| e.error_count()).sum()
+    }
+}
+
+/// Metrics shared by all clones of a client
+#[derive(Debug, Default)]
+pub(crate) struct ClientMetrics(Mutex<MetricsSnapshot>);
+
+impl ClientMetrics {
+    pub(crate) fn record_request(
+        &self,
+        endpoint: &'static str,
+        latency: Duration,
+        error: Option<ErrorKind>,
+    ) {
+        {
+            let mut state = self.0.lock().expect("metrics poisoned");
+            let metrics = state.endpoints.entry(endpoint).or_default();
+            metrics.requests += 1;
+            metrics.latency.record(latency);
+            if let Some(kind) = error {
+                *metrics.errors.entry(kind).or_default() += 1;
+            }
+        }
+
+        #[cfg(feature = "metrics")]
+        {
+            ::metrics::counter!("rusplay_requests_total", "endpoint" => endpoint).increment(1);
+            ::metrics::histogram!("rusplay_request_duration_seconds", "endpoint" => endpoint)
+                .record(latency.as_secs_f64());
+            if let Some(kind) = error {
+                ::metrics::counter!(
+                    "rusplay_errors_total",
+                    "endpoint" => endpoint,
+                    "kind" => kind.as_str()
+                )
+                .increment(1);
+            }
+        }
+    }
+
+    pub(crate) fn record_rate_limit_wait(&self, wait: Duration) {
+        {
+            let mut state = self.0.lock().expect("metrics poisoned");
+            state.rate_limit_waits += 1;
+            state.rate_limit_wait_time += wait;
+        }
+
+        #[cfg(feature = "metrics")]
+        {
+            ::metrics::counter!("rusplay_rate_limit_waits_total").increment(1);
+            ::metrics::histogram!("rusplay_rate_limit_wait_seconds").record(wait.as_secs_f64());
+        }
+    }
+
+    pub(crate) fn snapshot(&self) -> MetricsSnapshot {
+        self.0.lock().expect("metrics poisoned").clone()
+    }
+
+    pub(crate) fn reset(&self) {
+        *self.0.lock().expect("metrics poisoned") = MetricsSnapshot::default();
+    }
+}
@@ -1,24 +1,36 @@
 #![deny(clippy::all)]
 use std::fmt::Display;
+use std::time::Duration;
 
-use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+use crate::Secret;
+pub use crate::ids::{QuestionId, Symbol, UserId};
+use crate::schema::impl_unknown_fields;
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TopCoinsResponse {
     pub coins: Vec<CoinSummary>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CoinSummary {
-    pub symbol: String,
+    pub symbol: Symbol,
     pub name: String,
     pub icon: Option<String>,
     pub price: f64,
     pub change24h: f64,
     pub market_cap: f64,
     pub volume24h: f64,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
 // ---- /market ----
@@ -30,20 +42,24 @@ pub struct MarketResponse {
     pub page: u32,
     pub limit: u32,
     pub total_pages: u32,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct MarketCoin {
-    pub symbol: String,
+    pub symbol: Symbol,
     pub name: String,
     pub icon: Option<String>,
     pub current_price: f64,
     pub market_cap: f64,
     pub volume24h: f64,
     pub change24h: f64,
-    pub created_at: String,
+    pub created_at: DateTime<Utc>,
     pub creator_name: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
 // ---- /coin/{symbol} ----
@@ -54,6 +70,8 @@ pub struct CoinDetailsResponse {
     pub candlestick_data: Vec<Candle>,
     pub volume_data: Vec<VolumePoint>,
     pub timeframe: String,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -61,7 +79,7 @@ pub struct CoinDetailsResponse {
 pub struct CoinDetail {
     pub id: u64,
     pub name: String,
-    pub symbol: String,
+    pub symbol: Symbol,
     pub icon: Option<String>,
     pub current_price: f64,
     pub market_cap: f64,
@@ -71,55 +89,130 @@ pub struct CoinDetail {
     pub initial_supply: f64,
     pub creator_name: Option<String>,
     pub creator_username: Option<String>,
+    pub pool_coin_amount: Option<f64>,
+    pub pool_base_currency_amount: Option<f64>,
+    pub is_listed: Option<bool>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub creator_id: Option<UserId>,
+    pub creator_bio: Option<String>,
+    pub creator_image: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Candle {
+    /// Start of the candle, in seconds since the epoch
     pub time: u64,
     pub open: f64,
     pub high: f64,
     pub low: f64,
     pub close: f64,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
-#[derive(Debug, Deserialize)]
+impl Candle {
+    pub fn new(time: u64, open: f64, high: f64, low: f64, close: f64) -> Self {
+        Self {
+            time,
+            open,
+            high,
+            low,
+            close,
+            extra: Map::new(),
+        }
+    }
+
+    /// Average of the high, low and close prices
+    pub fn typical_price(&self) -> f64 {
+        (self.high + self.low + self.close) / 3.0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct VolumePoint {
     pub time: u64,
     pub volume: f64,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl VolumePoint {
+    pub fn new(time: u64, volume: f64) -> Self {
+        Self {
+            time,
+            volume,
+            extra: Map::new(),
+        }
+    }
 }
 
 // ---- /holders/{symbol} ----
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct HoldersResponse {
-    pub coin_symbol: String,
+    pub coin_symbol: Symbol,
     pub total_holders: u64,
     pub circulating_supply: f64,
     pub pool_info: PoolInfo,
     pub holders: Vec<Holder>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PoolInfo {
     pub coin_amount: f64,
     pub base_currency_amount: f64,
     pub current_price: f64,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
-#[derive(Debug, Deserialize)]
+/// Trades against the pool, which keeps `coin_amount * base_currency_amount` constant
+impl PoolInfo {
+    /// Base currency received for selling `coins`
+    pub fn sell_proceeds(&self, coins: f64) -> f64 {
+        self.base_currency_amount * coins / (self.coin_amount + coins)
+    }
+
+    /// Coins received for buying with `base` currency
+    pub fn buy_output(&self, base: f64) -> f64 {
+        self.coin_amount * base / (self.base_currency_amount + base)
+    }
+
+    /// Price of the coin after selling `coins`
+    pub fn price_after_sell(&self, coins: f64) -> f64 {
+        let k = self.coin_amount * self.base_currency_amount;
+        let coin_amount = self.coin_amount + coins;
+        k / coin_amount / coin_amount
+    }
+
+    /// Price of the coin after buying with `base` currency
+    pub fn price_after_buy(&self, base: f64) -> f64 {
+        let k = self.coin_amount * self.base_currency_amount;
+        let base_amount = self.base_currency_amount + base;
+        base_amount * base_amount / k
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Holder {
     pub rank: u32,
-    pub user_id: u64,
+    pub user_id: UserId,
     pub username: String,
     pub name: String,
     pub image: String,
     pub quantity: f64,
     pub percentage: f64,
     pub liquidation_value: f64,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
 // ---- /hopium ----
@@ -131,44 +224,84 @@ pub struct HopiumResponse {
     pub page: u32,
     pub limit: u32,
     pub total_pages: u32,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct HopiumQuestion {
-    pub id: u64,
+    pub id: QuestionId,
     pub question: String,
     pub status: HopiumStatus,
-    pub resolution_date: Option<String>,
+    pub resolution_date: Option<DateTime<Utc>>,
     pub total_amount: f64,
     pub yes_amount: f64,
     pub no_amount: f64,
     pub yes_percentage: f64,
     pub no_percentage: f64,
-    pub created_at: String,
+    pub created_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub requires_web_search: Option<bool>,
+    pub ai_resolution: Option<bool>,
     pub creator: HopiumCreator,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
-#[derive(Debug, Deserialize, PartialEq, PartialOrd)]
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum HopiumStatus {
     ACTIVE,
     RESOLVED,
     CANCELLED,
 }
 
-impl TryFrom<&str> for HopiumFilter {
-    type Error = ();
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+#[error("Invalid hopium status {0:?}")]
+pub struct InvalidHopiumStatus(pub String);
+
+impl TryFrom<&str> for HopiumStatus {
+    type Error = InvalidHopiumStatus;
     fn try_from(value: &str) -> Result<Self, Self::Error> {
         match value {
             "ACTIVE" => Ok(Self::ACTIVE),
             "RESOLVED" => Ok(Self::RESOLVED),
             "CANCELLED" => Ok(Self::CANCELLED),
-            _ => Err(()),
+            _ => Err(InvalidHopiumStatus(value.to_string())),
         }
     }
 }
 
-#[derive(Debug, Deserialize, PartialEq, PartialOrd)]
+impl Display for HopiumStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        HopiumFilter::from(*self).fmt(f)
+    }
+}
+
+/// Kept for compatibility, prefer [`HopiumStatus::try_from`]
+impl TryFrom<&str> for HopiumFilter {
+    type Error = ();
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "ALL" => Ok(Self::ALL),
+            _ => HopiumStatus::try_from(value)
+                .map(Self::from)
+                .map_err(|_| ()),
+        }
+    }
+}
+
+impl From<HopiumStatus> for HopiumFilter {
+    fn from(status: HopiumStatus) -> Self {
+        match status {
+            HopiumStatus::ACTIVE => Self::ACTIVE,
+            HopiumStatus::RESOLVED => Self::RESOLVED,
+            HopiumStatus::CANCELLED => Self::CANCELLED,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, PartialOrd)]
 pub enum HopiumFilter {
     ACTIVE,
     RESOLVED,
@@ -190,10 +323,12 @@ impl Display for HopiumFilter {
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct HopiumCreator {
-    pub id: u64,
+    pub id: UserId,
     pub name: String,
     pub username: String,
     pub image: String,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
 // ---- /hopium/{id} ----
@@ -202,22 +337,30 @@ pub struct HopiumCreator {
 pub struct HopiumDetailsResponse {
     pub question: HopiumQuestionDetails,
     pub probability_history: Vec<ProbabilityPoint>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct HopiumQuestionDetails {
-    pub id: u64,
+    pub id: QuestionId,
     pub question: String,
-    pub status: String,
+    pub status: HopiumStatus,
     pub creator: HopiumCreator,
     pub total_amount: f64,
     pub yes_amount: f64,
     pub no_amount: f64,
     pub yes_percentage: f64,
     pub no_percentage: f64,
-    pub created_at: String,
+    pub resolution_date: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub requires_web_search: Option<bool>,
+    pub ai_resolution: Option<bool>,
     pub recent_bets: Option<Vec<HopiumBet>>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -226,8 +369,10 @@ pub struct HopiumBet {
     pub id: u64,
     pub side: bool,
     pub amount: f64,
-    pub created_at: String,
+    pub created_at: DateTime<Utc>,
     pub user: HopiumCreator,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -235,23 +380,11 @@ pub struct HopiumBet {
 pub struct ProbabilityPoint {
     pub time: u64,
     pub value: f64,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct ClaimInfoResponse {
-    pub base_reward: u32,
-    pub can_claim: bool,
-    pub last_reward_claim: Option<String>,
-    pub login_streak: u32,
-    pub next_claim_time: Option<String>,
-    pub prestige_bonus: u32,
-    pub prestige_level: u32,
-    pub reward_amount: u32,
-    pub time_remaining: u64,
-    pub total_rewards_claimed: u32,
-}
-
+// ---- /rewards/claim ----
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ClaimInfo {
@@ -260,13 +393,20 @@ pub struct ClaimInfo {
     pub base_reward: u32,
     pub prestige_bonus: u32,
     pub prestige_level: u32,
-    pub time_remaining: u64,
-    pub next_claim_time: Option<String>,
+    /// Time until the next claim (zero when `can_claim`)
+    #[serde(deserialize_with = "duration_millis")]
+    pub time_remaining: Duration,
+    pub next_claim_time: Option<DateTime<Utc>>,
     pub total_rewards_claimed: u32,
-    pub last_reward_claim: Option<String>,
+    pub last_reward_claim: Option<DateTime<Utc>>,
     pub login_streak: u32,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
+#[deprecated(note = "Same model as `ClaimInfo`, use it instead")]
+pub type ClaimInfoResponse = ClaimInfo;
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RewardStatus {
@@ -278,7 +418,9 @@ pub struct RewardStatus {
     pub new_balance: f64,
     pub total_rewards_claimed: u32,
     pub login_streak: u32,
-    pub next_claim_time: Option<String>,
+    pub next_claim_time: Option<DateTime<Utc>>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -300,13 +442,15 @@ pub struct TradeResponse {
     pub r#type: CoinTradeType,
     pub coins_bought: Option<f64>,
     pub coins_sold: Option<f64>,
-    pub total_cost: Option<u32>,
+    pub total_cost: Option<f64>,
     pub new_price: f64,
     pub price_impact: f64,
     pub new_balance: f64,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum TradeType {
     Buy,
@@ -319,20 +463,180 @@ pub enum TradeType {
 #[serde(rename_all = "camelCase")]
 pub struct RecentTradeResponse {
     pub trades: Vec<Trade>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Trade {
     pub amount: f64,
     pub coin_icon: Option<String>,
     pub coin_name: String,
-    pub coin_symbol: String,
+    pub coin_symbol: Symbol,
     pub price: f64,
     pub timestamp: u64,
     pub total_value: f64,
     pub r#type: TradeType,
-    pub user_id: String,
+    pub user_id: UserId,
     pub user_image: Option<String>,
     pub username: String,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+// ---- /auth/get-session ----
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionResponse {
+    pub session: Session,
+    pub user: SessionUser,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Session {
+    pub id: Option<String>,
+    pub token: Option<Secret>,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub user_id: UserId,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionUser {
+    pub id: UserId,
+    pub name: String,
+    pub username: String,
+    pub image: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+// ---- /portfolio/total ----
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortfolioTotal {
+    pub base_currency_balance: f64,
+    pub total_coin_value: f64,
+    pub total_value: f64,
+    pub coin_holdings: Vec<CoinHolding>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CoinHolding {
+    pub symbol: Symbol,
+    pub icon: Option<String>,
+    pub quantity: f64,
+    pub current_price: f64,
+    pub value: f64,
+    pub change24h: f64,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// The user authenticated by the session cookie
+#[derive(Debug)]
+pub struct WhoAmI {
+    pub id: UserId,
+    pub username: String,
+    pub balance: f64,
+}
+
+/// Result of checking a single credential against Rugplay
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CredentialCheck {
+    Valid,
+    Invalid,
+    Missing,
+}
+
+/// Result of [`crate::RugplayClient::validate_credentials`]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct CredentialStatus {
+    /// The API key sent as a bearer token (official API)
+    pub token: CredentialCheck,
+    /// The session cookie (unofficial API)
+    pub cookie: CredentialCheck,
+}
+
+impl CredentialStatus {
+    pub fn is_valid(&self) -> bool {
+        self.token == CredentialCheck::Valid && self.cookie == CredentialCheck::Valid
+    }
+}
+
+impl_unknown_fields!(TopCoinsResponse {
+    coins => "coins",
+});
+impl_unknown_fields!(CoinSummary {});
+impl_unknown_fields!(MarketResponse {
+    coins => "coins",
+});
+impl_unknown_fields!(MarketCoin {});
+impl_unknown_fields!(CoinDetailsResponse {
+    coin => "coin",
+    candlestick_data => "candlestickData",
+    volume_data => "volumeData",
+});
+impl_unknown_fields!(CoinDetail {});
+impl_unknown_fields!(Candle {});
+impl_unknown_fields!(VolumePoint {});
+impl_unknown_fields!(HoldersResponse {
+    pool_info => "poolInfo",
+    holders => "holders",
+});
+impl_unknown_fields!(PoolInfo {});
+impl_unknown_fields!(Holder {});
+impl_unknown_fields!(HopiumResponse {
+    questions => "questions",
+});
+impl_unknown_fields!(HopiumQuestion {
+    creator => "creator",
+});
+impl_unknown_fields!(HopiumCreator {});
+impl_unknown_fields!(HopiumDetailsResponse {
+    question => "question",
+    probability_history => "probabilityHistory",
+});
+impl_unknown_fields!(HopiumQuestionDetails {
+    creator => "creator",
+    recent_bets => "recentBets",
+});
+impl_unknown_fields!(HopiumBet {
+    user => "user",
+});
+impl_unknown_fields!(ProbabilityPoint {});
+impl_unknown_fields!(ClaimInfo {});
+impl_unknown_fields!(RewardStatus {});
+impl_unknown_fields!(TradeResponse {});
+impl_unknown_fields!(RecentTradeResponse {
+    trades => "trades",
+});
+impl_unknown_fields!(Trade {});
+impl_unknown_fields!(SessionResponse {
+    session => "session",
+    user => "user",
+});
+impl_unknown_fields!(Session {});
+impl_unknown_fields!(SessionUser {});
+impl_unknown_fields!(PortfolioTotal {
+    coin_holdings => "coinHoldings",
+});
+impl_unknown_fields!(CoinHolding {});
+
+/// Deserializes a number of milliseconds
+fn duration_millis<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+    u64::deserialize(deserializer).map(Duration::from_millis)
 }
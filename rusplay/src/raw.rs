@@ -0,0 +1,186 @@
+#![deny(clippy::all)]
+//! Typed results together with the raw HTTP response
+//!
+//! Useful when a model lags behind Rugplay and the fields it doesn't know about are needed.
+use crate::ids::IntoSymbol;
+use crate::models::*;
+use crate::{Result, RugplayClient, RugplayError};
+use reqwest::StatusCode;
+use reqwest::header::HeaderMap;
+use std::time::Duration;
+
+/// A parsed response along with the original body, status, headers and timing
+#[derive(Debug, Clone)]
+pub struct RawResponse<T> {
+    pub value: T,
+    pub body: String,
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    /// Time from sending the request until the body was read
+    pub elapsed: Duration,
+}
+
+impl<T> RawResponse<T> {
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+
+    /// Parses the original body as untyped JSON
+    pub fn json(&self) -> serde_json::Result<serde_json::Value> {
+        serde_json::from_str(&self.body)
+    }
+
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> RawResponse<U> {
+        RawResponse {
+            value: f(self.value),
+            body: self.body,
+            status: self.status,
+            headers: self.headers,
+            elapsed: self.elapsed,
+        }
+    }
+}
+
+/// View of a [`RugplayClient`] whose endpoints return [`RawResponse`]s
+///
+/// Created with [`RugplayClient::with_raw`]. See the client for the documentation of each endpoint.
+#[derive(Debug, Clone, Copy)]
+pub struct RawClient<'a>(&'a RugplayClient);
+
+impl<'a> RawClient<'a> {
+    pub(crate) fn new(client: &'a RugplayClient) -> Self {
+        Self(client)
+    }
+
+    // ---- ENDPOINTS ----
+    pub async fn get_top_coins(&self) -> Result<RawResponse<TopCoinsResponse>> {
+        self.0.get("top", "top", None).await
+    }
+
+    pub async fn get_market(&self, params: &[(&str, &str)]) -> Result<RawResponse<MarketResponse>> {
+        self.0.get("market", "market", Some(params)).await
+    }
+
+    pub async fn get_coin_details(
+        &self,
+        symbol: impl IntoSymbol,
+        timeframe: Option<&str>,
+    ) -> Result<RawResponse<CoinDetailsResponse>> {
+        let endpoint = format!("coin/{}", symbol.into_symbol()?);
+        let params = timeframe.map(|t| [("timeframe", t)]).unwrap_or_default();
+        self.0
+            .get(
+                "coin",
+                &endpoint,
+                if timeframe.is_some() {
+                    Some(&params)
+                } else {
+                    None
+                },
+            )
+            .await
+    }
+
+    pub async fn get_holders(
+        &self,
+        symbol: impl IntoSymbol,
+        limit: Option<u32>,
+    ) -> Result<RawResponse<HoldersResponse>> {
+        let endpoint = format!("holders/{}", symbol.into_symbol()?);
+        if let Some(ref limit) = limit {
+            let params = &[("limit", &limit.to_string()[..])];
+            self.0.get("holders", &endpoint, Some(params)).await
+        } else {
+            self.0.get("holders", &endpoint, None).await
+        }
+    }
+
+    pub async fn get_hopium(
+        &self,
+        hopium_status: HopiumFilter,
+        limit: Option<u32>,
+        page: Option<u32>,
+    ) -> Result<RawResponse<HopiumResponse>> {
+        let params = &[
+            ("limit", &limit.unwrap_or(20).to_string()[..]),
+            ("status", &hopium_status.to_string()),
+            ("page", &page.unwrap_or(1).to_string()),
+        ];
+        self.0.get("hopium", "hopium", Some(params)).await
+    }
+
+    pub async fn get_hopium_details(
+        &self,
+        question_id: impl Into<QuestionId>,
+    ) -> Result<RawResponse<HopiumDetailsResponse>> {
+        let endpoint = format!("hopium/{}", question_id.into());
+
+        self.0.get("hopium_details", &endpoint, None).await
+    }
+
+    // ---- Unofficial API ----
+    pub async fn get_claim_info(&self) -> Result<RawResponse<ClaimInfo>> {
+        self.0.get("claim_info", "../rewards/claim", None).await
+    }
+
+    pub async fn claim_reward(&self) -> Result<RawResponse<RewardStatus>> {
+        self.0
+            .post::<RewardStatus, ()>("claim_reward", "../rewards/claim", None, None)
+            .await
+    }
+
+    pub async fn trade(
+        &self,
+        coin: impl IntoSymbol,
+        trade_type: CoinTradeType,
+        amount: u32,
+    ) -> Result<RawResponse<TradeResponse>> {
+        let coin = coin.into_symbol()?;
+        let trade_request = TradeRequest {
+            amount,
+            r#type: trade_type,
+        };
+
+        let endpoint = format!("../coin/{coin}/trade");
+
+        let response = self
+            .0
+            .post::<TradeResponse, TradeRequest>("trade", &endpoint, None, Some(trade_request))
+            .await?;
+
+        self.0.invalidate_coin(&coin);
+        Ok(response)
+    }
+
+    pub async fn get_recent_trades(&self, limit: u32) -> Result<RawResponse<RecentTradeResponse>> {
+        self.0
+            .get::<RecentTradeResponse>(
+                "recent_trades",
+                "../trades/recent",
+                Some(&[("limit", limit.to_string().as_str())]),
+            )
+            .await
+    }
+
+    pub async fn get_session(&self) -> Result<RawResponse<Option<SessionResponse>>> {
+        if !self.0.has_cookie() {
+            return Err(RugplayError::NoCookie);
+        }
+        self.0.get("session", "../auth/get-session", None).await
+    }
+
+    pub async fn get_portfolio_total(&self) -> Result<RawResponse<PortfolioTotal>> {
+        self.0
+            .get("portfolio_total", "../portfolio/total", None)
+            .await
+    }
+
+    /// Raw variant of [`RugplayClient::get_json`]
+    pub async fn get_json(
+        &self,
+        endpoint: &str,
+        params: Option<&[(&str, &str)]>,
+    ) -> Result<RawResponse<serde_json::Value>> {
+        self.0.get("json", endpoint, params).await
+    }
+}
@@ -0,0 +1,316 @@
+#![deny(clippy::all)]
+//! Record and replay HTTP traffic
+//!
+//! [`RecordLayer`] saves every request/response pair going through a client to a directory of
+//! JSON fixtures, with credentials and personal data redacted. [`ReplayTransport`] serves them
+//! back without network access:
+//!
+//! ```no_run
+//! # use rusplay::RugplayClient;
+//! # use rusplay::replay::{RecordLayer, ReplayTransport};
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let live = RugplayClient::new("token", None, None)?.with_layer(RecordLayer::new("fixtures"));
+//! let offline = RugplayClient::new("token", None, None)?
+//!     .with_transport(ReplayTransport::open("fixtures")?);
+//! # Ok(())
+//! # }
+//! ```
+use crate::transport::{BoxError, HttpRequest, HttpResponse};
+use bytes::Bytes;
+use futures::future::BoxFuture;
+use reqwest::header::{HeaderName, HeaderValue};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use thiserror::Error;
+use tower::{Layer, Service};
+
+/// Headers whose value is never written to fixtures
+pub const REDACTED_HEADERS: [&str; 3] = ["authorization", "cookie", "set-cookie"];
+/// JSON fields whose value is never written to fixtures
+pub const REDACTED_FIELDS: [&str; 4] = ["token", "ipAddress", "userAgent", "email"];
+
+const REDACTED: &str = "[REDACTED]";
+
+/// Errors that recording or replaying can generate
+#[derive(Debug, Error)]
+pub enum ReplayError {
+    #[error("Could not access fixture: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid fixture {path}: {source}")]
+    Json {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+    #[error("No fixture for {method} {uri}")]
+    NoFixture { method: String, uri: String },
+}
+
+/// A recorded request/response pair
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Fixture {
+    pub request: RecordedRequest,
+    pub response: RecordedResponse,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedRequest {
+    pub method: String,
+    /// Path and query, without the host
+    pub uri: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedResponse {
+    pub status: u16,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// The JSON body, or a string if the body is not JSON
+    pub body: Value,
+}
+
+/// Layer that records traffic to a fixture directory
+#[derive(Debug, Clone)]
+pub struct RecordLayer {
+    dir: PathBuf,
+    counter: Arc<AtomicUsize>,
+}
+
+impl RecordLayer {
+    /// Records to `dir`, after the fixtures it already contains
+    pub fn new(dir: impl AsRef<Path>) -> Self {
+        let dir = dir.as_ref().to_path_buf();
+        let existing = std::fs::read_dir(&dir)
+            .map(|entries| entries.filter(is_fixture).count())
+            .unwrap_or(0);
+
+        Self {
+            dir,
+            counter: Arc::new(AtomicUsize::new(existing)),
+        }
+    }
+}
+
+impl<S> Layer<S> for RecordLayer {
+    type Service = Record<S>;
+
+    fn layer(&self, inner: S) -> Record<S> {
+        Record {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+/// Service created by [`RecordLayer`]
+#[derive(Debug, Clone)]
+pub struct Record<S> {
+    inner: S,
+    layer: RecordLayer,
+}
+
+impl<S> Service<HttpRequest> for Record<S>
+where
+    S: Service<HttpRequest, Response = HttpResponse>,
+    S::Error: Into<BoxError>,
+    S::Future: Send + 'static,
+{
+    type Response = HttpResponse;
+    type Error = BoxError;
+    type Future = BoxFuture<'static, Result<HttpResponse, BoxError>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), BoxError>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, req: HttpRequest) -> Self::Future {
+        let request = RecordedRequest {
+            method: req.method().to_string(),
+            uri: path_and_query(&req),
+            body: (!req.body().is_empty()).then(|| redact_body(req.body())),
+        };
+        let layer = self.layer.clone();
+        let fut = self.inner.call(req);
+
+        Box::pin(async move {
+            let resp = fut.await.map_err(Into::into)?;
+            let headers = resp
+                .headers()
+                .iter()
+                .map(|(name, value)| {
+                    let value = if REDACTED_HEADERS.contains(&name.as_str()) {
+                        REDACTED.to_string()
+                    } else {
+                        String::from_utf8_lossy(value.as_bytes()).into_owned()
+                    };
+                    (name.to_string(), value)
+                })
+                .collect();
+            let fixture = Fixture {
+                request,
+                response: RecordedResponse {
+                    status: resp.status().as_u16(),
+                    headers,
+                    body: redact_body(resp.body()),
+                },
+            };
+
+            let seq = layer.counter.fetch_add(1, Ordering::SeqCst);
+            let path = layer.dir.join(file_name(seq, &fixture.request));
+            std::fs::create_dir_all(&layer.dir)?;
+            std::fs::write(&path, serde_json::to_string_pretty(&fixture)?)?;
+            tracing::debug!("Recorded {}", path.display());
+
+            Ok(resp)
+        })
+    }
+}
+
+/// Transport that answers from recorded fixtures
+///
+/// Requests are matched on method, path and query. Identical requests get the matching
+/// fixtures in recording order, the last one being repeated once they are exhausted.
+#[derive(Debug, Clone)]
+pub struct ReplayTransport {
+    fixtures: Arc<Vec<Fixture>>,
+    served: Arc<Mutex<Vec<bool>>>,
+}
+
+impl ReplayTransport {
+    /// Loads every fixture of `dir`
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self, ReplayError> {
+        let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)?
+            .filter(is_fixture)
+            .map(|e| e.map(|e| e.path()))
+            .collect::<Result<_, _>>()?;
+        paths.sort();
+
+        let fixtures = paths
+            .into_iter()
+            .map(|path| {
+                let text = std::fs::read_to_string(&path)?;
+                serde_json::from_str(&text).map_err(|source| ReplayError::Json { path, source })
+            })
+            .collect::<Result<Vec<Fixture>, _>>()?;
+
+        Ok(Self::from_fixtures(fixtures))
+    }
+
+    pub fn from_fixtures(fixtures: Vec<Fixture>) -> Self {
+        Self {
+            served: Arc::new(Mutex::new(vec![false; fixtures.len()])),
+            fixtures: Arc::new(fixtures),
+        }
+    }
+
+    fn find(&self, method: &str, uri: &str) -> Option<&Fixture> {
+        let mut served = self.served.lock().expect("replay state poisoned");
+        let mut matching = self
+            .fixtures
+            .iter()
+            .enumerate()
+            .filter(|(_, f)| f.request.method == method && f.request.uri == uri)
+            .peekable();
+
+        while let Some((i, fixture)) = matching.next() {
+            if !served[i] || matching.peek().is_none() {
+                served[i] = true;
+                return Some(fixture);
+            }
+        }
+        None
+    }
+}
+
+impl Service<HttpRequest> for ReplayTransport {
+    type Response = HttpResponse;
+    type Error = BoxError;
+    type Future = BoxFuture<'static, Result<HttpResponse, BoxError>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), BoxError>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: HttpRequest) -> Self::Future {
+        let method = req.method().to_string();
+        let uri = path_and_query(&req);
+        let result = match self.find(&method, &uri) {
+            Some(fixture) => to_response(&fixture.response),
+            None => Err(ReplayError::NoFixture { method, uri }.into()),
+        };
+        Box::pin(async move { result })
+    }
+}
+
+fn to_response(recorded: &RecordedResponse) -> Result<HttpResponse, BoxError> {
+    let body = match &recorded.body {
+        Value::String(text) => text.clone(),
+        json => json.to_string(),
+    };
+
+    let mut resp = HttpResponse::new(Bytes::from(body));
+    *resp.status_mut() = recorded.status.try_into()?;
+    for (name, value) in &recorded.headers {
+        resp.headers_mut()
+            .append(HeaderName::try_from(name)?, HeaderValue::try_from(value)?);
+    }
+    Ok(resp)
+}
+
+fn path_and_query(req: &HttpRequest) -> String {
+    req.uri()
+        .path_and_query()
+        .map(|p| p.to_string())
+        .unwrap_or_else(|| "/".into())
+}
+
+fn redact_body(body: &[u8]) -> Value {
+    match serde_json::from_slice(body) {
+        Ok(mut json) => {
+            redact_json(&mut json);
+            json
+        }
+        Err(_) => Value::String(String::from_utf8_lossy(body).into_owned()),
+    }
+}
+
+pub(crate) fn redact_json(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if REDACTED_FIELDS.contains(&key.as_str()) && !value.is_null() {
+                    *value = Value::String(REDACTED.into());
+                } else {
+                    redact_json(value);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_json),
+        _ => {}
+    }
+}
+
+fn file_name(seq: usize, request: &RecordedRequest) -> String {
+    let path = request.uri.split('?').next().unwrap_or_default();
+    let slug: String = path
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    format!(
+        "{seq:04}-{}-{}.json",
+        request.method,
+        slug.trim_matches('_')
+    )
+}
+
+fn is_fixture(entry: &std::io::Result<std::fs::DirEntry>) -> bool {
+    entry
+        .as_ref()
+        .is_ok_and(|e| e.path().extension().is_some_and(|ext| ext == "json"))
+}
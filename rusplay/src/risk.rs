@@ -0,0 +1,208 @@
+#![deny(clippy::all)]
+//! Holder concentration and rug-risk analytics
+//!
+//! Computed from [`HoldersResponse`], so they only see the holders Rugplay returned (top 50 by
+//! default, up to 200 with a higher `limit`).
+use crate::models::{CoinDetail, Holder, HoldersResponse, PoolInfo};
+use std::fmt::Display;
+
+/// How the supply is spread among the holders
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Concentration {
+    /// Share of the circulating supply held by the largest holder, in %
+    pub top1_share: f64,
+    /// Share of the circulating supply held by the 10 largest holders, in %
+    pub top10_share: f64,
+    /// Herfindahl-Hirschman index of the holders' shares, from 0 (spread out) to 1 (one holder)
+    pub herfindahl: f64,
+    /// Gini coefficient of the holders' quantities, from 0 (equal) to 1 (unequal)
+    pub gini: f64,
+}
+
+/// Result of the largest holder selling everything into the pool
+#[derive(Debug, Clone, PartialEq)]
+pub struct DumpImpact {
+    pub username: String,
+    pub quantity: f64,
+    /// Base currency the holder would receive
+    pub proceeds: f64,
+    /// Part of the pool's base currency the sale would take, from 0 to 1
+    pub pool_drain: f64,
+    /// Price change caused by the sale, in % (negative)
+    pub price_impact: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RiskLevel {
+    Low,
+    Medium,
+    High,
+    Extreme,
+}
+
+impl RiskLevel {
+    /// Level of a [`RugRisk::score`]
+    pub fn from_score(score: f64) -> Self {
+        match score {
+            s if s < 25.0 => RiskLevel::Low,
+            s if s < 50.0 => RiskLevel::Medium,
+            s if s < 75.0 => RiskLevel::High,
+            _ => RiskLevel::Extreme,
+        }
+    }
+}
+
+impl Display for RiskLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            RiskLevel::Low => "low",
+            RiskLevel::Medium => "medium",
+            RiskLevel::High => "high",
+            RiskLevel::Extreme => "extreme",
+        })
+    }
+}
+
+/// Weights of each factor of [`RugRisk::score`], summing to 1 by default
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RiskWeights {
+    /// [`DumpImpact::pool_drain`] of the largest holder
+    pub top_dump: f64,
+    /// Share of the creator
+    pub creator: f64,
+    /// [`Concentration::top10_share`]
+    pub top10: f64,
+    /// [`Concentration::herfindahl`]
+    pub herfindahl: f64,
+    /// [`Concentration::gini`]
+    pub gini: f64,
+}
+
+impl Default for RiskWeights {
+    fn default() -> Self {
+        Self {
+            top_dump: 0.35,
+            creator: 0.25,
+            top10: 0.2,
+            herfindahl: 0.1,
+            gini: 0.1,
+        }
+    }
+}
+
+/// Rug-risk assessment of a coin
+#[derive(Debug, Clone, PartialEq)]
+pub struct RugRisk {
+    pub concentration: Concentration,
+    /// Share of the circulating supply held by the creator, in %
+    ///
+    /// `None` if the creator isn't known, 0 if they aren't among the returned holders.
+    pub creator_share: Option<f64>,
+    pub top_dump: Option<DumpImpact>,
+    /// From 0 (safe) to 100 (the coin can be rugged by a single sale)
+    pub score: f64,
+    pub level: RiskLevel,
+}
+
+/// Computes the concentration of the holders
+pub fn concentration(holders: &[Holder]) -> Concentration {
+    let mut shares: Vec<f64> = holders.iter().map(|h| h.percentage).collect();
+    shares.sort_by(|a, b| b.total_cmp(a));
+
+    Concentration {
+        top1_share: shares.first().copied().unwrap_or_default(),
+        top10_share: shares.iter().take(10).sum(),
+        herfindahl: shares.iter().map(|s| (s / 100.0).powi(2)).sum(),
+        gini: gini(holders.iter().map(|h| h.quantity)),
+    }
+}
+
+/// Gini coefficient of non-negative values
+fn gini(values: impl Iterator<Item = f64>) -> f64 {
+    let mut values: Vec<f64> = values.map(|v| v.max(0.0)).collect();
+    values.sort_by(f64::total_cmp);
+    let n = values.len() as f64;
+    let total: f64 = values.iter().sum();
+    if values.len() < 2 || total == 0.0 {
+        return 0.0;
+    }
+
+    let weighted: f64 = values
+        .iter()
+        .enumerate()
+        .map(|(i, v)| (i + 1) as f64 * v)
+        .sum();
+    2.0 * weighted / (n * total) - (n + 1.0) / n
+}
+
+/// Share of the circulating supply held by `creator_username`, in %
+pub fn creator_share(holders: &[Holder], creator_username: &str) -> f64 {
+    holders
+        .iter()
+        .filter(|h| h.username == creator_username)
+        .map(|h| h.percentage)
+        .sum()
+}
+
+/// Impact of `holder` selling everything into the pool
+pub fn dump_impact(holder: &Holder, pool: &PoolInfo) -> DumpImpact {
+    let proceeds = pool.sell_proceeds(holder.quantity);
+    let spot = pool.base_currency_amount / pool.coin_amount;
+    let price_impact = (pool.price_after_sell(holder.quantity) - spot) / spot * 100.0;
+
+    DumpImpact {
+        username: holder.username.clone(),
+        quantity: holder.quantity,
+        proceeds,
+        pool_drain: if pool.base_currency_amount > 0.0 {
+            proceeds / pool.base_currency_amount
+        } else {
+            0.0
+        },
+        price_impact,
+    }
+}
+
+/// Scores the rug risk of a coin with the default [`RiskWeights`]
+///
+/// `coin` provides the creator; without it the creator share counts as 0 in the score.
+pub fn rug_risk(holders: &HoldersResponse, coin: Option<&CoinDetail>) -> RugRisk {
+    rug_risk_with(holders, coin, RiskWeights::default())
+}
+
+/// Scores the rug risk of a coin, see [`rug_risk`]
+pub fn rug_risk_with(
+    holders: &HoldersResponse,
+    coin: Option<&CoinDetail>,
+    weights: RiskWeights,
+) -> RugRisk {
+    let concentration = concentration(&holders.holders);
+    let creator_share = coin
+        .and_then(|c| c.creator_username.as_deref())
+        .map(|creator| creator_share(&holders.holders, creator));
+    let top_dump = holders
+        .holders
+        .iter()
+        .max_by(|a, b| a.quantity.total_cmp(&b.quantity))
+        .map(|h| dump_impact(h, &holders.pool_info));
+
+    let score = 100.0
+        * (weights.top_dump * top_dump.as_ref().map_or(0.0, |d| d.pool_drain)
+            + weights.creator * creator_share.unwrap_or_default() / 100.0
+            + weights.top10 * concentration.top10_share / 100.0
+            + weights.herfindahl * concentration.herfindahl
+            + weights.gini * concentration.gini);
+    let score = if score.is_finite() {
+        score.clamp(0.0, 100.0)
+    } else {
+        100.0
+    };
+
+    RugRisk {
+        concentration,
+        creator_share,
+        top_dump,
+        score,
+        level: RiskLevel::from_score(score),
+    }
+}
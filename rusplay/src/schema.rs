@@ -0,0 +1,72 @@
+#![deny(clippy::all)]
+//! Detection of changes in the Rugplay API schema
+//!
+//! Every response model keeps the fields it doesn't know about in its `extra` map.
+//! The client checks them after each request according to its [`SchemaMode`].
+use serde_json::Value;
+
+/// How the client reacts to fields that the models don't know about
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SchemaMode {
+    /// Keep unknown fields in `extra` and log a warning (default)
+    #[default]
+    Lenient,
+    /// Log a warning and fail with [`crate::RugplayError::UnknownFields`]
+    Strict,
+}
+
+/// Models that collect the fields they don't know about
+pub trait UnknownFields {
+    /// Appends the paths of the unknown fields (e.g. `coins[0].newField`) to `out`
+    fn unknown_fields(&self, path: &str, out: &mut Vec<String>);
+}
+
+impl<T: UnknownFields> UnknownFields for Vec<T> {
+    fn unknown_fields(&self, path: &str, out: &mut Vec<String>) {
+        for (i, item) in self.iter().enumerate() {
+            item.unknown_fields(&format!("{path}[{i}]"), out);
+        }
+    }
+}
+
+impl<T: UnknownFields> UnknownFields for Option<T> {
+    fn unknown_fields(&self, path: &str, out: &mut Vec<String>) {
+        if let Some(inner) = self {
+            inner.unknown_fields(path, out);
+        }
+    }
+}
+
+/// Untyped responses have no schema to drift from
+impl UnknownFields for Value {
+    fn unknown_fields(&self, _path: &str, _out: &mut Vec<String>) {}
+}
+
+pub(crate) fn join(path: &str, field: &str) -> String {
+    if path.is_empty() {
+        field.to_string()
+    } else {
+        format!("{path}.{field}")
+    }
+}
+
+/// Implements [`UnknownFields`] for a model with an `extra` map and the given nested models
+/// (`rust_field => "jsonName"`)
+macro_rules! impl_unknown_fields {
+    ($ty:ty { $($field:ident => $name:literal),* $(,)? }) => {
+        impl $crate::schema::UnknownFields for $ty {
+            fn unknown_fields(&self, path: &str, out: &mut Vec<String>) {
+                out.extend(self.extra.keys().map(|k| $crate::schema::join(path, k)));
+                $(
+                    $crate::schema::UnknownFields::unknown_fields(
+                        &self.$field,
+                        &$crate::schema::join(path, $name),
+                        out,
+                    );
+                )*
+            }
+        }
+    };
+}
+
+pub(crate) use impl_unknown_fields;
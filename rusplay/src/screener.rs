@@ -0,0 +1,345 @@
+#![deny(clippy::all)]
+//! Client-side market screener
+//!
+//! Scans every page of the market and keeps the coins matching a filter [`Expr`]:
+//!
+//! ```text
+//! market_cap > 10000 and change24h > 50 and top10_share < 0.6 and age < 2h
+//! ```
+//!
+//! Market variables come with the market pages. Holder and candle variables need a request per
+//! coin, which is only sent when the filter can't be decided without it.
+//!
+//! | Variables | Source |
+//! |---|---|
+//! | `symbol`, `name`, `creator`, `price`, `market_cap`, `volume24h`, `change24h`, `age` (s) | market page |
+//! | `holders`, `top1_share`, `top10_share`, `herfindahl`, `gini` (0 to 1), `liquidity`, `dump_drain` | [`crate::RugplayClient::get_holders`] |
+//! | `rsi` (14), `sma` (20), `ema` (20), `roc` (12), `atr` (14), `candles` | [`crate::RugplayClient::get_coin_details`] |
+use crate::candles::Timeframe;
+use crate::expr::{Expr, ExprError, Value};
+use crate::indicators::{Atr, Ema, Indicator, Roc, Rsi, Sma};
+use crate::models::{Candle, CoinDetailsResponse, HoldersResponse, MarketCoin};
+use crate::{Result, RugplayClient, RugplayError, risk};
+use chrono::{DateTime, Utc};
+use futures::{StreamExt, stream};
+use std::collections::BTreeSet;
+
+/// Variables of [`MarketCoin`]
+pub const MARKET_VARIABLES: [&str; 8] = [
+    "symbol",
+    "name",
+    "creator",
+    "price",
+    "market_cap",
+    "volume24h",
+    "change24h",
+    "age",
+];
+
+/// Variables computed from the holders of a coin
+pub const HOLDER_VARIABLES: [&str; 7] = [
+    "holders",
+    "top1_share",
+    "top10_share",
+    "herfindahl",
+    "gini",
+    "liquidity",
+    "dump_drain",
+];
+
+/// Variables computed from the candles of a coin
+pub const CANDLE_VARIABLES: [&str; 6] = ["rsi", "sma", "ema", "roc", "atr", "candles"];
+
+/// Coins per market page, the maximum Rugplay allows
+const PAGE_SIZE: u32 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+/// Filter, sort and limit of a market scan
+#[derive(Debug, Clone)]
+pub struct Screener {
+    filter: Expr,
+    sort: Option<(Expr, SortOrder)>,
+    limit: Option<usize>,
+    max_pages: Option<u32>,
+    timeframe: Timeframe,
+}
+
+impl Screener {
+    /// Creates a screener keeping the coins matching `filter`
+    pub fn new(filter: &str) -> std::result::Result<Self, ExprError> {
+        Ok(Self {
+            filter: parse(filter)?,
+            sort: None,
+            limit: None,
+            max_pages: None,
+            timeframe: Timeframe::M1,
+        })
+    }
+
+    /// Sorts the results by the value of `key` (e.g. `"change24h"` or `"volume24h / market_cap"`)
+    ///
+    /// Coins for which `key` can't be computed come last. Without a key the results are in market
+    /// cap order.
+    pub fn with_sort(
+        mut self,
+        key: &str,
+        order: SortOrder,
+    ) -> std::result::Result<Self, ExprError> {
+        self.sort = Some((parse(key)?, order));
+        Ok(self)
+    }
+
+    /// Maximum number of results
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Maximum number of market pages of 100 coins to scan (default: all of them)
+    pub fn with_max_pages(mut self, max_pages: u32) -> Self {
+        self.max_pages = Some(max_pages);
+        self
+    }
+
+    /// Timeframe of the candles of the candle variables (default: 1m)
+    pub fn with_timeframe(mut self, timeframe: Timeframe) -> Self {
+        self.timeframe = timeframe;
+        self
+    }
+
+    pub fn filter(&self) -> &Expr {
+        &self.filter
+    }
+}
+
+/// Parses an expression and checks that it only uses screener variables
+fn parse(source: &str) -> std::result::Result<Expr, ExprError> {
+    let expr = Expr::parse(source)?;
+    let known = MARKET_VARIABLES
+        .iter()
+        .chain(&HOLDER_VARIABLES)
+        .chain(&CANDLE_VARIABLES);
+    if let Some(unknown) = expr
+        .variables()
+        .into_iter()
+        .find(|v| !known.clone().any(|k| k == v))
+    {
+        return Err(ExprError::UnknownVariable(unknown.to_string()));
+    }
+    Ok(expr)
+}
+
+/// A coin that matched the filter, with the data fetched to evaluate it
+#[derive(Debug)]
+pub struct ScreenedCoin {
+    pub coin: MarketCoin,
+    pub holders: Option<HoldersResponse>,
+    pub details: Option<CoinDetailsResponse>,
+    /// Value of the sort key
+    pub sort_value: Option<f64>,
+}
+
+impl ScreenedCoin {
+    /// Value of a screener variable at `now`, `None` if its data wasn't fetched or there aren't
+    /// enough candles
+    pub fn variable(&self, name: &str, now: DateTime<Utc>) -> Option<Value> {
+        let coin = &self.coin;
+        let value = match name {
+            "symbol" => coin.symbol.as_str().into(),
+            "name" => coin.name.as_str().into(),
+            "creator" => coin.creator_name.as_deref().unwrap_or_default().into(),
+            "price" => coin.current_price.into(),
+            "market_cap" => coin.market_cap.into(),
+            "volume24h" => coin.volume24h.into(),
+            "change24h" => coin.change24h.into(),
+            "age" => ((now - coin.created_at).num_milliseconds() as f64 / 1000.0).into(),
+            name if HOLDER_VARIABLES.contains(&name) => self.holder_variable(name)?.into(),
+            name => self.candle_variable(name)?.into(),
+        };
+        Some(value)
+    }
+
+    fn holder_variable(&self, name: &str) -> Option<f64> {
+        let holders = self.holders.as_ref()?;
+        let concentration = risk::concentration(&holders.holders);
+        let value = match name {
+            "holders" => holders.total_holders as f64,
+            "top1_share" => concentration.top1_share / 100.0,
+            "top10_share" => concentration.top10_share / 100.0,
+            "herfindahl" => concentration.herfindahl,
+            "gini" => concentration.gini,
+            "liquidity" => holders.pool_info.base_currency_amount,
+            "dump_drain" => risk::rug_risk(holders, None)
+                .top_dump
+                .map_or(0.0, |d| d.pool_drain),
+            _ => return None,
+        };
+        Some(value)
+    }
+
+    fn candle_variable(&self, name: &str) -> Option<f64> {
+        let candles = &self.details.as_ref()?.candlestick_data;
+        match name {
+            "rsi" => last(Rsi::new(14), candles),
+            "sma" => last(Sma::new(20), candles),
+            "ema" => last(Ema::new(20), candles),
+            "roc" => last(Roc::new(12), candles),
+            "atr" => last(Atr::new(14), candles),
+            "candles" => Some(candles.len() as f64),
+            _ => None,
+        }
+    }
+}
+
+fn last<I: Indicator<Output = f64>>(mut indicator: I, candles: &[Candle]) -> Option<f64> {
+    indicator.batch(candles).pop().flatten()
+}
+
+/// See [`RugplayClient::screen`]
+pub(crate) async fn screen(
+    client: &RugplayClient,
+    screener: &Screener,
+) -> Result<Vec<ScreenedCoin>> {
+    let now = Utc::now();
+    let coins = market(client, screener.max_pages).await?;
+
+    let screened: Vec<Option<ScreenedCoin>> = stream::iter(coins)
+        .map(|coin| screen_coin(client, screener, coin, now))
+        .buffered(client.batch_concurrency)
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<Result<_>>()?;
+    let mut screened: Vec<ScreenedCoin> = screened.into_iter().flatten().collect();
+
+    if let Some((_, order)) = &screener.sort {
+        screened.sort_by(|a, b| match (a.sort_value, b.sort_value) {
+            (Some(a), Some(b)) if *order == SortOrder::Asc => a.total_cmp(&b),
+            (Some(a), Some(b)) => b.total_cmp(&a),
+            (a, b) => b.is_some().cmp(&a.is_some()),
+        });
+    }
+    if let Some(limit) = screener.limit {
+        screened.truncate(limit);
+    }
+    Ok(screened)
+}
+
+/// Every coin of the market, by market cap
+async fn market(client: &RugplayClient, max_pages: Option<u32>) -> Result<Vec<MarketCoin>> {
+    let mut coins = Vec::new();
+    let mut seen = BTreeSet::new();
+    let limit = PAGE_SIZE.to_string();
+    let mut page = 1;
+
+    loop {
+        let page_str = page.to_string();
+        let response = client
+            .get_market(&[
+                ("sortBy", "marketCap"),
+                ("sortOrder", "desc"),
+                ("limit", &limit),
+                ("page", &page_str),
+            ])
+            .await?;
+
+        let empty = response.coins.is_empty();
+        // Coins can move to another page between two requests
+        coins.extend(
+            response
+                .coins
+                .into_iter()
+                .filter(|c| seen.insert(c.symbol.clone())),
+        );
+        if empty || page >= response.total_pages || max_pages.is_some_and(|max| page >= max) {
+            return Ok(coins);
+        }
+        page += 1;
+    }
+}
+
+/// Evaluates the filter and sort key of a coin, fetching its holders and candles if needed
+async fn screen_coin(
+    client: &RugplayClient,
+    screener: &Screener,
+    coin: MarketCoin,
+    now: DateTime<Utc>,
+) -> Result<Option<ScreenedCoin>> {
+    let mut screened = ScreenedCoin {
+        coin,
+        holders: None,
+        details: None,
+        sort_value: None,
+    };
+
+    loop {
+        let result = screener
+            .filter
+            .eval_bool(&|name| screened.variable(name, now));
+        match result {
+            Ok(true) => break,
+            Ok(false) => return Ok(None),
+            Err(ExprError::Unavailable(name)) => {
+                match fetch(client, screener, &mut screened, &name).await {
+                    Ok(true) => {}
+                    // Fetched, but not enough data to compute it
+                    Ok(false) => return Ok(None),
+                    Err(e) => return Ok(fetch_failed(&screened, e)),
+                }
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    if let Some((key, _)) = &screener.sort {
+        screened.sort_value = loop {
+            match key.eval_number(&|name| screened.variable(name, now)) {
+                Ok(value) => break Some(value),
+                Err(ExprError::Unavailable(name)) => {
+                    match fetch(client, screener, &mut screened, &name).await {
+                        Ok(true) => {}
+                        Ok(false) => break None,
+                        Err(e) => return Ok(fetch_failed(&screened, e)),
+                    }
+                }
+                Err(e) => return Err(e.into()),
+            }
+        };
+    }
+    Ok(Some(screened))
+}
+
+/// A coin whose data can't be fetched doesn't match
+fn fetch_failed(screened: &ScreenedCoin, e: RugplayError) -> Option<ScreenedCoin> {
+    tracing::warn!("Screening {} failed: {e}", screened.coin.symbol);
+    None
+}
+
+/// Fetches the data of `variable`, returns `false` if it was already fetched
+async fn fetch(
+    client: &RugplayClient,
+    screener: &Screener,
+    screened: &mut ScreenedCoin,
+    variable: &str,
+) -> Result<bool> {
+    let symbol = &screened.coin.symbol;
+    if HOLDER_VARIABLES.contains(&variable) {
+        if screened.holders.is_some() {
+            return Ok(false);
+        }
+        screened.holders = Some(client.get_holders(symbol, None).await?);
+    } else {
+        if screened.details.is_some() {
+            return Ok(false);
+        }
+        let timeframe = Some(screener.timeframe.as_str());
+        screened.details = Some(client.get_coin_details(symbol, timeframe).await?);
+    }
+    Ok(true)
+}
@@ -0,0 +1,275 @@
+#![deny(clippy::all)]
+//! Whale and creator-dump detection on recent trades
+//!
+//! [`TradeDetector`] turns trades into [`TradeEvent`]s and publishes them to its subscribers.
+//! Trades come from polling [`crate::RugplayClient::get_recent_trades`] or from any other
+//! source through [`TradeDetector::process`].
+use crate::models::{PoolInfo, Symbol, Trade, TradeType};
+use crate::{Result, RugplayClient};
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+/// Events a subscriber can miss before it starts lagging
+const CHANNEL_CAPACITY: usize = 256;
+
+/// Something notable about a trade. A trade can raise several events
+#[derive(Debug, Clone)]
+pub enum TradeEvent {
+    /// A trade worth at least the whale threshold
+    Whale(Trade),
+    /// The creator of a coin sold some of it
+    CreatorSell(Trade),
+    /// A sell that took at least the drain threshold of the pool's base currency
+    LiquidityDrain {
+        trade: Trade,
+        /// Part of the pool's base currency the sell took, from 0 to 1
+        pool_drain: f64,
+    },
+}
+
+impl TradeEvent {
+    pub fn trade(&self) -> &Trade {
+        match self {
+            TradeEvent::Whale(trade)
+            | TradeEvent::CreatorSell(trade)
+            | TradeEvent::LiquidityDrain { trade, .. } => trade,
+        }
+    }
+}
+
+/// Part of the pool's base currency taken by a sell, from the pool after it
+///
+/// Assumes no other trade of the coin happened since the sell.
+pub fn pool_drain(trade: &Trade, pool_after: &PoolInfo) -> f64 {
+    let before = pool_after.base_currency_amount + trade.total_value;
+    if before > 0.0 {
+        trade.total_value / before
+    } else {
+        0.0
+    }
+}
+
+/// Identifies a trade, which has no id, between two polls
+type TradeKey = (u64, u64, Symbol, u64, u64);
+
+fn trade_key(trade: &Trade) -> TradeKey {
+    (
+        trade.timestamp,
+        trade.user_id.get(),
+        trade.coin_symbol.clone(),
+        trade.amount.to_bits(),
+        trade.total_value.to_bits(),
+    )
+}
+
+/// Detects whale trades, creator sells and liquidity drains
+#[derive(Debug)]
+pub struct TradeDetector {
+    client: RugplayClient,
+    sender: broadcast::Sender<TradeEvent>,
+    whale_threshold: Option<f64>,
+    creator_sells: bool,
+    drain_threshold: Option<f64>,
+    poll_interval: Duration,
+    trades_limit: u32,
+    /// Creator username of each coin, fetched once
+    creators: BTreeMap<Symbol, Option<String>>,
+    /// Trades of the last poll, `None` before the first one
+    seen: Option<BTreeSet<TradeKey>>,
+}
+
+impl TradeDetector {
+    /// A detector of trades worth $10k or more, creator sells, and sells draining 10% of a pool
+    pub fn new(client: RugplayClient) -> Self {
+        Self {
+            client,
+            sender: broadcast::channel(CHANNEL_CAPACITY).0,
+            whale_threshold: Some(10_000.0),
+            creator_sells: true,
+            drain_threshold: Some(0.1),
+            poll_interval: Duration::from_secs(5),
+            trades_limit: 100,
+            creators: BTreeMap::new(),
+            seen: None,
+        }
+    }
+
+    /// Minimum total value of a whale trade, `None` to disable them
+    pub fn with_whale_threshold(mut self, threshold: Option<f64>) -> Self {
+        self.whale_threshold = threshold;
+        self
+    }
+
+    /// Whether to detect creators selling their coin
+    pub fn with_creator_sells(mut self, enabled: bool) -> Self {
+        self.creator_sells = enabled;
+        self
+    }
+
+    /// Minimum part of a pool's base currency (0.1 for 10%) a sell must take to be a liquidity
+    /// drain, `None` to disable them
+    pub fn with_drain_threshold(mut self, threshold: Option<f64>) -> Self {
+        self.drain_threshold = threshold;
+        self
+    }
+
+    /// Time between two polls of [`TradeDetector::run`] (default: 5s)
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Number of recent trades read by a poll, max 100 (default: 100)
+    pub fn with_trades_limit(mut self, limit: u32) -> Self {
+        self.trades_limit = limit;
+        self
+    }
+
+    /// Receives the events of every later poll or [`TradeDetector::process`]
+    pub fn subscribe(&self) -> broadcast::Receiver<TradeEvent> {
+        self.sender.subscribe()
+    }
+
+    /// Fetches the recent trades and processes the ones that weren't in the previous poll
+    ///
+    /// The first poll only records the trades, so that old trades don't raise events.
+    pub async fn poll(&mut self) -> Result<Vec<TradeEvent>> {
+        let trades = self
+            .client
+            .get_recent_trades(self.trades_limit)
+            .await?
+            .trades;
+        let keys: BTreeSet<TradeKey> = trades.iter().map(trade_key).collect();
+
+        let Some(seen) = self.seen.as_ref() else {
+            self.seen = Some(keys);
+            return Ok(Vec::new());
+        };
+        // Oldest first
+        let new: Vec<Trade> = trades
+            .into_iter()
+            .rev()
+            .filter(|t| !seen.contains(&trade_key(t)))
+            .collect();
+
+        let events = self.process(new).await;
+        self.seen = Some(keys);
+        Ok(events)
+    }
+
+    /// Detects the events of `trades`, oldest first, and sends them to the subscribers
+    ///
+    /// Sells may need the coin's creator and pool, which are fetched. If they can't be, the
+    /// error is logged and the trade doesn't raise the events that need them.
+    pub async fn process(&mut self, trades: impl IntoIterator<Item = Trade>) -> Vec<TradeEvent> {
+        let trades: Vec<Trade> = trades.into_iter().collect();
+        let pools_after = self.pools_after(&trades).await;
+        let mut events = Vec::new();
+
+        for (trade, pool_after) in trades.into_iter().zip(pools_after) {
+            if self
+                .whale_threshold
+                .is_some_and(|threshold| trade.total_value >= threshold)
+            {
+                events.push(TradeEvent::Whale(trade.clone()));
+            }
+            if trade.r#type != TradeType::Sell {
+                continue;
+            }
+
+            if self.creator_sells {
+                match self.creator(&trade.coin_symbol).await {
+                    Ok(creator) if creator == Some(trade.username.as_str()) => {
+                        events.push(TradeEvent::CreatorSell(trade.clone()));
+                    }
+                    Ok(_) => {}
+                    Err(e) => {
+                        tracing::warn!("Could not fetch creator of {}: {e}", trade.coin_symbol)
+                    }
+                }
+            }
+
+            if let (Some(threshold), Some(pool_after)) = (self.drain_threshold, pool_after) {
+                let pool_drain = pool_drain(&trade, &pool_after);
+                if pool_drain >= threshold {
+                    events.push(TradeEvent::LiquidityDrain { trade, pool_drain });
+                }
+            }
+        }
+
+        for event in &events {
+            // No subscriber isn't an error
+            let _ = self.sender.send(event.clone());
+        }
+        events
+    }
+
+    /// The pool of the coin right after each sell of `trades`, `None` for the other trades
+    ///
+    /// Only the current pool of a coin can be fetched. The pool after an older sell is found by
+    /// undoing the later trades of `trades`, so trades missing from it (or made after it) skew
+    /// the result.
+    async fn pools_after(&self, trades: &[Trade]) -> Vec<Option<PoolInfo>> {
+        let mut pools_after = vec![None; trades.len()];
+        if self.drain_threshold.is_none() {
+            return pools_after;
+        }
+
+        let sold: BTreeSet<&Symbol> = trades
+            .iter()
+            .filter(|t| t.r#type == TradeType::Sell)
+            .map(|t| &t.coin_symbol)
+            .collect();
+        let mut pools: BTreeMap<&Symbol, PoolInfo> = BTreeMap::new();
+        for symbol in sold {
+            match self.client.get_holders(symbol, Some(1)).await {
+                Ok(holders) => {
+                    pools.insert(symbol, holders.pool_info);
+                }
+                Err(e) => tracing::warn!("Could not fetch pool of {symbol}: {e}"),
+            }
+        }
+
+        // Newest first, from the current pool
+        for (i, trade) in trades.iter().enumerate().rev() {
+            let Some(pool) = pools.get_mut(&trade.coin_symbol) else {
+                continue;
+            };
+            match trade.r#type {
+                TradeType::Sell => {
+                    pools_after[i] = Some(pool.clone());
+                    pool.base_currency_amount += trade.total_value;
+                    pool.coin_amount -= trade.amount;
+                }
+                TradeType::Buy => {
+                    pool.base_currency_amount -= trade.total_value;
+                    pool.coin_amount += trade.amount;
+                }
+                TradeType::TransferIn | TradeType::TransferOut => {}
+            }
+        }
+        pools_after
+    }
+
+    async fn creator(&mut self, symbol: &Symbol) -> Result<Option<&str>> {
+        if !self.creators.contains_key(symbol) {
+            let details = self.client.get_coin_details(symbol, None).await?;
+            self.creators
+                .insert(symbol.clone(), details.coin.creator_username);
+        }
+        Ok(self.creators[symbol].as_deref())
+    }
+
+    /// Polls forever, sending the events to the subscribers
+    ///
+    /// A failed poll is logged and retried at the next interval.
+    pub async fn run(mut self) {
+        loop {
+            if let Err(e) = self.poll().await {
+                tracing::warn!("Polling recent trades failed: {e}");
+            }
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+}
@@ -0,0 +1,91 @@
+#![deny(clippy::all)]
+//! Pluggable HTTP transport
+//!
+//! Every request of a [`crate::RugplayClient`] goes through a [`Transport`], a boxed
+//! [`tower::Service`] taking an [`HttpRequest`] and returning an [`HttpResponse`]. The default
+//! one sends requests with reqwest ([`ReqwestTransport`]). Wrap it with any tower layer
+//! (timeouts, concurrency limits, custom auth...) with
+//! [`crate::RugplayClient::with_layer`], or replace it with
+//! [`crate::RugplayClient::with_transport`].
+use bytes::Bytes;
+use futures::future::BoxFuture;
+use std::task::{Context, Poll};
+use tower::util::BoxCloneSyncService;
+use tower::{Service, ServiceExt};
+
+/// Error returned by a transport
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+/// Request sent to a transport
+pub type HttpRequest = http::Request<Bytes>;
+/// Response returned by a transport
+pub type HttpResponse = http::Response<Bytes>;
+
+/// A type erased, cloneable transport
+#[derive(Clone)]
+pub struct Transport(BoxCloneSyncService<HttpRequest, HttpResponse, BoxError>);
+
+impl Transport {
+    pub fn new<S>(service: S) -> Self
+    where
+        S: Service<HttpRequest, Response = HttpResponse> + Clone + Send + Sync + 'static,
+        S::Error: Into<BoxError>,
+        S::Future: Send + 'static,
+    {
+        Self(BoxCloneSyncService::new(service.map_err(Into::into)))
+    }
+}
+
+impl std::fmt::Debug for Transport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Transport")
+    }
+}
+
+impl Service<HttpRequest> for Transport {
+    type Response = HttpResponse;
+    type Error = BoxError;
+    type Future = BoxFuture<'static, Result<HttpResponse, BoxError>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), BoxError>> {
+        self.0.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: HttpRequest) -> Self::Future {
+        self.0.call(req)
+    }
+}
+
+/// Sends requests with a [`reqwest::Client`], including its cookie store
+#[derive(Debug, Clone)]
+pub struct ReqwestTransport(reqwest::Client);
+
+impl ReqwestTransport {
+    pub fn new(client: reqwest::Client) -> Self {
+        Self(client)
+    }
+}
+
+impl Service<HttpRequest> for ReqwestTransport {
+    type Response = HttpResponse;
+    type Error = BoxError;
+    type Future = BoxFuture<'static, Result<HttpResponse, BoxError>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), BoxError>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: HttpRequest) -> Self::Future {
+        let client = self.0.clone();
+        Box::pin(async move {
+            let resp = client.execute(reqwest::Request::try_from(req)?).await?;
+
+            let mut builder = http::Response::builder()
+                .status(resp.status())
+                .version(resp.version());
+            if let Some(headers) = builder.headers_mut() {
+                *headers = resp.headers().clone();
+            }
+            Ok(builder.body(resp.bytes().await?)?)
+        })
+    }
+}
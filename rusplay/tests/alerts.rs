@@ -0,0 +1,234 @@
+#![deny(clippy::all)]
+mod common;
+use chrono::{DateTime, Utc};
+use common::{coin_details, coin_details_json};
+use rusplay::alerts::*;
+use rusplay::models::{HopiumDetailsResponse, QuestionId, Symbol, Trade};
+use serde_json::{Value, json};
+use std::time::Duration;
+
+const START: i64 = 1_700_000_040;
+
+fn at(minutes: f64) -> DateTime<Utc> {
+    DateTime::from_timestamp(START + (minutes * 60.0) as i64, 0).unwrap()
+}
+
+fn symbol(symbol: &str) -> Symbol {
+    Symbol::new(symbol).unwrap()
+}
+
+fn trade(price: f64, amount: f64, seconds: i64) -> Trade {
+    let timestamp_ms = ((START + seconds) * 1000) as u64;
+    common::trade("TEST", "BUY", "alice", price, amount, timestamp_ms)
+}
+
+fn question(id: u64, yes_percentage: f64) -> HopiumDetailsResponse {
+    serde_json::from_value(question_json(id, yes_percentage)).unwrap()
+}
+
+fn question_json(id: u64, yes_percentage: f64) -> Value {
+    json!({
+        "question": {
+            "id": id,
+            "question": "Will TEST moon?",
+            "status": "ACTIVE",
+            "creator": {"id": 1, "name": "Alice", "username": "alice", "image": ""},
+            "totalAmount": 100.0,
+            "yesAmount": yes_percentage,
+            "noAmount": 100.0 - yes_percentage,
+            "yesPercentage": yes_percentage,
+            "noPercentage": 100.0 - yes_percentage,
+            "resolutionDate": null,
+            "createdAt": "2025-06-24T16:18:51.278Z",
+            "resolvedAt": null,
+        },
+        "probabilityHistory": [],
+    })
+}
+
+#[test]
+fn test_price_cross_debounce_and_cooldown() {
+    let mut engine = AlertEngine::new().with_rule(
+        Rule::new(
+            "TEST above 1",
+            Condition::PriceCross {
+                symbol: symbol("TEST"),
+                level: 1.0,
+                cross: Cross::Above,
+            },
+        )
+        .with_debounce(2)
+        .with_cooldown(Duration::from_secs(10 * 60)),
+    );
+    let mut receiver = engine.subscribe();
+
+    let fired = |engine: &mut AlertEngine, price: f64, minutes: f64| {
+        !engine
+            .on_coin(&coin_details("TEST", price, "dev"), at(minutes))
+            .is_empty()
+    };
+    // Already above at the first evaluation
+    assert!(!fired(&mut engine, 1.5, 0.0));
+    assert!(!fired(&mut engine, 0.5, 1.0));
+    // Debounced
+    assert!(!fired(&mut engine, 1.2, 2.0));
+    assert!(fired(&mut engine, 1.3, 3.0));
+    assert!(!fired(&mut engine, 1.4, 4.0));
+    assert!(!fired(&mut engine, 0.9, 5.0));
+    assert!(!fired(&mut engine, 1.1, 6.0));
+    // Cooling down until minute 13
+    assert!(!fired(&mut engine, 1.2, 7.0));
+    assert!(fired(&mut engine, 1.2, 13.0));
+    // Other coins don't matter
+    assert!(
+        engine
+            .on_coin(&coin_details("OTHER", 0.1, "dev"), at(15.0))
+            .is_empty()
+    );
+
+    let alert = receiver.try_recv().unwrap();
+    assert_eq!(alert.rule, "TEST above 1");
+    assert_eq!(alert.time, at(3.0));
+    assert_eq!(
+        alert.event,
+        AlertEvent::PriceCrossed {
+            symbol: symbol("TEST"),
+            price: 1.3,
+            level: 1.0,
+            cross: Cross::Above,
+        }
+    );
+    assert_eq!(receiver.try_recv().unwrap().time, at(13.0));
+    assert!(receiver.try_recv().is_err());
+
+    assert!(engine.remove_rule("TEST above 1"));
+    assert_eq!(engine.rules().count(), 0);
+}
+
+#[test]
+fn test_move_and_volume_spike_on_trades() {
+    let mut engine = AlertEngine::new()
+        .with_rule(Rule::new(
+            "pump",
+            Condition::Move {
+                symbol: symbol("TEST"),
+                percent: 10.0,
+                window: Duration::from_secs(5 * 60),
+            },
+        ))
+        .with_rule(Rule::new(
+            "volume",
+            Condition::VolumeSpike {
+                symbol: symbol("TEST"),
+                factor: 3.0,
+                lookback: 3,
+            },
+        ));
+
+    for minute in 0..=5 {
+        assert!(engine.on_trade(&trade(1.0, 100.0, minute * 60)).is_empty());
+    }
+
+    let alerts = engine.on_trade(&trade(1.2, 1_000.0, 5 * 60 + 10));
+    assert_eq!(alerts.len(), 2, "{alerts:?}");
+    match &alerts[0].event {
+        AlertEvent::Moved { change, window, .. } => {
+            assert!((change - 20.0).abs() < 1e-9, "{change}");
+            assert_eq!(window.as_secs(), 300);
+        }
+        other => panic!("{other:?}"),
+    }
+    assert_eq!(
+        alerts[1].event,
+        AlertEvent::VolumeSpike {
+            symbol: symbol("TEST"),
+            volume: 1_300.0,
+            average: 100.0,
+        }
+    );
+
+    // Still true, but already fired
+    assert!(engine.on_trade(&trade(1.25, 10.0, 5 * 60 + 20)).is_empty());
+}
+
+#[test]
+fn test_probability_cross() {
+    let mut engine = AlertEngine::new()
+        .with_rule(Rule::new(
+            "likely",
+            Condition::ProbabilityCross {
+                question: QuestionId(7),
+                level: 50.0,
+                cross: Cross::Above,
+            },
+        ))
+        .with_rule(Rule::new(
+            "unlikely",
+            Condition::ProbabilityCross {
+                question: QuestionId(7),
+                level: 20.0,
+                cross: Cross::Below,
+            },
+        ));
+
+    assert!(engine.on_hopium(&question(7, 40.0), at(0.0)).is_empty());
+    assert!(engine.on_hopium(&question(8, 90.0), at(1.0)).is_empty());
+
+    let alerts = engine.on_hopium(&question(7, 60.0), at(2.0));
+    assert_eq!(alerts.len(), 1);
+    assert_eq!(alerts[0].rule, "likely");
+    assert_eq!(
+        alerts[0].event,
+        AlertEvent::ProbabilityCrossed {
+            question: QuestionId(7),
+            probability: 60.0,
+            level: 50.0,
+            cross: Cross::Above,
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_poll_fetches_rule_targets() {
+    use httpmock::prelude::*;
+
+    let server = MockServer::start_async().await;
+    let coin = server
+        .mock_async(|when, then| {
+            when.method(GET)
+                .path("/coin/TEST")
+                .query_param("timeframe", "1m");
+            then.status(200)
+                .json_body(coin_details_json("TEST", 2.0, "dev"));
+        })
+        .await;
+    let hopium = server
+        .mock_async(|when, then| {
+            when.method(GET).path("/hopium/7");
+            then.status(200).json_body(question_json(7, 40.0));
+        })
+        .await;
+
+    let client = rusplay::RugplayClient::new("mock_token", None, Some(&server.base_url())).unwrap();
+    let mut engine = AlertEngine::new()
+        .with_rule(Rule::new(
+            "TEST",
+            Condition::PriceCross {
+                symbol: symbol("TEST"),
+                level: 1.0,
+                cross: Cross::Below,
+            },
+        ))
+        .with_rule(Rule::new(
+            "question",
+            Condition::ProbabilityCross {
+                question: QuestionId(7),
+                level: 50.0,
+                cross: Cross::Above,
+            },
+        ));
+
+    assert!(engine.poll(&client).await.is_empty());
+    coin.assert();
+    hopium.assert();
+}
@@ -0,0 +1,128 @@
+#![deny(clippy::all)]
+use httpmock::prelude::*;
+use rusplay::auth::{self, AuthError, RUGPLAY_DOMAIN};
+
+const NETSCAPE: &str = "# Netscape HTTP Cookie File\n\
+# This is a generated file! Do not edit.\n\
+\n\
+#HttpOnly_rugplay.com\tFALSE\t/\tTRUE\t4102444800\t__Secure-better-auth.session_token\tsession123\n\
+.rugplay.com\tTRUE\t/\tFALSE\t0\ttheme\tdark\n\
+.example.com\tTRUE\t/\tTRUE\t4102444800\t__Secure-better-auth.session_token\tother\n";
+
+const JSON_EXPORT: &str = r#"
+[
+  {
+    "domain": "rugplay.com",
+    "expirationDate": 4102444800.5,
+    "hostOnly": true,
+    "httpOnly": true,
+    "name": "__Secure-better-auth.session_token",
+    "path": "/",
+    "sameSite": "lax",
+    "secure": true,
+    "session": false,
+    "storeId": "0",
+    "value": "session123"
+  },
+  {
+    "domain": "rugplay.com",
+    "hostOnly": true,
+    "httpOnly": false,
+    "name": "__Secure-better-auth.session_data",
+    "path": "/",
+    "secure": true,
+    "session": true,
+    "value": "data456"
+  },
+  {
+    "domain": ".google.com",
+    "name": "NID",
+    "path": "/",
+    "secure": true,
+    "session": true,
+    "value": "tracking"
+  }
+]
+"#;
+
+#[test]
+fn test_parse_netscape() {
+    let cookies = auth::parse_netscape(NETSCAPE).unwrap();
+    assert_eq!(cookies.len(), 3);
+    assert_eq!(cookies[0].domain, "rugplay.com");
+    assert!(cookies[0].secure);
+    assert_eq!(cookies[0].expires.unwrap().timestamp(), 4102444800);
+    assert!(cookies[1].expires.is_none());
+
+    let session = auth::session_cookies(cookies, RUGPLAY_DOMAIN).unwrap();
+    assert_eq!(session.len(), 1);
+    assert_eq!(session[0].value.expose(), "session123");
+}
+
+#[test]
+fn test_parse_netscape_invalid_line() {
+    let err = auth::parse_netscape("rugplay.com\tFALSE\t/\n").unwrap_err();
+    assert!(matches!(err, AuthError::InvalidLine { line: 1, .. }));
+}
+
+#[test]
+fn test_parse_json_export() {
+    let cookies = auth::parse_json_export(JSON_EXPORT).unwrap();
+    assert_eq!(cookies.len(), 3);
+    assert_eq!(cookies[0].expires.unwrap().timestamp(), 4102444800);
+    assert!(cookies[1].expires.is_none());
+
+    let session = auth::session_cookies(cookies, RUGPLAY_DOMAIN).unwrap();
+    let names: Vec<&str> = session.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "__Secure-better-auth.session_token",
+            "__Secure-better-auth.session_data"
+        ]
+    );
+
+    let wrapped = format!(r#"{{"cookies": {JSON_EXPORT}}}"#);
+    assert_eq!(auth::parse_json_export(&wrapped).unwrap().len(), 3);
+}
+
+#[test]
+fn test_no_session_cookie() {
+    let cookies = auth::parse_netscape(".rugplay.com\tTRUE\t/\tFALSE\t0\ttheme\tdark\n").unwrap();
+    assert!(matches!(
+        auth::session_cookies(cookies, RUGPLAY_DOMAIN),
+        Err(AuthError::NoSessionCookie)
+    ));
+}
+
+#[tokio::test]
+async fn test_client_from_cookie_file_mocked() {
+    let server = MockServer::start_async().await;
+
+    let mock = server
+        .mock_async(|when, then| {
+            when.method(GET)
+                .path("/api/auth/get-session")
+                .cookie("__Secure-better-auth.session_token", "session123")
+                .cookie("__Secure-better-auth.session_data", "data456");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body("null");
+        })
+        .await;
+
+    let path = std::env::temp_dir().join(format!("rusplay-export-{}.json", server.port()));
+    std::fs::write(&path, JSON_EXPORT).unwrap();
+
+    let client = auth::client_from_cookie_file(
+        "mock_token",
+        &path,
+        Some(&format!("{}/api/v1", server.base_url())),
+    )
+    .unwrap();
+
+    assert!(client.get_session().await.unwrap().is_none());
+    mock.assert();
+
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,83 @@
+#![deny(clippy::all)]
+use httpmock::prelude::*;
+use rusplay::{RugplayClient, RugplayError};
+use std::time::{Duration, Instant};
+
+const HOLDERS: &str = r#"
+{
+  "coinSymbol": "TEST",
+  "totalHolders": 1,
+  "circulatingSupply": 1000000000,
+  "poolInfo": {
+    "coinAmount": 114176.23963001,
+    "baseCurrencyAmount": 8758389.68983547,
+    "currentPrice": 76.70938996
+  },
+  "holders": []
+}
+"#;
+
+#[tokio::test]
+async fn test_holders_many_per_item_errors() {
+    let server = MockServer::start_async().await;
+    let ok = server
+        .mock_async(|when, then| {
+            when.method(GET).path("/holders/TEST");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(HOLDERS);
+        })
+        .await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/holders/MISSING");
+            then.status(404).body(r#"{"error": "Coin not found"}"#);
+        })
+        .await;
+
+    let client = RugplayClient::new("mock_token", None, Some(&server.base_url()))
+        .unwrap()
+        .with_batch_concurrency(2);
+
+    let results = client
+        .get_holders_many(["TEST", "MISSING", "test", "NOT A COIN"], Some(10))
+        .await;
+
+    assert_eq!(results.len(), 3);
+    assert_eq!(results["TEST"].as_ref().unwrap().coin_symbol, "TEST");
+    assert!(results["MISSING"].is_err());
+    assert!(matches!(
+        results["NOT A COIN"],
+        Err(RugplayError::InvalidSymbol(_))
+    ));
+    ok.assert_hits(1);
+}
+
+#[tokio::test]
+async fn test_rate_limit_spaces_batch() {
+    let server = MockServer::start_async().await;
+    let mock = server
+        .mock_async(|when, then| {
+            when.method(GET).path_contains("/holders/");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(HOLDERS);
+        })
+        .await;
+
+    let client = RugplayClient::new("mock_token", None, Some(&server.base_url()))
+        .unwrap()
+        .with_rate_limit(10, Duration::from_secs(1));
+
+    let start = Instant::now();
+    let results = client.get_holders_many(["A", "B", "C", "D"], None).await;
+    let elapsed = start.elapsed();
+
+    assert!(results.values().all(|r| r.is_ok()));
+    mock.assert_hits(4);
+    // 4 requests, 100ms apart
+    assert!(elapsed >= Duration::from_millis(300), "{elapsed:?}");
+
+    let metrics = client.metrics();
+    assert_eq!(metrics.rate_limit_waits, 3);
+}
@@ -0,0 +1,149 @@
+#![deny(clippy::all)]
+#![cfg(feature = "blocking")]
+use httpmock::prelude::*;
+use rusplay::RugplayError;
+use rusplay::blocking::RugplayClient;
+use rusplay::models::CoinTradeType;
+use rusplay::transport::HttpRequest;
+use tower::util::MapRequestLayer;
+
+const TOP: &str = r#"
+{
+  "coins": [
+    {
+      "symbol": "TEST",
+      "name": "Test",
+      "icon": "coins/test.webp",
+      "price": 76.52377103,
+      "change24h": 7652377003.1039,
+      "marketCap": 76523771031.04,
+      "volume24h": 13744958.18
+    }
+  ]
+}
+"#;
+
+const HOLDERS: &str = r#"
+{
+  "coinSymbol": "TEST",
+  "totalHolders": 1,
+  "circulatingSupply": 1000000000,
+  "poolInfo": {
+    "coinAmount": 114176.23963001,
+    "baseCurrencyAmount": 8758389.68983547,
+    "currentPrice": 76.70938996
+  },
+  "holders": []
+}
+"#;
+
+const TRADE: &str = r#"
+{
+  "success": true,
+  "type": "BUY",
+  "coinsBought": 1.5,
+  "totalCost": 100,
+  "newPrice": 76.8,
+  "priceImpact": 0.01,
+  "newBalance": 900.0
+}
+"#;
+
+#[test]
+fn test_blocking_get_top_coins() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(GET)
+            .path("/top")
+            .header("authorization", "Bearer mock_token");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(TOP);
+    });
+
+    let client = RugplayClient::new("mock_token", None, Some(&server.base_url()))
+        .expect("Failed to create client");
+
+    let result = client.get_top_coins().expect("API call failed");
+    assert_eq!(result.coins[0].symbol, "TEST");
+    mock.assert();
+}
+
+#[test]
+fn test_blocking_holders_many_and_errors() {
+    let server = MockServer::start();
+    server.mock(|when, then| {
+        when.method(GET).path("/holders/TEST");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(HOLDERS);
+    });
+    server.mock(|when, then| {
+        when.method(GET).path("/holders/MISSING");
+        then.status(404).body(r#"{"error": "Coin not found"}"#);
+    });
+
+    let client = RugplayClient::new("mock_token", None, Some(&server.base_url())).unwrap();
+
+    let holders = client.get_holders("TEST", None).unwrap();
+    assert_eq!(holders.coin_symbol, "TEST");
+
+    let err = client.get_holders("MISSING", None).unwrap_err();
+    assert!(matches!(err, RugplayError::Request(_)));
+
+    let results = client.get_holders_many(["TEST", "MISSING"], None);
+    assert!(results["TEST"].is_ok());
+    assert!(results["MISSING"].is_err());
+}
+
+#[test]
+fn test_blocking_trade() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/api/coin/TEST/trade")
+            .json_body(serde_json::json!({"type": "BUY", "amount": 100}));
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(TRADE);
+    });
+
+    let client = RugplayClient::new(
+        "mock_token",
+        Some("session=abc".into()),
+        Some(&format!("{}/api/v1", server.base_url())),
+    )
+    .unwrap();
+
+    let trade = client.trade("TEST", CoinTradeType::BUY, 100).unwrap();
+    assert!(trade.success);
+    mock.assert();
+    assert_eq!(client.metrics().total_requests(), 1);
+}
+
+#[test]
+fn test_blocking_raw_with_layer() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(GET).path("/top").header("x-bot", "collector");
+        then.status(200)
+            .header("content-type", "application/json")
+            .header("x-request-id", "abc")
+            .body(TOP);
+    });
+
+    let client = RugplayClient::new("mock_token", None, Some(&server.base_url()))
+        .unwrap()
+        .with_layer(MapRequestLayer::new(|mut req: HttpRequest| {
+            req.headers_mut()
+                .insert("x-bot", "collector".parse().unwrap());
+            req
+        }));
+
+    let raw = client.with_raw().get_top_coins().unwrap();
+    assert_eq!(raw.value.coins[0].symbol, "TEST");
+    assert_eq!(raw.status, 200);
+    assert_eq!(raw.headers["x-request-id"], "abc");
+    assert_eq!(raw.body, TOP);
+    mock.assert();
+}
@@ -0,0 +1,195 @@
+#![deny(clippy::all)]
+use httpmock::prelude::*;
+use rusplay::RugplayClient;
+use rusplay::cache::CacheConfig;
+use rusplay::models::CoinTradeType;
+use std::time::Duration;
+
+const HOLDERS: &str = r#"
+{
+  "coinSymbol": "TEST",
+  "totalHolders": 1,
+  "circulatingSupply": 1000000000,
+  "poolInfo": {
+    "coinAmount": 114176.23963001,
+    "baseCurrencyAmount": 8758389.68983547,
+    "currentPrice": 76.70938996
+  },
+  "holders": []
+}
+"#;
+
+const TRADE: &str = r#"
+{
+  "success": true,
+  "type": "BUY",
+  "coinsBought": 1.5,
+  "totalCost": 100,
+  "newPrice": 76.8,
+  "priceImpact": 0.01,
+  "newBalance": 900.0
+}
+"#;
+
+#[tokio::test]
+async fn test_cache_hit_and_ttl() {
+    let server = MockServer::start_async().await;
+    let mock = server
+        .mock_async(|when, then| {
+            when.method(GET).path("/top");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(r#"{"coins": []}"#);
+        })
+        .await;
+
+    let client = RugplayClient::new("mock_token", None, Some(&server.base_url()))
+        .unwrap()
+        .with_cache(CacheConfig::empty().ttl("top", Duration::from_millis(200)));
+
+    client.get_top_coins().await.unwrap();
+    client.get_top_coins().await.unwrap();
+    mock.assert_hits(1);
+
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    client.get_top_coins().await.unwrap();
+    mock.assert_hits(2);
+
+    client.clear_cache();
+    client.get_top_coins().await.unwrap();
+    mock.assert_hits(3);
+}
+
+#[tokio::test]
+async fn test_cache_coalesces_concurrent_requests() {
+    let server = MockServer::start_async().await;
+    let mock = server
+        .mock_async(|when, then| {
+            when.method(GET).path("/holders/TEST");
+            then.status(200)
+                .header("content-type", "application/json")
+                .delay(Duration::from_millis(200))
+                .body(HOLDERS);
+        })
+        .await;
+
+    let client = RugplayClient::new("mock_token", None, Some(&server.base_url()))
+        .unwrap()
+        .with_cache(CacheConfig::default());
+
+    let (a, b, c) = tokio::join!(
+        client.get_holders("TEST", None),
+        client.get_holders("TEST", None),
+        client.get_holders("TEST", None),
+    );
+    assert!(a.is_ok() && b.is_ok() && c.is_ok());
+    mock.assert_hits(1);
+
+    // Different parameters are cached separately
+    client.get_holders("TEST", Some(5)).await.unwrap();
+    client.get_holders("TEST", Some(5)).await.unwrap();
+    mock.assert_hits(2);
+}
+
+#[tokio::test]
+async fn test_trade_invalidates_coin() {
+    let server = MockServer::start_async().await;
+    let holders = server
+        .mock_async(|when, then| {
+            when.method(GET).path("/api/v1/holders/TEST");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(HOLDERS);
+        })
+        .await;
+    let top = server
+        .mock_async(|when, then| {
+            when.method(GET).path("/api/v1/top");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(r#"{"coins": []}"#);
+        })
+        .await;
+    server
+        .mock_async(|when, then| {
+            when.method(POST).path("/api/coin/TEST/trade");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(TRADE);
+        })
+        .await;
+
+    let client = RugplayClient::new(
+        "mock_token",
+        None,
+        Some(&format!("{}/api/v1", server.base_url())),
+    )
+    .unwrap()
+    .with_cache(CacheConfig::default());
+
+    client.get_holders("TEST", None).await.unwrap();
+    client.get_top_coins().await.unwrap();
+    client.trade("TEST", CoinTradeType::BUY, 100).await.unwrap();
+    client.get_holders("TEST", None).await.unwrap();
+    client.get_top_coins().await.unwrap();
+
+    holders.assert_hits(2);
+    top.assert_hits(1);
+}
+
+#[tokio::test]
+async fn test_invalidation_during_request_is_not_cached_over() {
+    let server = MockServer::start_async().await;
+    let mock = server
+        .mock_async(|when, then| {
+            when.method(GET).path("/holders/TEST");
+            then.status(200)
+                .header("content-type", "application/json")
+                .delay(Duration::from_millis(200))
+                .body(HOLDERS);
+        })
+        .await;
+
+    let client = RugplayClient::new("mock_token", None, Some(&server.base_url()))
+        .unwrap()
+        .with_cache(CacheConfig::default());
+
+    let symbol = "TEST".parse().unwrap();
+    let (fetched, ()) = tokio::join!(client.get_holders("TEST", None), async {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        client.invalidate_coin(&symbol);
+    });
+    fetched.unwrap();
+
+    // The response may predate the invalidation
+    client.get_holders("TEST", None).await.unwrap();
+    mock.assert_hits(2);
+    client.get_holders("TEST", None).await.unwrap();
+    mock.assert_hits(2);
+}
+
+#[tokio::test]
+async fn test_cancelled_request_releases_key() {
+    let server = MockServer::start_async().await;
+    let mock = server
+        .mock_async(|when, then| {
+            when.method(GET).path("/holders/TEST");
+            then.status(200)
+                .header("content-type", "application/json")
+                .delay(Duration::from_millis(200))
+                .body(HOLDERS);
+        })
+        .await;
+
+    let client = RugplayClient::new("mock_token", None, Some(&server.base_url()))
+        .unwrap()
+        .with_cache(CacheConfig::default());
+
+    let cancelled =
+        tokio::time::timeout(Duration::from_millis(50), client.get_holders("TEST", None)).await;
+    assert!(cancelled.is_err());
+
+    client.get_holders("TEST", None).await.unwrap();
+    client.get_holders("TEST", None).await.unwrap();
+    mock.assert_hits(2);
+}
@@ -0,0 +1,143 @@
+#![deny(clippy::all)]
+mod common;
+use rusplay::candles::*;
+use rusplay::models::{Candle, Trade, VolumePoint};
+use std::time::Duration;
+
+fn trade(timestamp_ms: u64, price: f64, amount: f64) -> Trade {
+    common::trade("TEST", "BUY", "alice", price, amount, timestamp_ms)
+}
+
+#[test]
+fn test_timeframes() {
+    for timeframe in Timeframe::ALL {
+        assert_eq!(timeframe.as_str().parse::<Timeframe>(), Ok(timeframe));
+    }
+    assert_eq!(Duration::from(Timeframe::M15), Duration::from_secs(900));
+    assert_eq!(Timeframe::H4.to_string(), "4h");
+    assert!("2m".parse::<Timeframe>().is_err());
+}
+
+#[test]
+fn test_candles_from_trades() {
+    // Newest first, like /trades/recent, with nothing traded from 120s to 180s
+    let trades = [
+        trade(190_000, 4.0, 1.0),
+        trade(100_000, 3.0, 2.0),
+        trade(70_000, 1.0, 1.0),
+        trade(61_000, 2.0, 1.0),
+        trade(10_000, 1.5, 2.0),
+    ];
+
+    let series = from_trades(&trades, Timeframe::M1);
+
+    assert_eq!(
+        series.candles,
+        [
+            Candle::new(0, 1.5, 1.5, 1.5, 1.5),
+            Candle::new(60, 2.0, 3.0, 1.0, 3.0),
+            Candle::new(120, 3.0, 3.0, 3.0, 3.0),
+            Candle::new(180, 4.0, 4.0, 4.0, 4.0),
+        ]
+    );
+    assert_eq!(
+        series.volumes,
+        [
+            VolumePoint::new(0, 3.0),
+            VolumePoint::new(60, 9.0),
+            VolumePoint::new(120, 0.0),
+            VolumePoint::new(180, 4.0),
+        ]
+    );
+}
+
+#[test]
+fn test_resample() {
+    // 1m candles with the 3rd minute missing
+    let candles = [
+        Candle::new(600, 1.0, 2.0, 0.5, 1.5),
+        Candle::new(660, 1.5, 3.0, 1.0, 2.5),
+        Candle::new(780, 2.5, 2.6, 2.0, 2.1),
+        Candle::new(900, 2.1, 2.2, 1.9, 2.0),
+    ];
+    let volumes = [
+        VolumePoint::new(600, 10.0),
+        VolumePoint::new(660, 5.0),
+        VolumePoint::new(900, 1.0),
+    ];
+
+    let series = resample(&candles, &volumes, Timeframe::M5);
+    assert_eq!(
+        series.candles,
+        [
+            Candle::new(600, 1.0, 3.0, 0.5, 2.1),
+            Candle::new(900, 2.1, 2.2, 1.9, 2.0),
+        ]
+    );
+    assert_eq!(
+        series.volumes,
+        [VolumePoint::new(600, 15.0), VolumePoint::new(900, 1.0)]
+    );
+
+    // Resampling to the same interval fills the gap and keeps the rest
+    let same = resample(&candles, &volumes, Timeframe::M1);
+    assert_eq!(same.candles.len(), 6);
+    assert_eq!(same.candles[2], Candle::new(720, 2.5, 2.5, 2.5, 2.5));
+    assert_eq!(same.candles[3], candles[2]);
+}
+
+#[test]
+fn test_builder_without_forward_fill() {
+    let mut builder = CandleBuilder::new(Duration::from_secs(60)).with_forward_fill(false);
+    builder.push(0, 1.0, 1.0);
+    builder.push(300, 2.0, 1.0);
+    // Late point: extends its candle but doesn't move the close
+    builder.push(30, 0.5, 2.0);
+    // In a gap without a candle: dropped
+    builder.push(240, 9.0, 1.0);
+
+    assert_eq!(
+        builder.candles(),
+        [
+            Candle::new(0, 1.0, 1.0, 0.5, 1.0),
+            Candle::new(300, 2.0, 2.0, 2.0, 2.0),
+        ]
+    );
+    assert_eq!(builder.volumes()[0].volume, 3.0);
+}
+
+#[test]
+fn test_builder_max_candles() {
+    let mut builder = CandleBuilder::new(Duration::from_secs(60)).with_max_candles(2);
+    builder.push(0, 1.0, 1.0);
+    builder.push(180, 2.0, 1.0);
+
+    let times: Vec<u64> = builder.candles().iter().map(|c| c.time).collect();
+    assert_eq!(times, [120, 180]);
+    assert_eq!(builder.volumes()[1].volume, 1.0);
+}
+
+#[test]
+fn test_long_gap_is_not_filled_entirely() {
+    let mut builder = CandleBuilder::new(Duration::from_secs(60)).with_max_candles(3);
+    builder.push(0, 1.0, 1.0);
+    // 10^15 minutes of silence
+    builder.push(60_000_000_000_000_000, 2.0, 1.0);
+
+    assert_eq!(
+        builder.candles(),
+        [
+            Candle::new(59_999_999_999_999_880, 1.0, 1.0, 1.0, 1.0),
+            Candle::new(59_999_999_999_999_940, 1.0, 1.0, 1.0, 1.0),
+            Candle::new(60_000_000_000_000_000, 2.0, 2.0, 2.0, 2.0),
+        ]
+    );
+
+    let mut builder = CandleBuilder::new(Duration::from_secs(60));
+    builder.push(0, 1.0, 1.0);
+    builder.push(60_000_000_000_000_000, 2.0, 1.0);
+    assert_eq!(builder.candles().len(), MAX_FORWARD_FILL + 2);
+    assert_eq!(builder.volumes().len(), MAX_FORWARD_FILL + 2);
+    assert_eq!(builder.candles()[0].time, 0);
+    assert_eq!(builder.candles()[1].close, 1.0);
+}
@@ -1,10 +1,16 @@
 #![deny(clippy::all)]
+use httpmock::prelude::*;
 use rusplay::RugplayClient;
-use std::sync::Once;
+use rusplay::models::{CoinDetailsResponse, Trade};
+use rusplay::replay::{RecordLayer, ReplayTransport};
+use serde_json::{Value, json};
+use std::io::Write;
+use std::sync::{Arc, Mutex, Once};
 use tracing_subscriber::{EnvFilter, fmt};
 
 static INIT: Once = Once::new();
 
+#[allow(dead_code)]
 pub fn init_tracing() {
     INIT.call_once(|| {
         let env_filter =
@@ -17,10 +23,173 @@ pub fn init_tracing() {
     });
 }
 
+#[allow(dead_code)]
 pub fn test_client() -> Option<RugplayClient> {
     init_tracing();
 
     let token = std::env::var("RUGPLAY_TOKEN").ok()?;
     let cookie = std::env::var("RUGPLAY_COOKIE").ok();
-    RugplayClient::new(token, cookie, None).ok()
+    let client = RugplayClient::new(token, cookie, None).ok()?;
+
+    // Set RUGPLAY_RECORD to a directory to save the live traffic as fixtures
+    Some(match std::env::var("RUGPLAY_RECORD") {
+        Ok(dir) => client.with_layer(RecordLayer::new(dir)),
+        Err(_) => client,
+    })
+}
+
+/// Client answering from the fixtures of `tests/fixtures/replay`
+#[allow(dead_code)]
+pub fn replay_client() -> RugplayClient {
+    let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/replay");
+    RugplayClient::new("replay_token", None, None)
+        .unwrap()
+        .with_transport(ReplayTransport::open(dir).expect("Failed to load fixtures"))
+}
+
+/// A `/trades/recent` trade of `amount` coins at `price`
+#[allow(dead_code)]
+pub fn trade_json(
+    symbol: &str,
+    side: &str,
+    username: &str,
+    price: f64,
+    amount: f64,
+    timestamp_ms: u64,
+) -> Value {
+    json!({
+        "type": side,
+        "username": username,
+        "userImage": null,
+        "userId": "1",
+        "amount": amount,
+        "coinSymbol": symbol,
+        "coinName": symbol,
+        "coinIcon": null,
+        "totalValue": price * amount,
+        "price": price,
+        "timestamp": timestamp_ms,
+    })
+}
+
+#[allow(dead_code)]
+pub fn trade(
+    symbol: &str,
+    side: &str,
+    username: &str,
+    price: f64,
+    amount: f64,
+    timestamp_ms: u64,
+) -> Trade {
+    serde_json::from_value(trade_json(
+        symbol,
+        side,
+        username,
+        price,
+        amount,
+        timestamp_ms,
+    ))
+    .unwrap()
+}
+
+/// A `/coin/{symbol}` response without candles, for a supply of 1M
+#[allow(dead_code)]
+pub fn coin_details_json(symbol: &str, price: f64, creator: &str) -> Value {
+    json!({
+        "coin": {
+            "id": 1,
+            "name": symbol,
+            "symbol": symbol,
+            "currentPrice": price,
+            "marketCap": 100_000.0,
+            "volume24h": 0.0,
+            "change24h": 0.0,
+            "circulatingSupply": 1_000_000.0,
+            "initialSupply": 1_000_000.0,
+            "creatorUsername": creator,
+        },
+        "candlestickData": [],
+        "volumeData": [],
+        "timeframe": "1m",
+    })
+}
+
+#[allow(dead_code)]
+pub fn coin_details(symbol: &str, price: f64, creator: &str) -> CoinDetailsResponse {
+    serde_json::from_value(coin_details_json(symbol, price, creator)).unwrap()
+}
+
+/// A `/holders/{symbol}` response with `pool_base` in the pool and a supply of 1M, holders
+/// being `(username, quantity)` by rank
+#[allow(dead_code)]
+pub fn holders_json(symbol: &str, pool_base: f64, holders: &[(&str, f64)]) -> Value {
+    let supply = 1_000_000.0;
+    json!({
+        "coinSymbol": symbol,
+        "totalHolders": holders.len(),
+        "circulatingSupply": supply,
+        "poolInfo": {"coinAmount": 1_000_000.0, "baseCurrencyAmount": pool_base, "currentPrice": 0.1},
+        "holders": holders.iter().enumerate().map(|(i, (username, quantity))| json!({
+            "rank": i + 1,
+            "userId": i + 1,
+            "username": username,
+            "name": username,
+            "image": "",
+            "quantity": quantity,
+            "percentage": quantity / supply * 100.0,
+            "liquidationValue": 0.0,
+        })).collect::<Vec<_>>(),
+    })
+}
+
+/// Mocks the details and holders of a coin without holders
+#[allow(dead_code)]
+pub async fn mock_coin(server: &MockServer, symbol: &str, creator: &str, pool_base: f64) {
+    let details = coin_details_json(symbol, 0.1, creator);
+    let holders = holders_json(symbol, pool_base, &[]);
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path(format!("/coin/{symbol}"));
+            then.status(200).json_body(details);
+        })
+        .await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path(format!("/holders/{symbol}"));
+            then.status(200).json_body(holders);
+        })
+        .await;
+}
+
+/// Collects the formatted tracing output of a test
+#[allow(dead_code)]
+#[derive(Clone, Default)]
+pub struct Capture(Arc<Mutex<Vec<u8>>>);
+
+#[allow(dead_code)]
+impl Capture {
+    /// Installs a TRACE level subscriber writing to this capture for the current thread
+    pub fn set_default(&self) -> tracing::subscriber::DefaultGuard {
+        let writer = self.clone();
+        let subscriber = fmt()
+            .with_max_level(tracing::Level::TRACE)
+            .with_ansi(false)
+            .with_writer(move || writer.clone())
+            .finish();
+        tracing::subscriber::set_default(subscriber)
+    }
+
+    pub fn logs(&self) -> String {
+        String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+    }
+}
+
+impl Write for Capture {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
 }
@@ -0,0 +1,105 @@
+#![deny(clippy::all)]
+use httpmock::prelude::*;
+use rusplay::RugplayClient;
+use rusplay::depth::*;
+use rusplay::models::{PoolInfo, Symbol};
+
+fn pool() -> PoolInfo {
+    serde_json::from_str(
+        r#"{"coinAmount": 1000000, "baseCurrencyAmount": 100000, "currentPrice": 0.1}"#,
+    )
+    .unwrap()
+}
+
+fn assert_near(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() < 1e-6 * expected.abs().max(1.0),
+        "{actual} != {expected}"
+    );
+}
+
+#[test]
+fn test_depth_profile() {
+    let profile = depth_profile(Symbol::new("TEST").unwrap(), &pool(), &DEFAULT_SIZES);
+    assert_eq!(profile.spot_price, 0.1);
+    assert_eq!(profile.buy.len(), DEFAULT_SIZES.len());
+
+    let buy = &profile.buy[0];
+    assert_eq!(buy.size, TradeSize::Base(100.0));
+    assert_near(buy.coins, 1e8 / 100_100.0);
+    assert_near(buy.ending_price, 0.1002001);
+    assert_near(buy.impact, 0.2001);
+    assert!(buy.average_price > 0.1 && buy.average_price < buy.ending_price);
+
+    let buy = &profile.buy[5];
+    assert_near(buy.base, 10_000.0);
+    assert_near(buy.coins, 1e10 / 110_000.0);
+    assert_near(buy.impact, 21.0);
+
+    let sell = &profile.sell[0];
+    assert_near(sell.coins, 1_000.0);
+    assert_near(sell.base, 1e8 / 1_001_000.0);
+
+    let sell = &profile.sell[5];
+    assert_near(sell.coins, 100_000.0);
+    assert_near(sell.base, 1e10 / 1_100_000.0);
+    assert_near(sell.impact, (1.0 / 1.21 - 1.0) * 100.0);
+
+    // Bigger trades move the price more
+    let impacts: Vec<f64> = profile.sell.iter().map(|l| l.impact).collect();
+    assert!(impacts[..3].windows(2).all(|w| w[1] < w[0]), "{impacts:?}");
+}
+
+#[test]
+fn test_depth_table() {
+    let table = depth_profile(
+        Symbol::new("TEST").unwrap(),
+        &pool(),
+        &[TradeSize::Base(100.0), TradeSize::PoolPercent(10.0)],
+    )
+    .to_table();
+
+    let lines: Vec<&str> = table.lines().collect();
+    assert_eq!(lines.len(), 6, "{table}");
+    assert_eq!(lines[0], "*TEST depth, spot price 0.10000000");
+    assert!(
+        lines[2].starts_with("BUY") && lines[2].contains("$100"),
+        "{table}"
+    );
+    assert!(
+        lines[5].starts_with("SELL") && lines[5].contains("10% pool"),
+        "{table}"
+    );
+    assert!(lines[5].ends_with("-17.36%"), "{table}");
+}
+
+#[tokio::test]
+async fn test_get_depth_profile_mocked() {
+    let server = MockServer::start_async().await;
+    let mock = server
+        .mock_async(|when, then| {
+            when.method(GET).path("/holders/TEST").query_param("limit", "1");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(
+                    r#"{
+                      "coinSymbol": "TEST",
+                      "totalHolders": 0,
+                      "circulatingSupply": 1000000,
+                      "poolInfo": {"coinAmount": 1000000, "baseCurrencyAmount": 100000, "currentPrice": 0.1},
+                      "holders": []
+                    }"#,
+                );
+        })
+        .await;
+
+    let client = RugplayClient::new("mock_token", None, Some(&server.base_url())).unwrap();
+    let profile = client
+        .get_depth_profile("test", &DEFAULT_SIZES)
+        .await
+        .unwrap();
+
+    assert_eq!(profile.symbol, "TEST");
+    assert_eq!(profile.sell.len(), 6);
+    mock.assert();
+}
@@ -0,0 +1,103 @@
+#![deny(clippy::all)]
+use rusplay::expr::*;
+
+fn vars(name: &str) -> Option<Value> {
+    match name {
+        "market_cap" => Some(Value::Number(20_000.0)),
+        "age" => Some(Value::Number(3_600.0)),
+        "symbol" => Some(Value::from("TEST")),
+        _ => None,
+    }
+}
+
+fn eval(source: &str) -> Result<Value, ExprError> {
+    Expr::parse(source).unwrap().eval(&vars)
+}
+
+#[test]
+fn test_eval() {
+    assert_eq!(eval("1 + 2 * 3"), Ok(Value::Number(7.0)));
+    assert_eq!(eval("(1 + 2) * -3"), Ok(Value::Number(-9.0)));
+    assert_eq!(eval("10_000 + 60% + 2h"), Ok(Value::Number(17_200.6)));
+    assert_eq!(
+        eval("market_cap > 10000 and age < 2h"),
+        Ok(Value::Bool(true))
+    );
+    assert_eq!(
+        eval("symbol == 'TEST' && !(age > 1d)"),
+        Ok(Value::Bool(true))
+    );
+    assert_eq!(eval("symbol = \"OTHER\" || false"), Ok(Value::Bool(false)));
+    assert_eq!(eval("not true or 1 < 2"), Ok(Value::Bool(true)));
+}
+
+#[test]
+fn test_unavailable_variables() {
+    let expr = Expr::parse("market_cap > 50000 and top10_share < 0.6").unwrap();
+    assert_eq!(
+        expr.variables().into_iter().collect::<Vec<_>>(),
+        ["market_cap", "top10_share"]
+    );
+    // Decided by the available side
+    assert_eq!(expr.eval_bool(&vars), Ok(false));
+    assert_eq!(eval("top10_share < 0.6 or age > 1m"), Ok(Value::Bool(true)));
+
+    assert_eq!(
+        eval("market_cap > 10000 and top10_share < 0.6"),
+        Err(ExprError::Unavailable("top10_share".to_string()))
+    );
+    assert_eq!(
+        eval("rsi > 70 or age > 1d"),
+        Err(ExprError::Unavailable("rsi".to_string()))
+    );
+}
+
+#[test]
+fn test_errors() {
+    for invalid in ["", "1 +", "(1", "1 2", "a > > b", "2y", "'open", "a # b"] {
+        assert!(
+            matches!(Expr::parse(invalid), Err(ExprError::Parse { .. })),
+            "{invalid:?}"
+        );
+    }
+    assert_eq!(
+        Expr::parse("age < 2x"),
+        Err(ExprError::Parse {
+            position: 7,
+            message: "unknown unit \"x\"".to_string()
+        })
+    );
+
+    assert!(matches!(eval("symbol > 1"), Err(ExprError::Type(_))));
+    assert!(matches!(eval("1 and true"), Err(ExprError::Type(_))));
+    assert!(matches!(
+        Expr::parse("market_cap").unwrap().eval_bool(&vars),
+        Err(ExprError::Type(_))
+    ));
+}
+
+#[test]
+fn test_depth_limit() {
+    let nested = |depth: usize| format!("{}1{}", "(".repeat(depth), ")".repeat(depth));
+    assert!(Expr::parse(&nested(MAX_DEPTH)).is_ok());
+
+    let too_deep = [
+        nested(100_000),
+        "not ".repeat(100_000) + "true",
+        "-".repeat(100_000) + "1",
+        "1".to_string() + &" + 1".repeat(100_000),
+    ];
+    for source in &too_deep {
+        assert!(
+            matches!(Expr::parse(source), Err(ExprError::Parse { message, .. }) if message.contains("nested")),
+            "{}",
+            &source[..20]
+        );
+    }
+
+    let chain = "1".to_string() + &" + 1".repeat(MAX_DEPTH - 1);
+    assert_eq!(
+        Expr::parse(&chain).unwrap().eval_number(&vars),
+        Ok(MAX_DEPTH as f64)
+    );
+}
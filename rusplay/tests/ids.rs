@@ -0,0 +1,78 @@
+#![deny(clippy::all)]
+use httpmock::prelude::*;
+use rusplay::ids::{InvalidSymbol, QuestionId, Symbol, UserId};
+use rusplay::models::{Holder, Trade};
+use rusplay::{RugplayClient, RugplayError};
+
+#[test]
+fn test_symbol_normalization() {
+    assert_eq!(Symbol::new("test").unwrap(), "TEST");
+    assert_eq!(Symbol::new(" *Moon ").unwrap(), "MOON");
+    assert_eq!("btc".parse::<Symbol>().unwrap().to_string(), "BTC");
+
+    for invalid in ["", "*", "NOT A COIN", "ÜBER", "../top", "ABCDEFGHIJKLM"] {
+        assert_eq!(
+            Symbol::new(invalid),
+            Err(InvalidSymbol(invalid.to_string())),
+            "{invalid:?}"
+        );
+    }
+
+    // Symbols from responses are only normalized
+    let symbol: Symbol = serde_json::from_str(r#""*weird-coin_2025""#).unwrap();
+    assert_eq!(symbol, "WEIRD-COIN_2025");
+}
+
+#[test]
+fn test_ids_accept_numbers_and_strings() {
+    let holder: Holder = serde_json::from_str(
+        r#"{"rank": 1, "userId": "42", "username": "a", "name": "A", "image": "",
+            "quantity": 1, "percentage": 1, "liquidationValue": 1}"#,
+    )
+    .unwrap();
+    let trade: Trade = serde_json::from_str(
+        r#"{"amount": 1, "coinName": "Test", "coinSymbol": "test", "price": 1,
+            "timestamp": 0, "totalValue": 1, "type": "BUY", "userId": 42, "username": "a"}"#,
+    )
+    .unwrap();
+
+    assert_eq!(holder.user_id, UserId(42));
+    assert_eq!(trade.user_id, holder.user_id);
+    assert_eq!(trade.coin_symbol, "TEST");
+
+    assert_eq!(
+        serde_json::from_str::<QuestionId>("\"7\"").unwrap(),
+        QuestionId(7)
+    );
+    assert!(serde_json::from_str::<QuestionId>("-1").is_err());
+    assert!(serde_json::from_str::<UserId>("\"abc\"").is_err());
+    assert_eq!(serde_json::to_string(&UserId(42)).unwrap(), "42");
+}
+
+#[tokio::test]
+async fn test_invalid_symbol_is_not_sent() {
+    let server = MockServer::start_async().await;
+    let mock = server
+        .mock_async(|when, then| {
+            when.any_request();
+            then.status(500);
+        })
+        .await;
+
+    let client = RugplayClient::new("mock_token", None, Some(&server.base_url())).unwrap();
+
+    let err = client.get_holders("../top", None).await.unwrap_err();
+    assert!(matches!(err, RugplayError::InvalidSymbol(_)));
+    let results = client
+        .get_coin_details_many(["../top", "NOT A COIN"], None)
+        .await;
+    assert!(matches!(
+        results["NOT A COIN"],
+        Err(RugplayError::InvalidSymbol(_))
+    ));
+    assert!(matches!(
+        results["../top"],
+        Err(RugplayError::InvalidSymbol(_))
+    ));
+    mock.assert_hits(0);
+}
@@ -0,0 +1,172 @@
+#![deny(clippy::all)]
+use rusplay::indicators::*;
+use rusplay::models::{Candle, VolumePoint};
+
+/// Closes of StockCharts' moving average example
+const EMA_CLOSES: [f64; 30] = [
+    22.27, 22.19, 22.08, 22.17, 22.18, 22.13, 22.23, 22.43, 22.24, 22.29, 22.15, 22.39, 22.38,
+    22.61, 23.36, 24.05, 23.75, 23.83, 23.95, 23.63, 23.82, 23.87, 23.65, 23.19, 23.10, 23.33,
+    22.68, 23.10, 22.40, 22.17,
+];
+
+/// Closes of StockCharts' RSI example
+const RSI_CLOSES: [f64; 33] = [
+    44.34, 44.09, 44.15, 43.61, 44.33, 44.83, 45.10, 45.42, 45.84, 46.08, 45.89, 46.03, 45.61,
+    46.28, 46.28, 46.00, 46.03, 46.41, 46.22, 45.64, 46.21, 46.25, 45.71, 46.45, 45.78, 45.35,
+    44.03, 44.18, 44.22, 44.57, 43.42, 42.66, 43.13,
+];
+
+fn candles(closes: &[f64]) -> Vec<Candle> {
+    closes
+        .iter()
+        .enumerate()
+        .map(|(i, &c)| Candle::new(i as u64 * 60, c, c, c, c))
+        .collect()
+}
+
+fn assert_close(actual: &[Option<f64>], expected: &[f64], tolerance: f64) {
+    let warm_up = actual.len() - expected.len();
+    assert!(actual[..warm_up].iter().all(Option::is_none), "{actual:?}");
+    for (i, (a, e)) in actual[warm_up..].iter().zip(expected).enumerate() {
+        let i = i + warm_up;
+        let a = a.unwrap_or_else(|| panic!("no value at {i}"));
+        assert!((a - e).abs() <= tolerance, "value at {i}: {a} != {e}");
+    }
+}
+
+#[test]
+fn test_sma_and_ema() {
+    let series = candles(&EMA_CLOSES);
+
+    let sma = Sma::new(10).batch(&series);
+    assert_close(
+        &sma,
+        &[
+            22.22, 22.21, 22.23, 22.26, 22.30, 22.42, 22.61, 22.77, 22.91, 23.08, 23.21, 23.38,
+            23.52, 23.65, 23.71, 23.68, 23.61, 23.51, 23.43, 23.28, 23.13,
+        ],
+        0.01,
+    );
+
+    let ema = Ema::new(10).batch(&series);
+    assert_close(
+        &ema,
+        &[
+            22.22, 22.21, 22.24, 22.27, 22.33, 22.52, 22.80, 22.97, 23.13, 23.28, 23.34, 23.43,
+            23.51, 23.53, 23.47, 23.40, 23.39, 23.26, 23.23, 23.08, 22.92,
+        ],
+        0.01,
+    );
+}
+
+#[test]
+fn test_rsi() {
+    let rsi = Rsi::new(14).batch(&candles(&RSI_CLOSES));
+    assert_close(
+        &rsi,
+        &[
+            70.46, 66.25, 66.48, 69.35, 66.29, 57.92, 62.88, 63.21, 56.01, 62.34, 54.67, 50.39,
+            40.02, 41.49, 41.90, 45.50, 37.32, 33.09, 37.79,
+        ],
+        0.01,
+    );
+
+    let mut flat = Rsi::new(2);
+    assert_eq!(flat.update(1.0), None);
+    assert_eq!(flat.update(1.0), None);
+    assert_eq!(flat.update(1.0), Some(50.0));
+    assert_eq!(flat.update(2.0), Some(100.0));
+}
+
+#[test]
+fn test_macd() {
+    let macd = Macd::new(3, 6, 4).batch(&candles(&EMA_CLOSES));
+
+    assert!(macd[..8].iter().all(Option::is_none));
+    let first = macd[8].unwrap();
+    assert!((first.macd - 0.0237).abs() < 1e-4, "{first:?}");
+    assert!((first.signal - 0.0166).abs() < 1e-4, "{first:?}");
+    let last = macd[29].unwrap();
+    assert!((last.macd + 0.2783).abs() < 1e-4, "{last:?}");
+    assert!((last.signal + 0.2071).abs() < 1e-4, "{last:?}");
+    assert!((last.histogram - (last.macd - last.signal)).abs() < 1e-12);
+}
+
+#[test]
+fn test_bollinger_bands() {
+    let bands = BollingerBands::default().batch(&candles(&EMA_CLOSES));
+
+    assert_close(
+        &bands
+            .iter()
+            .map(|b| b.map(|b| b.middle))
+            .collect::<Vec<_>>(),
+        &[
+            22.7155, 22.793, 22.877, 22.9555, 23.0065, 23.0525, 23.1125, 23.135, 23.1685, 23.1765,
+            23.1705,
+        ],
+        1e-4,
+    );
+    let first = bands[19].unwrap();
+    assert!((first.upper - 24.1261).abs() < 1e-4, "{first:?}");
+    assert!((first.lower - 21.3049).abs() < 1e-4, "{first:?}");
+}
+
+#[test]
+fn test_atr() {
+    let series = [
+        Candle::new(0, 9.0, 10.0, 8.0, 9.0),
+        Candle::new(60, 9.0, 11.0, 9.0, 10.5),
+        Candle::new(120, 10.5, 12.0, 10.0, 11.0),
+        Candle::new(180, 11.0, 11.5, 9.5, 10.0),
+        // Gap up: the true range goes back to the previous close
+        Candle::new(240, 10.0, 13.0, 10.0, 12.5),
+        Candle::new(300, 12.5, 12.0, 11.0, 11.5),
+    ];
+
+    let atr = Atr::new(3).batch(&series);
+    assert_close(&atr, &[2.0, 2.0, 2.3333, 2.0556], 1e-4);
+}
+
+#[test]
+fn test_vwap() {
+    let series = [
+        Candle::new(0, 10.0, 12.0, 9.0, 9.0),
+        Candle::new(60, 10.0, 11.0, 10.0, 12.0),
+        Candle::new(120, 12.0, 20.0, 20.0, 20.0),
+    ];
+    let volumes = [VolumePoint::new(60, 1.0), VolumePoint::new(120, 3.0)];
+
+    let vwap = Vwap::new().batch(&series, &volumes);
+    assert_eq!(vwap, [None, Some(11.0), Some(17.75)]);
+
+    let mut trades = Vwap::new();
+    trades.update(2.0, 1.0);
+    assert_eq!(trades.update(4.0, 3.0), Some(3.5));
+}
+
+#[test]
+fn test_roc() {
+    let roc = Roc::new(12).batch(&candles(&EMA_CLOSES));
+    assert_close(
+        &roc,
+        &[
+            0.4939, 1.8927, 5.7971, 8.4799, 7.0784, 7.6819, 7.7373, 5.35, 7.1043, 7.0884, 6.772,
+            3.573, 3.2172, 3.1844, -2.911, -3.9501, -5.6842, -6.966,
+        ],
+        1e-4,
+    );
+}
+
+#[test]
+fn test_streaming_matches_batch() {
+    let series = candles(&RSI_CLOSES);
+    let batch = Rsi::new(14).batch(&series);
+
+    let mut rsi = Rsi::new(14);
+    let streamed: Vec<_> = RSI_CLOSES.iter().map(|&c| rsi.update(c)).collect();
+    assert_eq!(streamed, batch);
+
+    rsi.reset();
+    assert_eq!(rsi.batch(&series), batch);
+}
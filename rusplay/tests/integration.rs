@@ -1,25 +1,25 @@
 #![deny(clippy::all)]
+//! Runs every endpoint against the fixtures of `tests/fixtures/replay`
+//!
+//! To refresh them, record live traffic with `RUGPLAY_TOKEN` and `RUGPLAY_RECORD` set (see
+//! `common::test_client`).
 mod common;
-use std::time::Duration;
 
-use common::test_client;
-use rusplay::models::CoinTradeType;
+use common::replay_client;
+use rusplay::models::{CoinTradeType, HopiumFilter};
 
 #[tokio::test]
-#[ignore]
-async fn test_get_top_coins_live() {
-    let client = test_client().expect("Missing credentials: RUGPLAY_COOKIE or RUGPLAY_TOKEN");
+async fn test_get_top_coins() {
+    let client = replay_client();
     let result = client.get_top_coins().await;
     assert!(result.is_ok(), "API call failed: {:#?}", result);
     let response = result.unwrap();
     assert!(!response.coins.is_empty(), "Expected non-empty coin list");
-    println!("First coin: {:?}", response.coins[0]);
 }
 
 #[tokio::test]
-#[ignore]
-async fn test_get_market_live() {
-    let client = test_client().expect("Missing credentials: RUGPLAY_COOKIE or RUGPLAY_TOKEN");
+async fn test_get_market() {
+    let client = replay_client();
     let params = &[
         ("limit", "5"),
         ("sortBy", "marketCap"),
@@ -29,39 +29,34 @@ async fn test_get_market_live() {
     assert!(result.is_ok(), "API call failed: {:?}", result);
     let response = result.unwrap();
     assert!(!response.coins.is_empty(), "Expected non-empty market data");
-    println!("Market response sample: {:?}", response.coins.first());
 }
 
 #[tokio::test]
-#[ignore]
-async fn test_get_coin_details_live() {
-    let client = test_client().expect("Missing credentials: RUGPLAY_COOKIE or RUGPLAY_TOKEN");
-    let result = client.get_coin_details("BTC", Some("1h")).await;
+async fn test_get_coin_details() {
+    let client = replay_client();
+    let result = client.get_coin_details("TEST", Some("1h")).await;
     assert!(result.is_ok(), "API call failed: {:?}", result);
     let response = result.unwrap();
-    assert_eq!(response.coin.symbol, "BTC", "Expected BTC coin details");
-    println!("Coin details: {:?}", response);
+    assert_eq!(response.coin.symbol, "TEST", "Expected TEST coin details");
+    assert!(!response.candlestick_data.is_empty());
 }
 
 #[tokio::test]
-#[ignore]
-async fn test_get_holders_live() {
-    let client = test_client().expect("Missing credentials: RUGPLAY_COOKIE or RUGPLAY_TOKEN");
-    let result = client.get_holders("BTC", Some(10)).await;
+async fn test_get_holders() {
+    let client = replay_client();
+    let result = client.get_holders("TEST", Some(10)).await;
     assert!(result.is_ok(), "API call failed: {:?}", result);
     let response = result.unwrap();
+    assert_eq!(response.coin_symbol, "TEST");
     assert!(
         !response.holders.is_empty(),
         "Expected non-empty holder list"
     );
-    println!("Top holder: {:?}", response.holders.first());
 }
 
 #[tokio::test]
-#[ignore]
-async fn test_get_hopium_live() {
-    use rusplay::models::HopiumFilter;
-    let client = test_client().expect("Missing credentials: RUGPLAY_COOKIE or RUGPLAY_TOKEN");
+async fn test_get_hopium() {
+    let client = replay_client();
     let result = client.get_hopium(HopiumFilter::ALL, Some(5), Some(1)).await;
     assert!(result.is_ok(), "API call failed: {:?}", result);
     let response = result.unwrap();
@@ -69,74 +64,52 @@ async fn test_get_hopium_live() {
         !response.questions.is_empty(),
         "Expected non-empty hopium questions"
     );
-    println!("First hopium question: {:?}", response.questions.first());
 }
 
 #[tokio::test]
-#[ignore]
-async fn test_get_hopium_details_live() {
-    let client = test_client().expect("Missing credentials: RUGPLAY_COOKIE or RUGPLAY_TOKEN");
-    // Pick a known question ID or get from `get_hopium`
+async fn test_get_hopium_details() {
+    let client = replay_client();
     let question_id = 1;
     let result = client.get_hopium_details(question_id).await;
     assert!(result.is_ok(), "API call failed: {:?}", result);
     let response = result.unwrap();
     assert_eq!(
-        response.question.id as u32, question_id,
+        response.question.id, question_id,
         "Expected matching hopium question ID"
     );
-    println!("Hopium question details: {:?}", response);
 }
 
 #[tokio::test]
-#[ignore]
-async fn test_get_claim_info_live() {
-    let client = test_client().expect("Missing credentials: RUGPLAY_COOKIE or RUGPLAY_TOKEN");
+async fn test_claim_reward() {
+    let client = replay_client();
     let result = client.get_claim_info().await;
     assert!(result.is_ok(), "API call failed: {:?}", result);
-    let response = result.unwrap();
-    println!("Claim info: {:?}", response);
-}
+    assert!(result.unwrap().can_claim);
 
-#[tokio::test]
-#[ignore]
-async fn test_claim_reward_live() {
-    let client = test_client().expect("Missing credentials: RUGPLAY_COOKIE or RUGPLAY_TOKEN");
     let result = client.claim_reward().await;
     assert!(result.is_ok(), "API call failed: {:?}", result);
-    let response = result.unwrap();
-    println!("Claim reward response: {:?}", response);
+    assert!(result.unwrap().success);
 }
 
 #[tokio::test]
-#[ignore]
-async fn test_trade_live() {
-    let client = test_client().expect("Missing credentials: RUGPLAY_COOKIE or RUGPLAY_TOKEN");
-    let buy = client.trade("BTC", CoinTradeType::BUY, 1).await;
+async fn test_trade() {
+    let client = replay_client();
+    let buy = client.trade("TEST", CoinTradeType::BUY, 1).await;
     assert!(buy.is_ok(), "API call failed: {:?}", buy);
     let buy_response = buy.unwrap();
-    println!("Buy response: {:?}", buy_response);
+    assert!(buy_response.coins_bought.is_some());
 
-    std::thread::sleep(Duration::from_secs(1));
-
-    let sell = client.trade("BTC", CoinTradeType::SELL, 1).await;
+    let sell = client.trade("TEST", CoinTradeType::SELL, 1).await;
     assert!(sell.is_ok(), "API call failed: {:?}", sell);
     let sell_response = sell.unwrap();
-    println!("Claim reward response: {:?}", sell_response);
+    assert_eq!(sell_response.coins_sold, Some(1.0));
 }
 
 #[tokio::test]
-#[ignore]
-async fn test_recent_trades_live() {
-    let client = test_client().expect("Missing credentials: RUGPLAY_COOKIE or RUGPLAY_TOKEN");
-
+async fn test_recent_trades() {
+    let client = replay_client();
     let result = client.get_recent_trades(100).await;
-
     assert!(result.is_ok(), "API call failed: {:?}", result);
     let response = result.unwrap();
-    println!(
-        "Got {} trades. First trade: {:?}",
-        response.trades.len(),
-        response.trades.first().unwrap()
-    );
+    assert!(!response.trades.is_empty(), "Expected non-empty trades");
 }
@@ -0,0 +1,124 @@
+#![deny(clippy::all)]
+mod common;
+use common::{mock_coin, trade_json};
+use httpmock::prelude::*;
+use rusplay::RugplayClient;
+use rusplay::launches::LaunchWatcher;
+use serde_json::{Value, json};
+
+fn market(symbols: &[&str]) -> Value {
+    let coins: Vec<Value> = symbols
+        .iter()
+        .map(|symbol| {
+            json!({
+                "symbol": symbol,
+                "name": symbol,
+                "icon": null,
+                "currentPrice": 0.1,
+                "marketCap": 100_000.0,
+                "volume24h": 0.0,
+                "change24h": 0.0,
+                "createdAt": "2025-06-24T16:18:51.278Z",
+            })
+        })
+        .collect();
+    json!({"coins": coins, "total": symbols.len(), "page": 1, "limit": 20, "totalPages": 1})
+}
+
+fn trade(symbol: &str, timestamp: u64) -> Value {
+    trade_json(symbol, "BUY", "alice", 0.1, 10.0, timestamp)
+}
+
+async fn mock_market<'a>(server: &'a MockServer, symbols: &[&str]) -> httpmock::Mock<'a> {
+    let body = market(symbols);
+    server
+        .mock_async(|when, then| {
+            when.method(GET)
+                .path("/market")
+                .query_param("sortBy", "createdAt");
+            then.status(200).json_body(body);
+        })
+        .await
+}
+
+#[tokio::test]
+async fn test_launch_filters_and_first_trades() {
+    let server = MockServer::start_async().await;
+    let first = mock_market(&server, &["OLD"]).await;
+
+    let client = RugplayClient::new("mock_token", None, Some(&server.base_url())).unwrap();
+    let mut watcher = LaunchWatcher::new(client)
+        .with_creators(["bob", "carol"])
+        .with_excluded_creators(["carol"])
+        .with_min_liquidity(1_000.0)
+        .with_max_liquidity(1_000_000.0)
+        .with_first_trades(2, 100);
+    assert!(watcher.poll().await.unwrap().is_empty());
+    first.delete_async().await;
+
+    // Newest first
+    mock_market(&server, &["HUGE", "TINY", "EVE", "CAROL", "NEW", "OLD"]).await;
+    mock_coin(&server, "NEW", "bob", 10_000.0).await;
+    mock_coin(&server, "CAROL", "carol", 10_000.0).await;
+    mock_coin(&server, "EVE", "eve", 10_000.0).await;
+    mock_coin(&server, "TINY", "bob", 10.0).await;
+    mock_coin(&server, "HUGE", "bob", 10_000_000.0).await;
+    let recent = json!({"trades": [
+        trade("NEW", 4),
+        trade("OTHER", 3),
+        trade("NEW", 2),
+        trade("NEW", 1),
+    ]});
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/trades/recent");
+            then.status(200).json_body(recent);
+        })
+        .await;
+
+    let launches = watcher.poll().await.unwrap();
+    assert_eq!(launches.len(), 1, "{launches:?}");
+    let launch = &launches[0];
+    assert_eq!(launch.symbol(), "NEW");
+    assert_eq!(launch.creator(), Some("bob"));
+    assert_eq!(launch.liquidity(), 10_000.0);
+    let timestamps: Vec<u64> = launch.first_trades.iter().map(|t| t.timestamp).collect();
+    assert_eq!(timestamps, [1, 2]);
+
+    // Every coin was seen, even the filtered ones
+    assert!(watcher.poll().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_failing_coin_is_retried() {
+    let server = MockServer::start_async().await;
+    let first = mock_market(&server, &["OLD"]).await;
+
+    let client = RugplayClient::new("mock_token", None, Some(&server.base_url())).unwrap();
+    let mut watcher = LaunchWatcher::new(client).with_first_trades(0, 0);
+    assert!(watcher.poll().await.unwrap().is_empty());
+    first.delete_async().await;
+
+    mock_market(&server, &["GOOD", "BROKEN", "OLD"]).await;
+    mock_coin(&server, "GOOD", "bob", 10_000.0).await;
+    let broken = server
+        .mock_async(|when, then| {
+            when.method(GET).path("/holders/BROKEN");
+            then.status(500);
+        })
+        .await;
+
+    let launches = watcher.poll().await.unwrap();
+    assert_eq!(launches.len(), 1);
+    assert_eq!(launches[0].symbol(), "GOOD");
+
+    broken.assert_hits(1);
+    broken.delete_async().await;
+
+    // The broken coin is retried until it can be fetched, then seen
+    mock_coin(&server, "BROKEN", "bob", 10_000.0).await;
+    let launches = watcher.poll().await.unwrap();
+    assert_eq!(launches.len(), 1);
+    assert_eq!(launches[0].symbol(), "BROKEN");
+    assert!(watcher.poll().await.unwrap().is_empty());
+}
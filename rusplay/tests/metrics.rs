@@ -0,0 +1,89 @@
+#![deny(clippy::all)]
+use httpmock::prelude::*;
+use rusplay::RugplayClient;
+use rusplay::metrics::{ErrorKind, LatencyHistogram};
+use std::time::Duration;
+
+#[tokio::test]
+async fn test_request_metrics() {
+    let server = MockServer::start_async().await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/top");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(r#"{"coins": []}"#);
+        })
+        .await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/coin/NOPE");
+            then.status(404).body(r#"{"error": "Coin not found"}"#);
+        })
+        .await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/coin/BAD");
+            then.status(200).body(r#"{"coin": null}"#);
+        })
+        .await;
+
+    let client = RugplayClient::new("mock_token", None, Some(&server.base_url())).unwrap();
+    client.get_top_coins().await.unwrap();
+    client.get_top_coins().await.unwrap();
+    client.get_coin_details("NOPE", None).await.unwrap_err();
+    client.get_coin_details("BAD", None).await.unwrap_err();
+
+    // Clones share the same metrics
+    let metrics = client.clone().metrics();
+    assert_eq!(metrics.total_requests(), 4);
+    assert_eq!(metrics.total_errors(), 2);
+
+    let top = &metrics.endpoints["top"];
+    assert_eq!(top.requests, 2);
+    assert_eq!(top.error_count(), 0);
+    assert_eq!(top.latency.count, 2);
+    assert_eq!(top.latency.buckets.iter().sum::<u64>(), 2);
+
+    let coin = &metrics.endpoints["coin"];
+    assert_eq!(coin.requests, 2);
+    assert_eq!(coin.errors[&ErrorKind::Http], 1);
+    assert_eq!(coin.errors[&ErrorKind::Deserialize], 1);
+
+    client.reset_metrics();
+    assert_eq!(client.metrics().total_requests(), 0);
+}
+
+#[tokio::test]
+async fn test_rate_limit_wait_metrics() {
+    let server = MockServer::start_async().await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/top");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(r#"{"coins": []}"#);
+        })
+        .await;
+
+    let client = RugplayClient::new("mock_token", None, Some(&server.base_url()))
+        .unwrap()
+        .with_rate_limit(1, Duration::from_millis(100));
+    client.get_top_coins().await.unwrap();
+    client.get_top_coins().await.unwrap();
+
+    let metrics = client.metrics();
+    assert_eq!(metrics.rate_limit_waits, 1);
+    assert!(metrics.rate_limit_wait_time > Duration::ZERO);
+    assert_eq!(metrics.endpoints["top"].requests, 2);
+}
+
+#[test]
+fn test_latency_mean() {
+    let mut histogram = LatencyHistogram::default();
+    assert_eq!(histogram.mean(), None);
+
+    histogram.count = 1 << 32;
+    histogram.sum = Duration::from_secs(1 << 33);
+    assert_eq!(histogram.mean(), Some(Duration::from_secs(2)));
+}
@@ -1,6 +1,7 @@
 #![deny(clippy::all)]
 use httpmock::prelude::*;
 use rusplay::RugplayClient;
+use rusplay::models::HopiumStatus;
 
 #[tokio::test]
 async fn test_get_top_coins_mocked() {
@@ -317,6 +318,11 @@ async fn test_get_hopium_details_mocked() {
 
     let response = client.get_hopium_details(101).await.unwrap();
     assert_eq!(response.question.id, 101);
+    assert_eq!(response.question.status, HopiumStatus::ACTIVE);
+    assert_eq!(
+        response.question.resolution_date.unwrap().to_rfc3339(),
+        "2025-07-25T10:39:19.612+00:00"
+    );
     assert_eq!(response.question.creator.username, "eluskulus");
     assert_eq!(
         response.question.recent_bets.unwrap()[0].user.username,
@@ -324,3 +330,234 @@ async fn test_get_hopium_details_mocked() {
     );
     mock.assert();
 }
+
+#[tokio::test]
+async fn test_whoami_mocked() {
+    let server = MockServer::start_async().await;
+
+    let session_mock = server
+        .mock_async(|when, then| {
+            when.method(GET).path("/api/auth/get-session");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(
+                    r#"
+            {
+              "session": {
+                "expiresAt": "2025-07-25T10:39:19.612Z",
+                "userId": "1"
+              },
+              "user": {
+                "id": "1",
+                "name": "FaceDev",
+                "username": "facedev",
+                "image": "avatars/1.jpg"
+              }
+            }
+            "#,
+                );
+        })
+        .await;
+
+    let portfolio_mock = server
+        .mock_async(|when, then| {
+            when.method(GET).path("/api/portfolio/total");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(
+                    r#"
+            {
+              "baseCurrencyBalance": 47200.5,
+              "totalCoinValue": 100.0,
+              "totalValue": 47300.5,
+              "coinHoldings": [],
+              "currency": "$"
+            }
+            "#,
+                );
+        })
+        .await;
+
+    let client = RugplayClient::new(
+        "mock_token",
+        Some("__Secure-better-auth.session_token=mock".into()),
+        Some(&format!("{}/api/v1", server.base_url())),
+    )
+    .expect("Failed to create client");
+
+    let me = client.whoami().await.unwrap();
+    assert_eq!(me.id, 1);
+    assert_eq!(me.username, "facedev");
+    assert_eq!(me.balance, 47200.5);
+    session_mock.assert();
+    portfolio_mock.assert();
+}
+
+#[tokio::test]
+async fn test_validate_credentials_mocked() {
+    use rusplay::models::CredentialCheck;
+
+    let server = MockServer::start_async().await;
+
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/api/v1/top");
+            then.status(401).body(r#"{"error": "Invalid API key"}"#);
+        })
+        .await;
+
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/api/auth/get-session");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body("null");
+        })
+        .await;
+
+    let base_url = format!("{}/api/v1", server.base_url());
+
+    let client = RugplayClient::new(
+        "bad_token",
+        Some("__Secure-better-auth.session_token=expired".into()),
+        Some(&base_url),
+    )
+    .expect("Failed to create client");
+
+    let status = client.validate_credentials().await.unwrap();
+    assert_eq!(status.token, CredentialCheck::Invalid);
+    assert_eq!(status.cookie, CredentialCheck::Invalid);
+    assert!(!status.is_valid());
+
+    let client =
+        RugplayClient::new("bad_token", None, Some(&base_url)).expect("Failed to create client");
+
+    let status = client.validate_credentials().await.unwrap();
+    assert_eq!(status.cookie, CredentialCheck::Missing);
+}
+
+#[tokio::test]
+async fn test_persistent_cookie_store_mocked() {
+    use rusplay::cookies::{PersistentCookieStore, SESSION_COOKIE_NAME};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    let server = MockServer::start_async().await;
+    let base_url = format!("{}/api/v1", server.base_url());
+
+    // Rugplay refreshes the session cookie on claim
+    let claim_mock = server
+        .mock_async(|when, then| {
+            when.method(GET)
+                .path("/api/rewards/claim")
+                .header("cookie", format!("{SESSION_COOKIE_NAME}=old"));
+            then.status(200)
+                .header("content-type", "application/json")
+                .header(
+                    "set-cookie",
+                    format!("{SESSION_COOKIE_NAME}=new; Path=/; Max-Age=600; HttpOnly"),
+                )
+                .body(
+                    r#"
+            {
+              "canClaim": false,
+              "rewardAmount": 1800,
+              "baseReward": 1800,
+              "prestigeBonus": 0,
+              "prestigeLevel": 0,
+              "timeRemaining": 3600000,
+              "nextClaimTime": null,
+              "totalRewardsClaimed": 3,
+              "lastRewardClaim": null,
+              "loginStreak": 3
+            }
+            "#,
+                );
+        })
+        .await;
+
+    let path = std::env::temp_dir().join(format!("rusplay-cookies-{}.json", server.port()));
+    let _ = std::fs::remove_file(&path);
+
+    let store = Arc::new(PersistentCookieStore::open(&path).unwrap());
+    store
+        .add_cookie_str(
+            &format!("{SESSION_COOKIE_NAME}=old"),
+            &base_url.parse().unwrap(),
+        )
+        .unwrap();
+
+    let client = RugplayClient::new_with_cookie_store("mock_token", store, Some(&base_url))
+        .expect("Failed to create client");
+    assert!(
+        client
+            .check_session_expiry(Duration::from_secs(3600))
+            .is_ok()
+    );
+
+    let info = client.get_claim_info().await.unwrap();
+    assert_eq!(info.time_remaining, Duration::from_secs(3600));
+    assert!(info.next_claim_time.is_none());
+    claim_mock.assert();
+
+    // The refreshed cookie was saved with its expiry
+    let reopened = PersistentCookieStore::open(&path).unwrap();
+    let cookie = reopened.get(SESSION_COOKIE_NAME).unwrap();
+    assert_eq!(cookie.value.expose(), "new");
+    assert!(cookie.expires.is_some());
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    let expiring = client
+        .check_session_expiry(Duration::from_secs(3600))
+        .unwrap_err();
+    assert_eq!(expiring.name, SESSION_COOKIE_NAME);
+    assert!(expiring.remaining <= Duration::from_secs(600));
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn test_cookie_store_checked_when_used() {
+    use rusplay::RugplayError;
+    use rusplay::cookies::{PersistentCookieStore, SESSION_COOKIE_NAME};
+    use std::sync::Arc;
+
+    let server = MockServer::start_async().await;
+    let base_url = format!("{}/api/v1", server.base_url());
+    let session_mock = server
+        .mock_async(|when, then| {
+            when.method(GET).path("/api/auth/get-session");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body("null");
+        })
+        .await;
+
+    let path = std::env::temp_dir().join(format!("rusplay-cookies-{}.json", server.port()));
+    let _ = std::fs::remove_file(&path);
+    let store = Arc::new(PersistentCookieStore::open(&path).unwrap());
+    let client =
+        RugplayClient::new_with_cookie_store("mock_token", store.clone(), Some(&base_url)).unwrap();
+
+    assert!(matches!(
+        client.get_session().await,
+        Err(RugplayError::NoCookie)
+    ));
+
+    // Cookies added after the client was created are used
+    store
+        .add_cookie_str(
+            &format!("{SESSION_COOKIE_NAME}=later"),
+            &base_url.parse().unwrap(),
+        )
+        .unwrap();
+    assert!(client.get_session().await.unwrap().is_none());
+    session_mock.assert();
+
+    std::fs::remove_file(&path).unwrap();
+}
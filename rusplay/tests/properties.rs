@@ -0,0 +1,318 @@
+#![deny(clippy::all)]
+//! Property tests pushing arbitrary and mutated Rugplay-like JSON through every response model
+use proptest::prelude::*;
+use proptest::sample::Index;
+use rusplay::models::*;
+use rusplay::schema::UnknownFields;
+use serde::de::DeserializeOwned;
+use serde_json::{Value, json};
+
+type Check = fn(&str) -> Result<Vec<String>, serde_json::Error>;
+
+/// Deserializes `json` as `T` and walks it for unknown fields
+fn check<T: DeserializeOwned + UnknownFields>(
+    json: &str,
+) -> Result<Vec<String>, serde_json::Error> {
+    let value: T = serde_json::from_str(json)?;
+    let mut unknown = Vec::new();
+    value.unknown_fields("", &mut unknown);
+    Ok(unknown)
+}
+
+const MODELS: &[(&str, &str, Check)] = &[
+    (
+        "top",
+        include_str!("fixtures/models/top.json"),
+        check::<TopCoinsResponse>,
+    ),
+    (
+        "market",
+        include_str!("fixtures/models/market.json"),
+        check::<MarketResponse>,
+    ),
+    (
+        "coin",
+        include_str!("fixtures/models/coin.json"),
+        check::<CoinDetailsResponse>,
+    ),
+    (
+        "holders",
+        include_str!("fixtures/models/holders.json"),
+        check::<HoldersResponse>,
+    ),
+    (
+        "hopium",
+        include_str!("fixtures/models/hopium.json"),
+        check::<HopiumResponse>,
+    ),
+    (
+        "hopium_details",
+        include_str!("fixtures/models/hopium_details.json"),
+        check::<HopiumDetailsResponse>,
+    ),
+    (
+        "claim",
+        include_str!("fixtures/models/claim.json"),
+        check::<ClaimInfo>,
+    ),
+    (
+        "reward",
+        include_str!("fixtures/models/reward.json"),
+        check::<RewardStatus>,
+    ),
+    (
+        "trade",
+        include_str!("fixtures/models/trade.json"),
+        check::<TradeResponse>,
+    ),
+    (
+        "recent_trades",
+        include_str!("fixtures/models/recent_trades.json"),
+        check::<RecentTradeResponse>,
+    ),
+    (
+        "session",
+        include_str!("fixtures/models/session.json"),
+        check::<SessionResponse>,
+    ),
+    (
+        "portfolio",
+        include_str!("fixtures/models/portfolio.json"),
+        check::<PortfolioTotal>,
+    ),
+];
+
+/// Field names of the Rugplay responses, so generated objects hit real fields
+const FIELDS: &[&str] = &[
+    "coins",
+    "symbol",
+    "name",
+    "icon",
+    "price",
+    "change24h",
+    "marketCap",
+    "volume24h",
+    "total",
+    "page",
+    "limit",
+    "totalPages",
+    "currentPrice",
+    "createdAt",
+    "creatorName",
+    "coin",
+    "candlestickData",
+    "volumeData",
+    "timeframe",
+    "id",
+    "time",
+    "open",
+    "high",
+    "low",
+    "close",
+    "volume",
+    "coinSymbol",
+    "totalHolders",
+    "circulatingSupply",
+    "poolInfo",
+    "holders",
+    "rank",
+    "userId",
+    "quantity",
+    "questions",
+    "status",
+    "creator",
+    "question",
+    "canClaim",
+    "timeRemaining",
+    "totalCost",
+    "type",
+    "trades",
+    "session",
+    "user",
+    "token",
+    "expiresAt",
+];
+
+/// JSON pointers of every scalar in `value`
+fn leaves(value: &Value, path: String, out: &mut Vec<String>) {
+    match value {
+        Value::Object(map) => {
+            for (k, v) in map {
+                leaves(v, format!("{path}/{k}"), out);
+            }
+        }
+        Value::Array(items) => {
+            for (i, v) in items.iter().enumerate() {
+                leaves(v, format!("{path}/{i}"), out);
+            }
+        }
+        _ => out.push(path),
+    }
+}
+
+/// Deserialization of complete JSON either succeeds or returns an error, never panics or
+/// reports a truncated input
+///
+/// Invalid enum values are reported by serde_json as syntax errors, so both data and syntax
+/// errors are accepted.
+fn assert_clean(name: &str, json: &str, result: Result<Vec<String>, serde_json::Error>) {
+    if let Err(e) = result {
+        assert!(
+            e.is_data() || e.is_syntax(),
+            "{name}: unexpected {e:?} for {json}"
+        );
+    }
+}
+
+fn arb_json() -> impl Strategy<Value = Value> {
+    let leaf = prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::from),
+        any::<i64>().prop_map(Value::from),
+        any::<u64>().prop_map(Value::from),
+        any::<f64>()
+            .prop_filter("JSON numbers are finite", |f| f.is_finite())
+            .prop_map(Value::from),
+        "[a-zA-Z0-9 :.-]{0,16}".prop_map(Value::from),
+    ];
+    let key = prop_oneof![
+        prop::sample::select(FIELDS).prop_map(str::to_string),
+        "[a-z]{1,8}",
+    ];
+
+    leaf.prop_recursive(4, 64, 8, move |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 0..6).prop_map(Value::Array),
+            prop::collection::btree_map(key.clone(), inner, 0..10)
+                .prop_map(|m| Value::Object(m.into_iter().collect())),
+        ]
+    })
+}
+
+/// Values that stress numeric fields: nulls, negatives, huge and out of range numbers
+fn edge_value() -> impl Strategy<Value = Value> {
+    prop_oneof![
+        Just(Value::Null),
+        (-1e12..0.0f64).prop_map(Value::from),
+        (1e15..1e300f64).prop_map(Value::from),
+        Just(json!(f64::MAX)),
+        Just(json!(-0.0)),
+        Just(json!(0.5)),
+        Just(json!(i64::MIN)),
+        Just(json!(u64::MAX)),
+        Just(json!("")),
+        Just(json!(true)),
+        Just(json!([])),
+        Just(json!({})),
+    ]
+}
+
+#[test]
+fn test_fixtures_parse_without_unknown_fields() {
+    for (name, json, check) in MODELS {
+        let unknown = check(json).unwrap_or_else(|e| panic!("{name}: {e}"));
+        assert!(unknown.is_empty(), "{name}: {unknown:?}");
+    }
+}
+
+#[test]
+fn test_every_leaf_nulled() {
+    for (name, json, check) in MODELS {
+        let value: Value = serde_json::from_str(json).unwrap();
+        let mut paths = Vec::new();
+        leaves(&value, String::new(), &mut paths);
+
+        for path in paths {
+            let mut mutated = value.clone();
+            *mutated.pointer_mut(&path).unwrap() = Value::Null;
+            let text = mutated.to_string();
+            assert_clean(name, &text, check(&text));
+        }
+    }
+}
+
+proptest! {
+    #[test]
+    fn arbitrary_json_fails_cleanly(value in arb_json()) {
+        let text = value.to_string();
+        for (name, _, check) in MODELS {
+            assert_clean(name, &text, check(&text));
+        }
+    }
+
+    #[test]
+    fn arbitrary_bytes_never_panic(bytes in prop::collection::vec(any::<u8>(), 0..256)) {
+        let text = String::from_utf8_lossy(&bytes);
+        for (_, _, check) in MODELS {
+            let _ = check(&text);
+        }
+    }
+
+    #[test]
+    fn mutated_fixtures_fail_cleanly(
+        model in 0..MODELS.len(),
+        leaf in any::<Index>(),
+        replacement in edge_value(),
+    ) {
+        let (name, json, check) = MODELS[model];
+        let mut value: Value = serde_json::from_str(json).unwrap();
+        let mut paths = Vec::new();
+        leaves(&value, String::new(), &mut paths);
+        let path = leaf.get(&paths);
+
+        let target = value.pointer_mut(path).unwrap();
+        let was_float = target.is_f64();
+        *target = replacement.clone();
+        let text = value.to_string();
+        let result = check(&text);
+
+        // Float fields take any number: negative changes, huge market caps...
+        if was_float && replacement.is_number() {
+            prop_assert!(result.is_ok(), "{name}{path} = {replacement}: {result:?}");
+        }
+        assert_clean(name, &text, result);
+    }
+
+    #[test]
+    fn coin_numbers_round_trip(
+        change in any::<f64>().prop_filter("finite", |f| f.is_finite()),
+        market_cap in 0.0..f64::MAX,
+    ) {
+        let json = json!({
+            "coins": [{
+                "symbol": "TEST",
+                "name": "Test",
+                "icon": null,
+                "price": 0.0,
+                "change24h": change,
+                "marketCap": market_cap,
+                "volume24h": 0,
+            }]
+        });
+
+        let top: TopCoinsResponse = serde_json::from_value(json).unwrap();
+        prop_assert_eq!(top.coins[0].change24h, change);
+        prop_assert_eq!(top.coins[0].market_cap, market_cap);
+        prop_assert!(top.coins[0].icon.is_none());
+    }
+
+    #[test]
+    fn trade_amounts_round_trip(
+        total_cost in 0.0..1e12f64,
+        coins in 0.0..1e15f64,
+    ) {
+        let json = json!({
+            "success": true,
+            "type": "BUY",
+            "coinsBought": coins,
+            "totalCost": total_cost,
+            "newPrice": 0.1,
+            "priceImpact": 0.5,
+            "newBalance": 0.0,
+        });
+
+        let trade: TradeResponse = serde_json::from_value(json).unwrap();
+        prop_assert_eq!(trade.total_cost, Some(total_cost));
+        prop_assert_eq!(trade.coins_bought, Some(coins));
+    }
+}
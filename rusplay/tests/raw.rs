@@ -0,0 +1,74 @@
+#![deny(clippy::all)]
+use httpmock::prelude::*;
+use rusplay::RugplayClient;
+
+#[tokio::test]
+async fn test_raw_response_mocked() {
+    let server = MockServer::start_async().await;
+    let mock = server
+        .mock_async(|when, then| {
+            when.method(GET).path("/top");
+            then.status(200)
+                .header("content-type", "application/json")
+                .header("x-ratelimit-remaining", "99")
+                .body(
+                    r#"
+            {
+              "coins": [
+                {
+                  "symbol": "TEST",
+                  "name": "Test",
+                  "icon": "coins/test.webp",
+                  "price": 76.52377103,
+                  "change24h": 7652377003.1039,
+                  "marketCap": 76523771031.04,
+                  "volume24h": 13744958.18,
+                  "isLocked": false
+                }
+              ]
+            }
+            "#,
+                );
+        })
+        .await;
+
+    let client = RugplayClient::new("mock_token", None, Some(&server.base_url())).unwrap();
+    let raw = client.with_raw().get_top_coins().await.unwrap();
+
+    assert_eq!(raw.status, 200);
+    assert_eq!(raw.headers["x-ratelimit-remaining"], "99");
+    assert!(raw.body.contains("isLocked"));
+    assert_eq!(raw.json().unwrap()["coins"][0]["isLocked"], false);
+    assert_eq!(raw.value.coins[0].symbol, "TEST");
+    assert_eq!(raw.into_inner().coins[0].extra["isLocked"], false);
+    mock.assert();
+}
+
+#[tokio::test]
+async fn test_get_json_mocked() {
+    let server = MockServer::start_async().await;
+    let mock = server
+        .mock_async(|when, then| {
+            when.method(GET)
+                .path("/api/coin/TEST/comments")
+                .query_param("limit", "2");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(r#"{"comments": [{"id": 1, "content": "to the moon"}]}"#);
+        })
+        .await;
+
+    let client = RugplayClient::new(
+        "mock_token",
+        None,
+        Some(&format!("{}/api/v1", server.base_url())),
+    )
+    .unwrap();
+    let json = client
+        .get_json("../coin/TEST/comments", Some(&[("limit", "2")]))
+        .await
+        .unwrap();
+
+    assert_eq!(json["comments"][0]["content"], "to the moon");
+    mock.assert();
+}
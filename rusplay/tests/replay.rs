@@ -0,0 +1,103 @@
+#![deny(clippy::all)]
+mod common;
+use common::replay_client;
+use httpmock::prelude::*;
+use rusplay::RugplayClient;
+use rusplay::replay::{RecordLayer, ReplayTransport};
+
+#[tokio::test]
+async fn test_replay_fixtures() {
+    let client = replay_client();
+
+    let top = client.get_top_coins().await.unwrap();
+    assert_eq!(top.coins[0].symbol, "TEST");
+
+    let details = client.get_coin_details("TEST", Some("1h")).await.unwrap();
+    assert_eq!(details.coin.symbol, "TEST");
+    assert_eq!(details.timeframe, "1h");
+
+    // Requests without a fixture fail instead of reaching the network
+    let err = client
+        .get_coin_details("TEST", Some("1d"))
+        .await
+        .unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("No fixture for GET /api/v1/coin/TEST?timeframe=1d")
+    );
+}
+
+#[tokio::test]
+async fn test_record_then_replay() {
+    let server = MockServer::start_async().await;
+    let top = server
+        .mock_async(|when, then| {
+            when.method(GET).path("/api/v1/top");
+            then.status(200)
+                .header("content-type", "application/json")
+                .header("set-cookie", "session=secret_cookie")
+                .body(r#"{"coins": []}"#);
+        })
+        .await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/api/auth/get-session");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(
+                    r#"{
+                      "session": {
+                        "id": "s1",
+                        "token": "secret_session_token",
+                        "expiresAt": "2026-01-01T00:00:00.000Z",
+                        "createdAt": "2025-01-01T00:00:00.000Z",
+                        "updatedAt": "2025-01-01T00:00:00.000Z",
+                        "ipAddress": "203.0.113.7",
+                        "userAgent": "secret agent",
+                        "userId": "1"
+                      },
+                      "user": {"id": "1", "name": "Test", "username": "test", "image": null}
+                    }"#,
+                );
+        })
+        .await;
+
+    let dir = std::env::temp_dir().join(format!("rusplay-fixtures-{}", server.port()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let url = format!("{}/api/v1", server.base_url());
+
+    let client = RugplayClient::new("secret_api_key", Some("a=b".into()), Some(&url))
+        .unwrap()
+        .with_layer(RecordLayer::new(&dir));
+    let recorded = client.get_session().await.unwrap().unwrap();
+    client.get_top_coins().await.unwrap();
+    client.get_top_coins().await.unwrap();
+    top.assert_hits(2);
+
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(&dir).unwrap() {
+        let text = std::fs::read_to_string(entry.unwrap().path()).unwrap();
+        for secret in [
+            "secret_api_key",
+            "secret_cookie",
+            "secret_session_token",
+            "203.0.113.7",
+        ] {
+            assert!(!text.contains(secret), "{secret} leaked: {text}");
+        }
+        files.push(text);
+    }
+    assert_eq!(files.len(), 3);
+
+    // Replay works with the server gone
+    drop(server);
+    let offline = RugplayClient::new("other_key", Some("a=b".into()), Some(&url))
+        .unwrap()
+        .with_transport(ReplayTransport::open(&dir).unwrap());
+    let replayed = offline.get_session().await.unwrap().unwrap();
+    assert_eq!(replayed.user.username, recorded.user.username);
+    assert_eq!(replayed.session.expires_at, recorded.session.expires_at);
+    assert!(offline.get_top_coins().await.unwrap().coins.is_empty());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,76 @@
+#![deny(clippy::all)]
+mod common;
+use common::{coin_details, holders_json};
+use rusplay::models::{CoinDetail, HoldersResponse};
+use rusplay::risk::*;
+
+fn holders(quantities: &[(&str, f64)]) -> HoldersResponse {
+    serde_json::from_value(holders_json("TEST", 100_000.0, quantities)).unwrap()
+}
+
+fn coin(creator: &str) -> CoinDetail {
+    coin_details("TEST", 0.1, creator).coin
+}
+
+fn assert_near(actual: f64, expected: f64) {
+    assert!((actual - expected).abs() < 1e-9, "{actual} != {expected}");
+}
+
+#[test]
+fn test_concentration() {
+    let response = holders(&[
+        ("whale", 500_000.0),
+        ("dev", 300_000.0),
+        ("a", 150_000.0),
+        ("b", 50_000.0),
+    ]);
+
+    let c = concentration(&response.holders);
+    assert_near(c.top1_share, 50.0);
+    assert_near(c.top10_share, 100.0);
+    assert_near(c.herfindahl, 0.365);
+    assert_near(c.gini, 0.375);
+
+    let equal = concentration(&holders(&[("a", 10.0), ("b", 10.0)]).holders);
+    assert_near(equal.gini, 0.0);
+    assert_eq!(concentration(&[]).top1_share, 0.0);
+}
+
+#[test]
+fn test_dump_impact() {
+    let response = holders(&[("whale", 500_000.0)]);
+
+    let dump = dump_impact(&response.holders[0], &response.pool_info);
+    // 100k * 500k / (1M + 500k)
+    assert_near(dump.proceeds, 100_000.0 / 3.0);
+    assert_near(dump.pool_drain, 1.0 / 3.0);
+    // 0.1 -> 0.0444...
+    assert_near(dump.price_impact, -500.0 / 9.0);
+}
+
+#[test]
+fn test_rug_risk() {
+    let response = holders(&[
+        ("whale", 500_000.0),
+        ("dev", 300_000.0),
+        ("a", 150_000.0),
+        ("b", 50_000.0),
+    ]);
+
+    let risk = rug_risk(&response, Some(&coin("dev")));
+    assert_eq!(risk.creator_share, Some(30.0));
+    assert_eq!(risk.top_dump.as_ref().unwrap().username, "whale");
+    // 35 * 1/3 + 25 * 0.3 + 20 * 1 + 10 * 0.365 + 10 * 0.375
+    assert_near(risk.score, 46.566_666_666_666_67);
+    assert_eq!(risk.level, RiskLevel::Medium);
+
+    let unknown_creator = rug_risk(&response, None);
+    assert_eq!(unknown_creator.creator_share, None);
+    assert!(unknown_creator.score < risk.score);
+
+    let spread: Vec<(String, f64)> = (0..50).map(|i| (format!("user{i}"), 100.0)).collect();
+    let spread: Vec<(&str, f64)> = spread.iter().map(|(u, q)| (u.as_str(), *q)).collect();
+    let safe = rug_risk(&holders(&spread), Some(&coin("nobody")));
+    assert_eq!(safe.creator_share, Some(0.0));
+    assert_eq!(safe.level, RiskLevel::Low);
+}
@@ -0,0 +1,124 @@
+#![deny(clippy::all)]
+mod common;
+use common::Capture;
+use httpmock::prelude::*;
+use rusplay::schema::SchemaMode;
+use rusplay::{RugplayClient, RugplayError};
+
+const DRIFTED_HOLDERS: &str = r#"
+{
+  "coinSymbol": "TEST",
+  "totalHolders": 50,
+  "circulatingSupply": 1000000000,
+  "poolInfo": {
+    "coinAmount": 114176.23963001,
+    "baseCurrencyAmount": 8758389.68983547,
+    "currentPrice": 76.70938996,
+    "feePercent": 0.3
+  },
+  "holders": [
+    {
+      "rank": 1,
+      "userId": 1,
+      "username": "facedev",
+      "name": "FaceDev",
+      "image": "avatars/1.jpg",
+      "quantity": 999883146.4679264,
+      "percentage": 99.98831464679265,
+      "liquidationValue": 4368219.41924125,
+      "isCreator": true
+    }
+  ],
+  "updatedAt": "2025-06-25T10:39:19.613Z"
+}
+"#;
+
+async fn drifted_server() -> MockServer {
+    let server = MockServer::start_async().await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/holders/TEST");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(DRIFTED_HOLDERS);
+        })
+        .await;
+    server
+}
+
+#[tokio::test]
+async fn test_lenient_mode_keeps_unknown_fields() {
+    let server = drifted_server().await;
+    let capture = Capture::default();
+    let _guard = capture.set_default();
+
+    let client = RugplayClient::new("mock_token", None, Some(&server.base_url())).unwrap();
+    let response = client.get_holders("TEST", None).await.unwrap();
+
+    assert_eq!(response.extra["updatedAt"], "2025-06-25T10:39:19.613Z");
+    assert_eq!(response.pool_info.extra["feePercent"], 0.3);
+    assert_eq!(response.holders[0].extra["isCreator"], true);
+
+    let logs = capture.logs();
+    let warning = logs
+        .lines()
+        .find(|l| l.contains("WARN") && l.contains("schema drifted"))
+        .expect("No schema drift warning");
+    assert!(warning.contains("updatedAt"), "{warning}");
+    assert!(warning.contains("poolInfo.feePercent"), "{warning}");
+    assert!(warning.contains("holders[0].isCreator"), "{warning}");
+}
+
+#[tokio::test]
+async fn test_strict_mode_rejects_unknown_fields() {
+    let server = drifted_server().await;
+
+    let client = RugplayClient::new("mock_token", None, Some(&server.base_url()))
+        .unwrap()
+        .with_schema_mode(SchemaMode::Strict);
+    let err = client.get_holders("TEST", None).await.unwrap_err();
+
+    let RugplayError::UnknownFields { endpoint, fields } = err else {
+        panic!("Expected UnknownFields, got {err:?}");
+    };
+    assert_eq!(endpoint, "holders");
+    assert_eq!(
+        fields,
+        ["updatedAt", "poolInfo.feePercent", "holders[0].isCreator"]
+    );
+}
+
+#[tokio::test]
+async fn test_missing_optional_fields_default() {
+    let server = MockServer::start_async().await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/top");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(
+                    r#"
+            {
+              "coins": [
+                {
+                  "symbol": "TEST",
+                  "name": "Test",
+                  "price": 76.52377103,
+                  "change24h": 7652377003.1039,
+                  "marketCap": 76523771031.04,
+                  "volume24h": 13744958.18
+                }
+              ]
+            }
+            "#,
+                );
+        })
+        .await;
+
+    let client = RugplayClient::new("mock_token", None, Some(&server.base_url()))
+        .unwrap()
+        .with_schema_mode(SchemaMode::Strict);
+    let response = client.get_top_coins().await.unwrap();
+    assert!(response.coins[0].icon.is_none());
+    assert!(response.coins[0].extra.is_empty());
+}
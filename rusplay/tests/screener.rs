@@ -0,0 +1,143 @@
+#![deny(clippy::all)]
+mod common;
+use chrono::{Duration, Utc};
+use common::holders_json;
+use httpmock::prelude::*;
+use rusplay::RugplayClient;
+use rusplay::expr::ExprError;
+use rusplay::screener::*;
+use serde_json::{Value, json};
+
+fn coin(symbol: &str, market_cap: f64, change24h: f64, age_minutes: i64) -> Value {
+    json!({
+        "symbol": symbol,
+        "name": symbol,
+        "icon": null,
+        "currentPrice": 1.0,
+        "marketCap": market_cap,
+        "volume24h": 0.0,
+        "change24h": change24h,
+        "createdAt": Utc::now() - Duration::minutes(age_minutes),
+        "creatorName": "creator",
+    })
+}
+
+/// Holders with a single one owning `top_share`% of the supply
+fn holders(symbol: &str, top_share: f64) -> Value {
+    holders_json(symbol, 100_000.0, &[("whale", top_share * 10_000.0)])
+}
+
+#[test]
+fn test_screener_validates_variables() {
+    assert!(Screener::new("market_cap > 1000 and top10_share < 60%").is_ok());
+    assert_eq!(
+        Screener::new("marketcap > 1000").unwrap_err(),
+        ExprError::UnknownVariable("marketcap".to_string())
+    );
+    assert!(matches!(
+        Screener::new("market_cap >").unwrap_err(),
+        ExprError::Parse { .. }
+    ));
+    assert!(
+        Screener::new("age < 1h")
+            .unwrap()
+            .with_sort("volume", SortOrder::Desc)
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn test_screen_market() {
+    let server = MockServer::start_async().await;
+    let pages = [
+        vec![
+            coin("AAA", 50_000.0, 80.0, 30),
+            coin("BBB", 5_000.0, 500.0, 30),
+        ],
+        vec![
+            coin("CCC", 20_000.0, 120.0, 30),
+            coin("DDD", 30_000.0, 200.0, 60),
+        ],
+    ];
+    for (i, coins) in pages.iter().enumerate() {
+        let page = (i + 1).to_string();
+        let body =
+            json!({"coins": coins, "total": 4, "page": i + 1, "limit": 100, "totalPages": 2});
+        server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path("/market")
+                    .query_param("limit", "100")
+                    .query_param("page", &page);
+                then.status(200).json_body(body);
+            })
+            .await;
+    }
+    let mut holder_mocks = Vec::new();
+    for (symbol, top_share) in [("AAA", 40.0), ("CCC", 90.0), ("DDD", 50.0)] {
+        let body = holders(symbol, top_share);
+        holder_mocks.push(
+            server
+                .mock_async(|when, then| {
+                    when.method(GET).path(format!("/holders/{symbol}"));
+                    then.status(200).json_body(body);
+                })
+                .await,
+        );
+    }
+
+    let client = RugplayClient::new("mock_token", None, Some(&server.base_url())).unwrap();
+    let screener = Screener::new("market_cap > 10000 and top10_share < 0.6 and age < 2h")
+        .unwrap()
+        .with_sort("change24h", SortOrder::Desc)
+        .unwrap();
+
+    let results = client.screen(&screener).await.unwrap();
+    let symbols: Vec<&str> = results.iter().map(|r| r.coin.symbol.as_str()).collect();
+    assert_eq!(symbols, ["DDD", "AAA"]);
+    assert_eq!(results[0].sort_value, Some(200.0));
+    assert!(results[0].holders.is_some() && results[0].details.is_none());
+    // BBB is filtered out by its market cap, before fetching its holders
+    for mock in &holder_mocks {
+        mock.assert_hits(1);
+    }
+
+    let limited = client.screen(&screener.with_limit(1)).await.unwrap();
+    assert_eq!(limited.len(), 1);
+    assert_eq!(limited[0].coin.symbol, "DDD");
+}
+
+#[tokio::test]
+async fn test_screen_skips_failing_coins() {
+    let server = MockServer::start_async().await;
+    let body = json!({
+        "coins": [coin("AAA", 50_000.0, 80.0, 30), coin("BBB", 50_000.0, 80.0, 30)],
+        "total": 2, "page": 1, "limit": 100, "totalPages": 1,
+    });
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/market");
+            then.status(200).json_body(body);
+        })
+        .await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/holders/AAA");
+            then.status(200).json_body(holders("AAA", 40.0));
+        })
+        .await;
+    let failing = server
+        .mock_async(|when, then| {
+            when.method(GET).path("/holders/BBB");
+            then.status(500);
+        })
+        .await;
+
+    let client = RugplayClient::new("mock_token", None, Some(&server.base_url())).unwrap();
+    let screener = Screener::new("top10_share < 0.6").unwrap();
+
+    let results = client.screen(&screener).await.unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].coin.symbol, "AAA");
+    failing.assert();
+}
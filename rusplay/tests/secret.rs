@@ -0,0 +1,75 @@
+#![deny(clippy::all)]
+mod common;
+use common::Capture;
+use httpmock::prelude::*;
+use rusplay::{ClientCreateError, RugplayClient, Secret};
+
+const TOKEN: &str = "rgpl_supersecrettoken";
+const COOKIE: &str = "__Secure-better-auth.session_token=supersecretcookie";
+
+#[test]
+fn test_secret_is_redacted() {
+    let secret = Secret::new(TOKEN);
+    assert_eq!(secret.expose(), TOKEN);
+    assert!(!format!("{secret:?}").contains(TOKEN));
+    assert!(!format!("{secret}").contains(TOKEN));
+
+    let parsed: Secret = serde_json::from_str(&format!("\"{TOKEN}\"")).unwrap();
+    assert_eq!(parsed, secret);
+}
+
+#[test]
+fn test_client_debug_is_redacted() {
+    let client = RugplayClient::new(TOKEN, Some(COOKIE.into()), None).unwrap();
+    let debug = format!("{client:?}");
+    assert!(!debug.contains("supersecret"), "{debug}");
+
+    assert!(matches!(
+        RugplayClient::new("bad\ntoken", None, None),
+        Err(ClientCreateError::InvalidToken)
+    ));
+}
+
+#[test]
+fn test_stored_cookie_value_is_redacted() {
+    use rusplay::cookies::StoredCookie;
+
+    let cookie = StoredCookie {
+        name: "session".into(),
+        value: Secret::new("supersecretcookie"),
+        domain: "rugplay.com".into(),
+        path: "/".into(),
+        secure: true,
+        expires: None,
+    };
+    let debug = format!("{cookie:?}");
+    assert!(!debug.contains("supersecret"), "{debug}");
+
+    // The file keeps the actual value
+    let json = serde_json::to_string(&cookie).unwrap();
+    assert!(json.contains("\"value\":\"supersecretcookie\""), "{json}");
+    assert_eq!(serde_json::from_str::<StoredCookie>(&json).unwrap(), cookie);
+}
+
+#[tokio::test]
+async fn test_tracing_output_has_no_secrets() {
+    let server = MockServer::start_async().await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/top");
+            then.status(500).body("Internal error");
+        })
+        .await;
+
+    let capture = Capture::default();
+    let _guard = capture.set_default();
+
+    let client = RugplayClient::new(TOKEN, Some(COOKIE.into()), Some(&server.base_url())).unwrap();
+    let err = client.get_top_coins().await.unwrap_err();
+
+    tracing::error!("Request failed: {err} {err:?} {client:?}");
+
+    let logs = capture.logs();
+    assert!(!logs.is_empty());
+    assert!(!logs.contains("supersecret"), "{logs}");
+}
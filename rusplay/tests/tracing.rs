@@ -0,0 +1,100 @@
+#![deny(clippy::all)]
+mod common;
+use common::Capture;
+use httpmock::prelude::*;
+use rusplay::{RugplayClient, RugplayError};
+
+#[tokio::test]
+async fn test_request_span_fields() {
+    let server = MockServer::start_async().await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/top");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(r#"{"coins": []}"#);
+        })
+        .await;
+
+    let capture = Capture::default();
+    let _guard = capture.set_default();
+
+    let client = RugplayClient::new("mock_token", None, Some(&server.base_url())).unwrap();
+    client.get_top_coins().await.unwrap();
+
+    let logs = capture.logs();
+    let line = logs
+        .lines()
+        .find(|l| l.contains("Request finished"))
+        .expect("No request event");
+    assert!(line.contains("rugplay_request{"), "{line}");
+    assert!(line.contains("endpoint=\"top\""), "{line}");
+    assert!(line.contains("method=GET"), "{line}");
+    assert!(line.contains("status=200"), "{line}");
+    assert!(line.contains("size=13"), "{line}");
+    assert!(line.contains("retries=0"), "{line}");
+    assert!(line.contains("latency_ms="), "{line}");
+}
+
+#[tokio::test]
+async fn test_deserialize_failure_logs_body() {
+    let server = MockServer::start_async().await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/top");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(r#"{"renamedCoins": []}"#);
+        })
+        .await;
+
+    let capture = Capture::default();
+    let _guard = capture.set_default();
+
+    let client = RugplayClient::new("mock_token", None, Some(&server.base_url())).unwrap();
+    let err = client.get_top_coins().await.unwrap_err();
+    assert!(matches!(err, RugplayError::Deserialize { .. }));
+
+    let logs = capture.logs();
+    assert!(
+        logs.lines()
+            .any(|l| l.contains("DEBUG") && l.contains("renamedCoins")),
+        "{logs}"
+    );
+}
+
+#[tokio::test]
+async fn test_logged_body_hides_credentials() {
+    let server = MockServer::start_async().await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/api/auth/get-session");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(r#"{"session": {"token": "session_secret"}, "user": 1}"#);
+        })
+        .await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/api/rewards/claim");
+            then.status(200).body(r#"{"token": "claim_secret""#);
+        })
+        .await;
+
+    let capture = Capture::default();
+    let _guard = capture.set_default();
+
+    let client = RugplayClient::new(
+        "mock_token",
+        Some("session=abc".into()),
+        Some(&format!("{}/api/v1", server.base_url())),
+    )
+    .unwrap();
+    client.get_session().await.unwrap_err();
+    client.get_claim_info().await.unwrap_err();
+
+    let logs = capture.logs();
+    assert!(logs.contains(r#"{"token":"[REDACTED]"}"#), "{logs}");
+    assert!(!logs.contains("session_secret"), "{logs}");
+    assert!(!logs.contains("claim_secret"), "{logs}");
+}
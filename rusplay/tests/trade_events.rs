@@ -0,0 +1,124 @@
+#![deny(clippy::all)]
+mod common;
+use common::{mock_coin, trade_json};
+use httpmock::prelude::*;
+use rusplay::RugplayClient;
+use rusplay::models::Trade;
+use rusplay::trade_events::*;
+use serde_json::json;
+
+fn trade(symbol: &str, side: &str, username: &str, total_value: f64, timestamp: u64) -> Trade {
+    common::trade(symbol, side, username, 1.0, total_value, timestamp)
+}
+
+#[tokio::test]
+async fn test_detect_trade_events() {
+    let server = MockServer::start_async().await;
+    mock_coin(&server, "TEST", "dev", 100_000.0).await;
+    mock_coin(&server, "DUMP", "someone", 20_000.0).await;
+
+    let client = RugplayClient::new("mock_token", None, Some(&server.base_url())).unwrap();
+    let mut detector = TradeDetector::new(client).with_whale_threshold(Some(10_000.0));
+    let mut receiver = detector.subscribe();
+
+    let events = detector
+        .process([
+            trade("TEST", "BUY", "whale", 15_000.0, 1),
+            trade("TEST", "SELL", "dev", 500.0, 2),
+            trade("DUMP", "SELL", "bob", 5_000.0, 3),
+            trade("TEST", "SELL", "alice", 100.0, 4),
+        ])
+        .await;
+
+    assert_eq!(events.len(), 3, "{events:?}");
+    assert!(matches!(&events[0], TradeEvent::Whale(t) if t.username == "whale"));
+    assert!(matches!(&events[1], TradeEvent::CreatorSell(t) if t.username == "dev"));
+    match &events[2] {
+        TradeEvent::LiquidityDrain { trade, pool_drain } => {
+            assert_eq!(trade.coin_symbol, "DUMP");
+            // 5k out of 20k + 5k
+            assert_eq!(*pool_drain, 0.2);
+        }
+        other => panic!("{other:?}"),
+    }
+
+    for event in &events {
+        let received = receiver.try_recv().unwrap();
+        assert_eq!(received.trade().timestamp, event.trade().timestamp);
+    }
+    assert!(receiver.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn test_drain_of_several_sells() {
+    let server = MockServer::start_async().await;
+    mock_coin(&server, "DUMP", "someone", 20_000.0).await;
+
+    let client = RugplayClient::new("mock_token", None, Some(&server.base_url())).unwrap();
+    let mut detector = TradeDetector::new(client).with_whale_threshold(None);
+
+    let events = detector
+        .process([
+            trade("DUMP", "SELL", "bob", 5_000.0, 1),
+            trade("DUMP", "BUY", "alice", 1_000.0, 2),
+            trade("DUMP", "SELL", "bob", 5_000.0, 3),
+        ])
+        .await;
+
+    let drains: Vec<f64> = events
+        .iter()
+        .map(|event| match event {
+            TradeEvent::LiquidityDrain { pool_drain, .. } => *pool_drain,
+            other => panic!("{other:?}"),
+        })
+        .collect();
+    // The pool before the last sell had 20k + 5k, and 24k + 5k before the first one
+    assert_eq!(drains, [5_000.0 / 29_000.0, 0.2]);
+}
+
+#[tokio::test]
+async fn test_failing_coin_does_not_stop_detection() {
+    let server = MockServer::start_async().await;
+    mock_coin(&server, "TEST", "dev", 100_000.0).await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path_contains("/BROKEN");
+            then.status(500);
+        })
+        .await;
+
+    let client = RugplayClient::new("mock_token", None, Some(&server.base_url())).unwrap();
+    let mut detector = TradeDetector::new(client).with_whale_threshold(Some(10_000.0));
+
+    let events = detector
+        .process([
+            trade("BROKEN", "SELL", "whale", 50_000.0, 1),
+            trade("TEST", "SELL", "dev", 500.0, 2),
+        ])
+        .await;
+
+    // The creator and pool of BROKEN are unknown, but the other events are raised
+    assert_eq!(events.len(), 2, "{events:?}");
+    assert!(matches!(&events[0], TradeEvent::Whale(t) if t.coin_symbol == "BROKEN"));
+    assert!(matches!(&events[1], TradeEvent::CreatorSell(t) if t.coin_symbol == "TEST"));
+}
+
+#[tokio::test]
+async fn test_first_poll_only_records_trades() {
+    let server = MockServer::start_async().await;
+    let recent = json!({"trades": [trade_json("TEST", "BUY", "whale", 0.1, 1_000_000.0, 1)]});
+    let mock = server
+        .mock_async(|when, then| {
+            when.method(GET).path("/trades/recent");
+            then.status(200).json_body(recent);
+        })
+        .await;
+
+    let client = RugplayClient::new("mock_token", None, Some(&server.base_url())).unwrap();
+    let mut detector = TradeDetector::new(client);
+
+    assert!(detector.poll().await.unwrap().is_empty());
+    // Same trades as the previous poll
+    assert!(detector.poll().await.unwrap().is_empty());
+    mock.assert_hits(2);
+}
@@ -0,0 +1,82 @@
+#![deny(clippy::all)]
+use bytes::Bytes;
+use httpmock::prelude::*;
+use rusplay::metrics::ErrorKind;
+use rusplay::transport::{HttpRequest, HttpResponse};
+use rusplay::{RugplayClient, RugplayError};
+use std::convert::Infallible;
+use std::time::Duration;
+use tower::timeout::TimeoutLayer;
+use tower::util::MapRequestLayer;
+
+#[tokio::test]
+async fn test_layer_adds_header() {
+    let server = MockServer::start_async().await;
+    let mock = server
+        .mock_async(|when, then| {
+            when.method(GET)
+                .path("/top")
+                .header("authorization", "Bearer mock_token")
+                .header("x-bot", "collector");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(r#"{"coins": []}"#);
+        })
+        .await;
+
+    let client = RugplayClient::new("mock_token", None, Some(&server.base_url()))
+        .unwrap()
+        .with_layer(MapRequestLayer::new(|mut req: HttpRequest| {
+            req.headers_mut()
+                .insert("x-bot", "collector".parse().unwrap());
+            req
+        }));
+
+    client.get_top_coins().await.unwrap();
+    mock.assert();
+}
+
+#[tokio::test]
+async fn test_timeout_layer() {
+    let server = MockServer::start_async().await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/top");
+            then.status(200)
+                .delay(Duration::from_millis(500))
+                .body(r#"{"coins": []}"#);
+        })
+        .await;
+
+    let client = RugplayClient::new("mock_token", None, Some(&server.base_url()))
+        .unwrap()
+        .with_layer(TimeoutLayer::new(Duration::from_millis(50)));
+
+    let err = client.get_top_coins().await.unwrap_err();
+    assert!(matches!(err, RugplayError::Transport(_)), "{err:?}");
+    assert_eq!(err.kind(), ErrorKind::Timeout);
+}
+
+#[tokio::test]
+async fn test_custom_transport() {
+    let transport = tower::service_fn(|req: HttpRequest| async move {
+        let body = match req.uri().path() {
+            "/api/v1/top" => r#"{"coins": []}"#,
+            _ => r#"{"error": "Not found"}"#,
+        };
+        let status = if body.contains("error") { 404 } else { 200 };
+        let mut resp = HttpResponse::new(Bytes::from(body));
+        *resp.status_mut() = reqwest::StatusCode::from_u16(status).unwrap();
+        Ok::<_, Infallible>(resp)
+    });
+
+    let client = RugplayClient::new("mock_token", None, None)
+        .unwrap()
+        .with_transport(transport);
+
+    assert!(client.get_top_coins().await.unwrap().coins.is_empty());
+    let err = client.get_holders("NOPE", None).await.unwrap_err();
+    assert!(
+        matches!(&err, RugplayError::Request(e) if e.status() == Some(reqwest::StatusCode::NOT_FOUND))
+    );
+}